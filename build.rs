@@ -0,0 +1,19 @@
+//! Writes `include/rubiks.h`, the C header of the `ffi` module, when it's built.
+
+fn main() {
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("Error when finding the crate");
+        let config = cbindgen::Config::from_file(format!("{crate_dir}/cbindgen.toml"))
+            .expect("Error when reading cbindgen.toml");
+        // only the functions of the module are for C, not the rest of the crate
+        cbindgen::Builder::new()
+            .with_config(config)
+            .with_src(format!("{crate_dir}/src/ffi.rs"))
+            .generate()
+            .expect("Error when generating the C header")
+            .write_to_file(format!("{crate_dir}/include/rubiks.h"));
+    }
+}
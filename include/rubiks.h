@@ -0,0 +1,83 @@
+/* The cube and the solvers of the rubiks crate, see src/ffi.rs. */
+
+#ifndef RUBIKS_H
+#define RUBIKS_H
+
+/* Don't edit this file, it's written by cbindgen when the ffi feature is built. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * A cube handed to C, created by `rubiks_cube_new`.
+ */
+typedef struct RubiksCube RubiksCube;
+
+/**
+ * Creates a solved cube of size `size`, or returns NULL if `size` is less than 2.
+ */
+struct RubiksCube *rubiks_cube_new(uintptr_t size);
+
+/**
+ * Frees a cube created by `rubiks_cube_new`. Does nothing if `cube` is NULL.
+ *
+ * # Safety
+ *
+ * `cube` must be NULL or a cube created by `rubiks_cube_new` that wasn't freed yet.
+ */
+void rubiks_cube_free(struct RubiksCube *cube);
+
+/**
+ * Applies the turns of `algo`, like `R U R' U'`, to `cube`. Returns false and leaves the cube as
+ * it is if the turns are invalid or turn layers that the cube doesn't have.
+ *
+ * # Safety
+ *
+ * `cube` must be a cube created by `rubiks_cube_new`, and `algo` a NUL-terminated string.
+ */
+bool rubiks_cube_apply(struct RubiksCube *cube, const char *algo);
+
+/**
+ * Returns whether `cube` is solved.
+ *
+ * # Safety
+ *
+ * `cube` must be a cube created by `rubiks_cube_new`.
+ */
+bool rubiks_cube_is_solved(const struct RubiksCube *cube);
+
+/**
+ * Returns the letters of the colors of the facelets of `cube`, see `Cube::facelet_string`. The
+ * string must be freed with `rubiks_string_free`.
+ *
+ * # Safety
+ *
+ * `cube` must be a cube created by `rubiks_cube_new`.
+ */
+char *rubiks_cube_facelets(const struct RubiksCube *cube);
+
+/**
+ * Solves `cube` with the fastest solver for its size (see `search::solve`), giving up after
+ * `time_limit_ms` milliseconds unless it's 0. Returns the turns of the solution, like
+ * `R U R' U'`, or NULL if there's none. The string must be freed with `rubiks_string_free`.
+ *
+ * The cube isn't turned, the solution can be applied with `rubiks_cube_apply`.
+ *
+ * # Safety
+ *
+ * `cube` must be a cube created by `rubiks_cube_new`.
+ */
+char *rubiks_solve(const struct RubiksCube *cube, uint64_t time_limit_ms);
+
+/**
+ * Frees a string returned by the functions of this module. Does nothing if `s` is NULL.
+ *
+ * # Safety
+ *
+ * `s` must be NULL or a string returned by this module that wasn't freed yet.
+ */
+void rubiks_string_free(char *s);
+
+#endif  /* RUBIKS_H */
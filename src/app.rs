@@ -1,168 +1,2394 @@
-use std::io::{stdin, stdout, Write};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fs,
+    io::{self, stdout, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers, MouseButton, MouseEventKind,
+    },
+    execute, queue,
+    terminal::{
+        self, Clear, ClearType, DisableLineWrap, EnableLineWrap, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
+};
+
+use rustyline::{
+    completion::Completer, highlight::Highlighter, hint::Hinter, history::DefaultHistory,
+    validate::Validator, Context, Editor, Helper,
+};
+
+use rubiks::{
+    cube::palette::Palette,
+    cube::rendering::{self, CameraView, CubeRender, ImageFit, PixelStyle, RenderMode},
+    cube::{Cube, FaceDir, Turn},
+    search::{
+        self, parallel_idastar, solve, Heuristic, MoveSet, SearchConfig, SearchResult, Solver,
+    },
+};
 
 use crate::{
-    cube::rendering::CubeRender,
-    cube::{Cube, FaceDir, Turn, TurnDir},
-    search::{self, idastar},
+    batch::read_algorithm_file,
+    config::Config,
+    practice::{format_time, SolveHistory},
+    profile::Profile,
+    scramble::ScrambleOptions,
+    session::{SessionEvent, SessionLog},
 };
 
-/// Parses a string to a `FaceDir`.
-///
-/// Trims, ignore case of the input. Returns `None` if the input is not in "UDLRFB".j
-fn string_to_face_dir(s: &String) -> Option<FaceDir> {
-    match s.trim().to_uppercase().as_str() {
-        "U" => Some(FaceDir::Up),
-        "D" => Some(FaceDir::Down),
-        "L" => Some(FaceDir::Left),
-        "R" => Some(FaceDir::Right),
-        "F" => Some(FaceDir::Front),
-        "B" => Some(FaceDir::Back),
-        _ => None,
-    }
-}
-
-/// Takes a list of strings, parses and returns a list of `FaceDir` and `TurnDir` that the list of
-/// strings represents
-///
-/// The format is "face_dir[' or 2]". `face_dir` indicates the face (U, D, L, R, F, or B). "'"
-/// indicates a counter-clockwise turn and "2" indicates a 180-degree turn. Returns None if the
-/// turns are invalid.
-fn parse_algorithm(turns: Vec<&str>) -> Option<Vec<Turn>> {
-    // create a macro to make parsing and return `None` if the element is invalid quicker.
-    macro_rules! parse_or_return {
-        ($e:expr) => {
-            match string_to_face_dir($e) {
-                Some(face_dir) => face_dir,
-                None => return None,
+/// Number of frames drawn for each turn.
+const TURN_FRAMES: usize = 8;
+/// Time between the frames of a turn, and of the other animations.
+const FRAME_DURATION: Duration = Duration::from_millis(20);
+/// Largest number of turns played per second, see `Screen::set_turn_speed`.
+const MAX_TURN_SPEED: f32 = 50.0;
+/// Number of explanations of the last turns shown under the cube while a solution is played, see
+/// `explain_turn`.
+const EXPLANATION_LINES: usize = 6;
+/// Time given to find a solution in the fewest moves mode, like in competitions.
+const FMC_DURATION: Duration = Duration::from_secs(60 * 60);
+/// Keys of the two players of a race (see `Screen::race`) for the up, down, right, left, front
+/// and back faces, turned counterclockwise with Shift like the keys of the app.
+const RACE_KEYS: [[char; 6]; 2] = [
+    ['w', 's', 'd', 'a', 'e', 'q'],
+    ['i', 'k', 'l', 'j', 'o', 'u'],
+];
+/// Number of frames drawn when the view moves to a preset orientation.
+const VIEW_FRAMES: usize = 15;
+/// Time without any input after which the cube spins on itself until the next input, and the
+/// angle it spins by every frame.
+const IDLE_DURATION: Duration = Duration::from_secs(30);
+const SPIN_ANGLE: f32 = 0.02;
+/// Number of times the stickers of a turn of a solution blink before it's played, and how long
+/// they stay on and off.
+const HIGHLIGHT_BLINKS: usize = 2;
+const BLINK_DURATION: Duration = Duration::from_millis(150);
+
+/// File with the colors of the custom palette, see `Palette::from_file`.
+const PALETTE_PATH: &str = "palette.txt";
+
+/// Files that the view and the net of the cube are exported to.
+const VIEW_SVG_PATH: &str = "cube.svg";
+const NET_SVG_PATH: &str = "cube_net.svg";
+/// Size of the stickers in the exported images, in pixels.
+const SVG_STICKER_SIZE: f32 = 40.0;
+/// File that the session is saved to, see `SessionLog::write_csv`.
+const SESSION_LOG_PATH: &str = "session.csv";
+/// File that the solves timed by the app are kept in, see `SolveHistory::save`.
+const SOLVES_PATH: &str = "solves.csv";
+/// File that the lifetime statistics of the user are kept in, see `Profile::save`.
+const PROFILE_PATH: &str = "profile.json";
+/// Number of the last turns shown in the status bar.
+const RECENT_TURNS: usize = 8;
+
+/// Line shown under the status bar, instead of the whole help.
+const HELP_HINT: &str =
+    "? for the keys, the commands, the notation of the turns and the settings, Q to quit";
+/// Lines of help about the keys, shown with "?", see `help_lines`.
+const KEY_HELP: [&str; 30] = [
+    "u/d/r/l/f/b to turn a face clockwise, Shift+u/d/r/l/f/b to turn it counter-clockwise",
+    "Z to undo the last turn or algorithm, Y to redo it, > to save the session to session.csv",
+    "Enter to type an algorithm, drag or arrows to rotate the view, V to switch to the net",
+    "< to apply the algorithms of a file line by line, --reset to start each one from solved",
+    "Scroll or +/- to zoom, O to switch the perspective on or off, [/] to change its field of view",
+    ",/. to roll the view, 1/2/3 to look at the front, at a corner or at the top, I to label faces",
+    "M to scramble, X to reset, C to change the colors (custom ones in palette.txt), Q to quit",
+    "/ to go back to solved by undoing the scramble and the turns since, and show its inverse",
+    "S to solve (Ctrl+C to stop searching), G to solve only turning some faces, e.g. R U",
+    "^ to choose the solver of S, e.g. two-phase or lbl, and $ the heuristic of IDA*",
+    "| to compare solvers in a table, e.g. two-phase idastar:coordinate, Ctrl+C to skip one",
+    "A to play the solution found by S or G, Space to step through it one turn at a time",
+    "; to explain each turn played by A or Space: the heuristic, the distance, the pieces placed",
+    "! to play the solutions as soon as they're found, ~ to set the speed of the turns",
+    "Shift+H for a hint: the next turn to solve the cube, and how many are left if pressed again",
+    "% to show the times of the scrambles solved by hand and their averages, kept in solves.csv",
+    "{ to time a solve of a real cube, with the net of the scramble to check it before starting",
+    "} for the fewest moves challenge: an hour to write a short solution, switching to the inverse",
+    ") to show the profile: the solves, moves and best times of all sessions, the algorithms typed",
+    "@ to type the colors of a real 2x2 or 3x3 face by face and load them, e.g. to solve it",
+    "& to read the colors from a photo of the net or photos of the faces to check and load them",
+    "H to highlight the stickers that aren't solved, * to show the Speffz letters of the stickers",
+    "E to export the view and the net as SVG, K to draw with braille dots for finer edges",
+    "P to find the algorithm that makes the cube from solved, W to compare it before and after turns",
+    "\" to pick a pattern like the checkerboard to apply, or to find a shorter algorithm making it",
+    "T to build the 2x2 depth table, N to show how many turns the 2x2 is from solved",
+    "J to draw arrows on the net of how turns move the pieces, e.g. of a commutator",
+    "( to race another player on the same scramble, each with their own keys on one keyboard",
+    "0 to make the visible faces fill the view instead of the whole cube, # to change the size",
+    ": to type a command like :scramble 20 instead of its key, with Tab to complete it",
+];
+
+/// Commands that can be typed after ":", with the key that does the same. Turns typed with
+/// Enter are always parsed as an algorithm, so the commands can't be mistaken for turns. The
+/// commands that take input (`true`) can be typed with it, e.g. `:size 4`, otherwise they
+/// prompt it like their key.
+const COMMANDS: [(&str, KeyCode, bool); 47] = [
+    ("turn", KeyCode::Enter, true),
+    ("apply", KeyCode::Char('<'), true),
+    ("undo", KeyCode::Char('z'), false),
+    ("redo", KeyCode::Char('y'), false),
+    ("save", KeyCode::Char('>'), false),
+    ("reset", KeyCode::Char('x'), false),
+    ("size", KeyCode::Char('#'), true),
+    ("edit", KeyCode::Char('@'), false),
+    ("photo", KeyCode::Char('&'), true),
+    ("scramble", KeyCode::Char('m'), true),
+    ("unscramble", KeyCode::Char('/'), false),
+    ("solve", KeyCode::Char('s'), false),
+    ("solve-with", KeyCode::Char('g'), true),
+    ("solver", KeyCode::Char('^'), true),
+    ("heuristic", KeyCode::Char('$'), true),
+    ("compare-solvers", KeyCode::Char('|'), true),
+    ("play", KeyCode::Char('a'), false),
+    ("autoplay", KeyCode::Char('!'), false),
+    ("speed", KeyCode::Char('~'), true),
+    ("step", KeyCode::Char(' '), false),
+    ("explain", KeyCode::Char(';'), false),
+    ("hint", KeyCode::Char('H'), false),
+    ("stats", KeyCode::Char('%'), false),
+    ("timer", KeyCode::Char('{'), true),
+    ("fmc", KeyCode::Char('}'), true),
+    ("profile", KeyCode::Char(')'), false),
+    ("race", KeyCode::Char('('), true),
+    ("pattern", KeyCode::Char('p'), false),
+    ("patterns", KeyCode::Char('"'), false),
+    ("compare", KeyCode::Char('w'), true),
+    ("arrows", KeyCode::Char('j'), true),
+    ("table", KeyCode::Char('t'), false),
+    ("distance", KeyCode::Char('n'), false),
+    ("colors", KeyCode::Char('c'), false),
+    ("net", KeyCode::Char('v'), false),
+    ("labels", KeyCode::Char('i'), false),
+    ("braille", KeyCode::Char('k'), false),
+    ("letters", KeyCode::Char('*'), false),
+    ("unsolved", KeyCode::Char('h'), false),
+    ("perspective", KeyCode::Char('o'), false),
+    ("fit", KeyCode::Char('0'), false),
+    ("front", KeyCode::Char('1'), false),
+    ("corner", KeyCode::Char('2'), false),
+    ("top", KeyCode::Char('3'), false),
+    ("export", KeyCode::Char('e'), false),
+    ("quit", KeyCode::Char('q'), false),
+    ("help", KeyCode::Char('?'), false),
+];
+
+/// Returns the key that runs the command named `name`, or `None` if there's no such command.
+pub fn command_key(name: &str) -> Option<KeyCode> {
+    COMMANDS
+        .iter()
+        .find(|(command, _, _)| *command == name)
+        .map(|&(_, key, _)| key)
+}
+
+/// Parses a command typed after ":", e.g. `scramble 20`, into the key that does the same and
+/// the input typed with it, if any. Otherwise returns the message to show instead.
+fn parse_command(line: &str) -> Result<(KeyCode, Option<String>), String> {
+    let line = line.trim();
+    let (name, input) = match line.split_once(char::is_whitespace) {
+        Some((name, input)) => (name, Some(input.trim().to_string())),
+        None => (line, None),
+    };
+    let Some(&(_, key, takes_input)) = COMMANDS.iter().find(|(command, _, _)| *command == name)
+    else {
+        return Err(format!(
+            "Unknown command :{name}, type :help to list the commands"
+        ));
+    };
+    if input.is_some() && !takes_input {
+        return Err(format!(":{name} doesn't take any input"));
+    }
+    Ok((key, input))
+}
+
+/// Returns the lines of the help shown with "?": what the keys do, the commands typed after ":"
+/// with their keys, the notation of the turns, and `settings`, which are the settings in use
+/// with their names.
+fn help_lines(settings: &[(&str, String)]) -> Vec<String> {
+    let mut lines = vec!["Keys:".to_string()];
+    lines.extend(KEY_HELP.iter().map(|line| format!("  {line}")));
+
+    lines.push(String::new());
+    lines.push("Commands, typed after \":\" with their input, e.g. :size 4:".to_string());
+    let commands: Vec<String> = COMMANDS
+        .iter()
+        .map(|(name, key, _)| {
+            let key = match key {
+                KeyCode::Enter => "Enter".to_string(),
+                KeyCode::Char(' ') => "Space".to_string(),
+                KeyCode::Char(c) => c.to_string(),
+                key => format!("{key:?}"),
+            };
+            format!("{:<22}", format!(":{name} ({key})"))
+        })
+        .collect();
+    lines.extend(
+        commands
+            .chunks(4)
+            .map(|commands| format!("  {}", commands.concat().trim_end())),
+    );
+
+    lines.push(String::new());
+    lines.extend(
+        [
+            "Notation of the turns, e.g. R U R' U', or 2R2 U' on bigger cubes:",
+            "  R, U, F, L, D and B turn the right, up, front, left, down and back faces clockwise",
+            "  R' turns the face counter-clockwise, and R2 turns it twice",
+            "  2R turns the second layer from the right face, 3R the third one, and so on",
+            "  M, E and S turn the middle slice of the 3x3 like L, D and F",
+            "  Wide turns and rotations aren't supported, but Rw is R 2R and x is R 2R L' on a 3x3",
+        ]
+        .map(str::to_string),
+    );
+
+    lines.push(String::new());
+    lines.push("Settings:".to_string());
+    lines.extend(
+        settings
+            .iter()
+            .map(|(name, value)| format!("  {name}: {value}")),
+    );
+    lines
+}
+
+/// Commands whose input is turns, which are completed like the turns of `Completion::Turns`.
+const TURN_COMMANDS: [&str; 4] = ["turn", "solve-with", "compare", "arrows"];
+/// Layers of the turns completed by `Completion::Turns`, which are completed with every direction.
+const TURN_LAYERS: [&str; 9] = ["U", "D", "R", "L", "F", "B", "M", "E", "S"];
+
+/// What Tab completes in a prompt, see `InputHelper`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Completion {
+    Nothing,
+    /// The turns of an algorithm, e.g. `R` to `R`, `R'` or `R2`.
+    Turns,
+    /// The name of a command typed after ":", then the turns of the commands that take turns.
+    Commands,
+}
+
+/// Completes the words of the prompts with Tab, see `Completion`. The other features of the
+/// line editor are left as they are.
+struct InputHelper {
+    completion: Completion,
+}
+impl Completer for InputHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos].rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let word = &line[start..pos];
+        let is_turn_command = || {
+            line.split_whitespace()
+                .next()
+                .is_some_and(|command| TURN_COMMANDS.contains(&command))
+        };
+        let candidates = match self.completion {
+            Completion::Commands if start == 0 => COMMANDS
+                .iter()
+                .map(|(name, _, _)| name.to_string())
+                .filter(|name| name.starts_with(word))
+                .collect(),
+            Completion::Commands if !is_turn_command() => Vec::new(),
+            Completion::Commands | Completion::Turns => {
+                // the number of the layer, e.g. the 2 of `2R`, is kept as it is
+                let layer_end = word
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(word.len());
+                let (layer, turn) = word.split_at(layer_end);
+                TURN_LAYERS
+                    .iter()
+                    .flat_map(|face| ["", "'", "2"].map(|dir| format!("{face}{dir}")))
+                    .filter(|candidate| {
+                        candidate.len() >= turn.len()
+                            && candidate[..turn.len()].eq_ignore_ascii_case(turn)
+                    })
+                    .map(|candidate| format!("{layer}{candidate}"))
+                    .collect()
+            }
+            Completion::Nothing => Vec::new(),
+        };
+        Ok((start, candidates))
+    }
+}
+impl Hinter for InputHelper {
+    type Hint = String;
+}
+impl Highlighter for InputHelper {}
+impl Validator for InputHelper {}
+impl Helper for InputHelper {}
+
+/// Returns the turn of the face whose key is `c`: u/d/r/l/f/b turn a face clockwise, and the
+/// same keys with Shift turn it counter-clockwise.
+pub fn face_turn(c: char) -> Option<Turn> {
+    if !"udrlfb".contains(c.to_ascii_lowercase()) {
+        return None;
+    }
+    let turn = if c.is_lowercase() {
+        c.to_uppercase().to_string()
+    } else {
+        format!("{c}'")
+    };
+    Turn::parse_algorithm(vec![turn.as_str()])?.first().copied()
+}
+
+/// Returns the player that `c` is a key of in a race, with the turn it makes, see `RACE_KEYS`.
+fn race_turn(c: char) -> Option<(usize, Turn)> {
+    RACE_KEYS.iter().enumerate().find_map(|(player, keys)| {
+        let face = keys.iter().position(|&key| key == c.to_ascii_lowercase())?;
+        let face = "udrlfb".chars().nth(face)?;
+        let face = if c.is_lowercase() {
+            face
+        } else {
+            face.to_ascii_uppercase()
+        };
+        Some((player, face_turn(face)?))
+    })
+}
+
+/// Parses the solvers to compare, like `two-phase idastar:coordinate`, each with the heuristic
+/// after its colon or `heuristic`. Returns the message to show if a name is unknown.
+fn parse_solvers(input: &str, heuristic: Heuristic) -> Result<Vec<(Solver, Heuristic)>, String> {
+    input
+        .split_whitespace()
+        .map(|arg| {
+            let (solver_name, heuristic_name) = match arg.split_once(':') {
+                Some((solver_name, heuristic_name)) => (solver_name, Some(heuristic_name)),
+                None => (arg, None),
+            };
+            let solver = Solver::from_name(solver_name)
+                .ok_or_else(|| format!("Unknown solver `{solver_name}`"))?;
+            let heuristic = match heuristic_name {
+                Some(name) => Heuristic::from_name(name)
+                    .ok_or_else(|| format!("Unknown heuristic `{name}`"))?,
+                None => heuristic,
+            };
+            Ok((solver, heuristic))
+        })
+        .collect()
+}
+
+/// Turns applied to the cube that can be undone and redone, by units: the turn of a key, or a
+/// whole algorithm typed or played at once.
+#[derive(Default)]
+struct History {
+    done: Vec<Vec<Turn>>,
+    undone: Vec<Vec<Turn>>,
+}
+impl History {
+    /// Records that `algo` was applied, after which the undone units can't be redone anymore.
+    fn push(&mut self, algo: &[Turn]) {
+        if !algo.is_empty() {
+            self.done.push(algo.to_vec());
+            self.undone.clear();
+        }
+    }
+
+    /// Returns the turns that undo the last unit applied, from its last turn to its first one,
+    /// or `None` if there's nothing to undo.
+    fn undo(&mut self) -> Option<Vec<Turn>> {
+        let algo = self.done.pop()?;
+        let reversed = Turn::inverse_algorithm(&algo);
+        self.undone.push(algo);
+        Some(reversed)
+    }
+
+    /// Returns the last unit undone to apply it again, or `None` if there's nothing to redo.
+    fn redo(&mut self) -> Option<Vec<Turn>> {
+        let algo = self.undone.pop()?;
+        self.done.push(algo.clone());
+        Some(algo)
+    }
+
+    /// Forgets every unit, e.g. when the cube is scrambled or reset.
+    fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+}
+
+/// What to do with the pattern picked in `Screen::pick_pattern`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PatternAction {
+    /// Apply the algorithm of the pattern to the cube.
+    Apply,
+    /// Search for an algorithm that makes the pattern in fewer turns.
+    FindShorter,
+}
+
+/// Full-screen interface of the app: the cube stays in place at the top, with a status bar, the
+/// help and the output of the last command under it.
+struct Screen {
+    /// Row of the status bar, right under the cube.
+    status_row: u16,
+    /// Row where prompts and the output of commands start.
+    message_row: u16,
+    /// Input typed with a command, which answers the next prompt instead of the user.
+    command_input: RefCell<Option<String>>,
+    /// Time between the frames of a turn, see `set_turn_speed`.
+    turn_frame_duration: Cell<Duration>,
+    /// Reads the lines typed in the prompts, with an editor for each prompt so that each one
+    /// has the history of its own lines.
+    editors: RefCell<HashMap<String, Editor<InputHelper, DefaultHistory>>>,
+}
+impl Screen {
+    /// Switches the terminal to the alternate screen, reading keys as soon as they're pressed.
+    fn enter(img_h: usize) -> Screen {
+        terminal::enable_raw_mode().expect("Error when enabling raw mode");
+        execute!(
+            stdout(),
+            EnterAlternateScreen,
+            DisableLineWrap,
+            EnableMouseCapture,
+            Hide
+        )
+        .expect("Error when entering the alternate screen");
+        let status_row = img_h as u16;
+        Screen {
+            status_row,
+            // under the status bar, the hint and an empty line
+            message_row: status_row + 3,
+            command_input: RefCell::new(None),
+            turn_frame_duration: Cell::new(FRAME_DURATION),
+            editors: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Restores the terminal as it was before `enter`.
+    fn leave(&self) {
+        execute!(
+            stdout(),
+            Show,
+            DisableMouseCapture,
+            EnableLineWrap,
+            LeaveAlternateScreen
+        )
+        .expect("Error when leaving the alternate screen");
+        terminal::disable_raw_mode().expect("Error when disabling raw mode");
+    }
+
+    /// Redraws the whole screen.
+    fn draw(&self, cube_render: &CubeRender, status: &str, message: &str) {
+        self.draw_lines(&cube_render.render_lines(), status, message);
+    }
+
+    /// Shows `lines` over the whole screen, a page at a time if they don't fit, until a key is
+    /// pressed on the last page or Esc is pressed.
+    fn show_pages(&self, lines: &[String]) {
+        let (_, rows) = terminal::size().expect("Error when reading the size of the terminal");
+        // the last row tells which key to press
+        let page_len = usize::max(1, rows as usize - 1);
+        let num_pages = lines.len().div_ceil(page_len);
+        let mut out = stdout();
+        for (i, page) in lines.chunks(page_len).enumerate() {
+            queue!(out, MoveTo(0, 0), Clear(ClearType::All)).expect("Error when printing text");
+            for (row, line) in page.iter().enumerate() {
+                queue!(out, MoveTo(0, row as u16)).expect("Error when printing text");
+                write!(out, "{line}").expect("Error when printing text");
+            }
+            let prompt = if i + 1 < num_pages {
+                format!(
+                    "Page {}/{num_pages}, any key for the next one, Esc to go back",
+                    i + 1
+                )
+            } else {
+                "Any key to go back".to_string()
+            };
+            queue!(out, MoveTo(0, page.len() as u16)).expect("Error when printing text");
+            write!(out, "\x1b[7m {prompt} \x1b[0m").expect("Error when printing text");
+            out.flush().expect("Error when printing text");
+            let key = loop {
+                if let Event::Key(key) = event::read().expect("Error when reading command") {
+                    if key.kind == KeyEventKind::Press {
+                        break key;
+                    }
+                }
+            };
+            if key.code == KeyCode::Esc {
+                break;
+            }
+        }
+    }
+
+    /// Returns about how many turns per second `play` plays, see `set_turn_speed`.
+    fn turn_speed(&self) -> f32 {
+        1.0 / (self.turn_frame_duration.get().as_secs_f32() * TURN_FRAMES as f32)
+    }
+
+    /// Redraws the whole screen, with `lines` in place of the cube.
+    fn draw_lines(&self, lines: &[String], status: &str, message: &str) {
+        let mut out = stdout();
+        queue!(out, MoveTo(0, 0), Clear(ClearType::All)).expect("Error when printing text");
+        for (row, line) in lines.iter().enumerate() {
+            queue!(out, MoveTo(0, row as u16)).expect("Error when printing text");
+            write!(out, "{line}").expect("Error when printing text");
+        }
+        queue!(out, MoveTo(0, self.status_row)).expect("Error when printing text");
+        write!(out, "\x1b[7m {status} \x1b[0m").expect("Error when printing text");
+        queue!(out, MoveTo(0, self.status_row + 1)).expect("Error when printing text");
+        write!(out, "{HELP_HINT}").expect("Error when printing text");
+        for (i, line) in message.lines().enumerate() {
+            queue!(out, MoveTo(0, self.message_row + i as u16)).expect("Error when printing text");
+            write!(out, "{line}").expect("Error when printing text");
+        }
+        out.flush().expect("Error when printing text");
+    }
+
+    /// Runs `f` with the terminal back in line mode, below the help, so that it can read whole
+    /// lines and print as usual. Ctrl+C is only sent as a signal (which cancels the searches) in
+    /// line mode, and the mouse isn't captured so that its events aren't typed in the lines.
+    fn in_line_mode<T>(&self, f: impl FnOnce() -> T) -> T {
+        execute!(
+            stdout(),
+            MoveTo(0, self.message_row),
+            Clear(ClearType::FromCursorDown),
+            DisableMouseCapture,
+            Show
+        )
+        .expect("Error when printing text");
+        terminal::disable_raw_mode().expect("Error when disabling raw mode");
+        let output = f();
+        terminal::enable_raw_mode().expect("Error when enabling raw mode");
+        execute!(stdout(), EnableMouseCapture, Hide).expect("Error when printing text");
+        output
+    }
+
+    /// Lets the user type the colors of the stickers of a real 2x2 or 3x3 face by face on the
+    /// net, starting from the colors of `cube` and showing every color as soon as it's typed.
+    /// Returns the cube once the user is done and it can be solved, or `None` if they give up
+    /// with Esc.
+    fn enter_colors(&self, cube_render: &mut CubeRender, cube: &Cube) -> Option<Cube> {
+        let size = cube.size();
+        let mut letters: Vec<char> = cube.facelet_string().chars().collect();
+        let mode = cube_render.mode();
+        cube_render.set_mode(RenderMode::Net);
+        // the facelet that the next color goes to, and the last problem with the colors
+        let mut cursor = 0;
+        let mut error = String::new();
+        let entered = loop {
+            let entered = Cube::from_facelet_string(size, &letters.iter().collect::<String>())
+                .expect("Error when reading the colors");
+            cube_render.update_colors(&entered);
+            let status = if cursor < letters.len() {
+                let face_dir = FaceDir::FACELET_ORDER[cursor / (size * size)];
+                cube_render.set_cursor(Some(cursor));
+                format!(
+                    "Colors of the {face_dir} face: sticker {} of {}",
+                    cursor % (size * size) + 1,
+                    size * size
+                )
+            } else {
+                cube_render.set_cursor(None);
+                "All the colors are typed, Enter to load the cube".to_string()
+            };
+            let message = format!(
+                "Type the colors W/R/B/Y/O/G row by row as on the net, Left/Backspace and Right \
+                 to move\nEnter to load the cube, Esc to give up\n{error}"
+            );
+            self.draw(cube_render, &status, &message);
+
+            let Event::Key(key) = event::read().expect("Error when reading command") else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            error.clear();
+            match key.code {
+                KeyCode::Char(c) if "WRBYOG".contains(c.to_ascii_uppercase()) => {
+                    if let Some(letter) = letters.get_mut(cursor) {
+                        *letter = c.to_ascii_uppercase();
+                        cursor += 1;
+                    }
+                }
+                KeyCode::Left | KeyCode::Backspace => cursor = cursor.saturating_sub(1),
+                KeyCode::Right => cursor = usize::min(cursor + 1, letters.len()),
+                KeyCode::Enter => match check_colors(&entered) {
+                    Ok(()) => break Some(entered),
+                    Err(problem) => error = problem,
+                },
+                KeyCode::Esc => break None,
+                _ => (),
             }
         };
+        cube_render.set_cursor(None);
+        cube_render.set_mode(mode);
+        entered
     }
 
-    // loop through the list of strings, and add to result the parsed move.
-    let mut result = Vec::with_capacity(turns.len());
-    for turn in turns.into_iter() {
-        if turn.is_empty() {
+    /// Lets the user pick one of `search::PATTERNS_3X3` with Up and Down, showing the pattern
+    /// made from a solved cube of size `size` and its algorithm. Returns the index of the pattern
+    /// with what to do with it, or `None` if the user gives up with Esc.
+    fn pick_pattern(
+        &self,
+        cube_render: &mut CubeRender,
+        size: usize,
+    ) -> Option<(usize, PatternAction)> {
+        let mut selected = 0;
+        loop {
+            let (name, algo) = search::PATTERNS_3X3[selected];
+            let mut pattern = Cube::new(size);
+            pattern.apply_algorithm(
+                Turn::parse_algorithm(algo.split_whitespace().collect())
+                    .expect("Error when reading the algorithm of a pattern"),
+            );
+            cube_render.update_colors(&pattern);
+            let list: Vec<String> = search::PATTERNS_3X3
+                .iter()
+                .enumerate()
+                .map(|(i, (name, algo))| {
+                    let cursor = if i == selected { ">" } else { " " };
+                    format!("{cursor} {name}: {algo}")
+                })
+                .collect();
+            let message = format!(
+                "Up and Down to choose a pattern, Enter to apply it, S to find a shorter \
+                 algorithm making it, Esc to give up\n{}",
+                list.join("\n")
+            );
+            self.draw(cube_render, &format!("Pattern: {name}"), &message);
+
+            let Event::Key(key) = event::read().expect("Error when reading command") else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Up => selected = selected.saturating_sub(1),
+                KeyCode::Down => selected = usize::min(selected + 1, list.len() - 1),
+                KeyCode::Enter => return Some((selected, PatternAction::Apply)),
+                KeyCode::Char('s' | 'S') => return Some((selected, PatternAction::FindShorter)),
+                KeyCode::Esc => return None,
+                _ => (),
+            }
+        }
+    }
+
+    /// Races two players on `scrambled`, each turning their own copy with their keys of
+    /// `RACE_KEYS`, drawn side by side with `cube_render`. The clock starts with the race and
+    /// goes on until both cubes are solved or the race is stopped with Esc. Returns the time of
+    /// each player, `None` for a cube that wasn't solved.
+    fn race(&self, cube_render: &CubeRender, scrambled: &Cube) -> [Option<Duration>; 2] {
+        let mut cubes = [scrambled.clone(), scrambled.clone()];
+        let mut move_counts = [0; 2];
+        let mut times = [None; 2];
+        let mut renders = [cube_render.clone(), cube_render.clone()];
+        let start_time = Instant::now();
+        let key_help = RACE_KEYS
+            .iter()
+            .enumerate()
+            .map(|(player, keys)| {
+                let keys: String = keys.iter().map(char::to_ascii_uppercase).collect();
+                format!("Player {}: {keys} for U D R L F B", player + 1)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let message = format!("{key_help}, with Shift for counterclockwise\nEsc to stop the race");
+
+        while times.iter().any(Option::is_none) {
+            let elapsed = start_time.elapsed();
+            let players: Vec<String> = (0..2)
+                .map(|player| {
+                    let time = match times[player] {
+                        Some(time) => format!("solved in {}", format_time(Some(time))),
+                        None => format_time(Some(elapsed)),
+                    };
+                    format!(
+                        "Player {}: {} moves, {time}",
+                        player + 1,
+                        move_counts[player]
+                    )
+                })
+                .collect();
+            for (render, cube) in renders.iter_mut().zip(&cubes) {
+                render.update_colors(cube);
+            }
+            let lines = rendering::render_side_by_side(&renders[0], &renders[1]);
+            self.draw_lines(&lines, &players.join(" | "), &message);
+
+            // the clock is drawn again every frame until a key is pressed
+            if !event::poll(FRAME_DURATION).expect("Error when reading command") {
+                continue;
+            }
+            let Event::Key(key) = event::read().expect("Error when reading command") else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Esc => break,
+                KeyCode::Char(c) => {
+                    let Some((player, turn)) = race_turn(c) else {
+                        continue;
+                    };
+                    if times[player].is_some() {
+                        continue;
+                    }
+                    cubes[player].apply_turn(&turn);
+                    move_counts[player] += 1;
+                    if cubes[player].is_solved() {
+                        times[player] = Some(start_time.elapsed());
+                    }
+                }
+                _ => (),
+            }
+        }
+        times
+    }
+
+    /// Times a solve of a real cube scrambled with `scramble`, showing `scrambled` on the net
+    /// with the scramble until Space starts the timer, so that the real cube can be checked
+    /// against it. Space stops the timer. Returns the time of the solve, or `None` if the user
+    /// gives up with Esc.
+    fn time_solve(
+        &self,
+        cube_render: &CubeRender,
+        scrambled: &Cube,
+        scramble: &[Turn],
+    ) -> Option<Duration> {
+        let mut net = cube_render.clone();
+        net.set_mode(RenderMode::Net);
+        net.update_colors(scrambled);
+        let message = format!(
+            "Scramble: {}\nSpace to start the timer once the cube looks like the net, Esc to \
+             give up",
+            Turn::algo_string(scramble)
+        );
+        self.draw(&net, "Timer | Scramble the cube", &message);
+        // the key pressed if it's Space or Esc, reading the next event
+        let read_key = || match event::read().expect("Error when reading command") {
+            Event::Key(key)
+                if key.kind == KeyEventKind::Press
+                    && [KeyCode::Char(' '), KeyCode::Esc].contains(&key.code) =>
+            {
+                Some(key.code)
+            }
+            _ => None,
+        };
+        let start = loop {
+            if let Some(key) = read_key() {
+                break key;
+            }
+        };
+        if start == KeyCode::Esc {
             return None;
         }
-        let trimmed_turn = &turn[0..turn.len() - 1].to_string();
 
-        // check for possible post-fixes.
-        match turn.to_uppercase().chars().last().unwrap() {
-            '\'' => {
-                result.push(Turn::new(
-                    parse_or_return!(trimmed_turn),
-                    TurnDir::CounterClockwise,
-                ));
+        let start_time = Instant::now();
+        loop {
+            let status = format!("Timer | {}", format_time(Some(start_time.elapsed())));
+            self.draw(&net, &status, "Space to stop the timer, Esc to give up");
+            // the clock is drawn again every frame until a key is pressed
+            if !event::poll(FRAME_DURATION).expect("Error when reading command") {
+                continue;
             }
-            '2' => {
-                result.push(Turn::new(
-                    parse_or_return!(trimmed_turn),
-                    TurnDir::Clockwise,
-                ));
-                result.push(Turn::new(
-                    parse_or_return!(trimmed_turn),
-                    TurnDir::Clockwise,
-                ));
+            match read_key() {
+                Some(KeyCode::Esc) => return None,
+                Some(_) => return Some(start_time.elapsed()),
+                None => (),
+            }
+        }
+    }
+
+    /// Applies `algo` to `cube`, showing every turn.
+    fn play(&self, cube_render: &mut CubeRender, cube: &mut Cube, algo: &[Turn], status: &str) {
+        for turn in algo {
+            cube_render.animate_turn(cube, turn, TURN_FRAMES, |cube_render| {
+                self.draw(cube_render, status, "");
+                thread::sleep(self.turn_frame_duration.get());
+            });
+            cube.apply_turn(turn);
+        }
+    }
+
+    /// Makes `play` play about `turns_per_second` turns per second, not counting the time taken
+    /// to draw them. Returns `false` and keeps the speed if it isn't between 0 and
+    /// `MAX_TURN_SPEED`.
+    fn set_turn_speed(&self, turns_per_second: f32) -> bool {
+        if !(turns_per_second > 0.0 && turns_per_second <= MAX_TURN_SPEED) {
+            return false;
+        }
+        self.turn_frame_duration.set(Duration::from_secs_f32(
+            1.0 / (turns_per_second * TURN_FRAMES as f32),
+        ));
+        true
+    }
+
+    /// Moves the view to the preset orientation `view`, drawing it on the way.
+    fn move_view(&self, cube_render: &mut CubeRender, view: CameraView, status: &str) {
+        cube_render.animate_view(view, VIEW_FRAMES, |cube_render| {
+            self.draw(cube_render, status, "");
+            thread::sleep(FRAME_DURATION);
+        });
+    }
+
+    /// Spins the cube (see `CubeRender::spin`) until there is an input to read.
+    fn spin_until_input(&self, cube_render: &mut CubeRender, status: &str, message: &str) {
+        while !event::poll(FRAME_DURATION).expect("Error when reading command") {
+            cube_render.spin(SPIN_ANGLE);
+            self.draw(cube_render, status, message);
+        }
+    }
+
+    /// Applies the turns of a solution to `cube`, making the stickers of every turn blink before
+    /// it's played so that it can be followed on a real cube.
+    ///
+    /// With a heuristic to `explain` the turns with, the explanation of every turn played (see
+    /// `explain_turn`) is shown under the cube, and the explanations are returned.
+    fn play_solution(
+        &self,
+        cube_render: &mut CubeRender,
+        cube: &mut Cube,
+        algo: &[Turn],
+        status: &str,
+        explain: Option<Heuristic>,
+    ) -> Vec<String> {
+        let mut explanations = Vec::new();
+        for turn in algo {
+            let shown = &explanations[explanations.len().saturating_sub(EXPLANATION_LINES)..];
+            let message = shown.join("\n");
+            for _ in 0..HIGHLIGHT_BLINKS {
+                for highlight in [Some(*turn), None] {
+                    cube_render.set_highlight(highlight);
+                    self.draw(cube_render, status, &message);
+                    thread::sleep(BLINK_DURATION);
+                }
+            }
+            let before = cube.clone();
+            self.play(cube_render, cube, &[*turn], status);
+            if let Some(heuristic) = explain {
+                explanations.push(explain_turn(&before, turn, cube, heuristic));
+            }
+        }
+        explanations
+    }
+
+    /// Prints `text` and returns the line typed after it, or the input typed with the command
+    /// that is running, see `parse_command`.
+    fn prompt(&self, text: &str) -> String {
+        self.prompt_completing(text, Completion::Nothing)
+    }
+
+    /// Prompts like `prompt`, with Tab completing the words of the line with `completion`.
+    /// Up and Down go through the lines typed before in the same prompt, and the line can be
+    /// edited before Enter. The lines of `text` before its last one are printed above it.
+    /// Ctrl+C or Ctrl+D give up on the line, which returns an empty one.
+    fn prompt_completing(&self, text: &str, completion: Completion) -> String {
+        if let Some(input) = self.command_input.borrow_mut().take() {
+            return input;
+        }
+        let (above, text) = text.rsplit_once('\n').unwrap_or(("", text));
+        let mut editors = self.editors.borrow_mut();
+        let editor = editors.entry(text.to_string()).or_insert_with(|| {
+            let mut editor = Editor::new().expect("Error when creating the line editor");
+            editor.set_helper(Some(InputHelper { completion }));
+            editor
+        });
+        self.in_line_mode(|| {
+            if !above.is_empty() {
+                println!("{above}");
+            }
+            match editor.readline(text) {
+                Ok(line) => {
+                    if !line.trim().is_empty() {
+                        editor
+                            .add_history_entry(line.as_str())
+                            .expect("Error when adding to the history");
+                    }
+                    line
+                }
+                Err(_) => String::new(),
+            }
+        })
+    }
+
+    /// Runs the fewest moves challenge on a cube of size `size` scrambled with `scramble`: the
+    /// user types turns to write a solution in `FMC_DURATION`, on the scrambled cube or on its
+    /// inverse (NISS, "normal-inverse scramble switch"), where the turns found on the inverse
+    /// are undone at the end of the solution. The cube of the side being worked on is drawn.
+    /// Returns the message with the solution submitted, checked against the scramble, or with
+    /// why there's none.
+    fn fewest_moves(&self, cube_render: &CubeRender, size: usize, scramble: &[Turn]) -> String {
+        let mut render = cube_render.clone();
+        // the turns of each side, in the lines they were typed, to remove them line by line
+        let mut normal: Vec<Vec<Turn>> = Vec::new();
+        let mut inverse: Vec<Vec<Turn>> = Vec::new();
+        let mut on_inverse = false;
+        let mut error = String::new();
+        let start_time = Instant::now();
+        loop {
+            let normal_turns: Vec<Turn> = normal.concat();
+            let inverse_turns: Vec<Turn> = inverse.concat();
+            // the turns of the inverse come before the scramble, undone, on the normal side
+            let premoves = Turn::inverse_algorithm(&inverse_turns);
+            let solution = [normal_turns.clone(), premoves.clone()].concat();
+            let mut view = Cube::new(size);
+            if on_inverse {
+                view.apply_algorithm(Turn::inverse_algorithm(&normal_turns));
+                view.apply_algorithm(Turn::inverse_algorithm(scramble));
+                view.apply_algorithm(inverse_turns.clone());
+            } else {
+                view.apply_algorithm(premoves);
+                view.apply_algorithm(scramble.to_vec());
+                view.apply_algorithm(normal_turns.clone());
+            }
+            render.update_colors(&view);
+
+            let time_left = FMC_DURATION.saturating_sub(start_time.elapsed());
+            let status = format!(
+                "FMC | {} side | {} moves | Time left: {}:{:02}",
+                if on_inverse { "Inverse" } else { "Normal" },
+                Turn::htm_count(&solution, size),
+                time_left.as_secs() / 60,
+                time_left.as_secs() % 60
+            );
+            self.draw(&render, &status, "");
+
+            let workspace = format!(
+                "Scramble: {}\nNormal: {}\nInverse: {}\nSolution: {}\n{error}\nType turns to \
+                 add them to the {} side, niss to switch sides, undo, done to submit, quit: ",
+                Turn::algo_string(scramble),
+                Turn::algo_string(&normal_turns),
+                Turn::algo_string(&inverse_turns),
+                Turn::algo_string(&solution),
+                if on_inverse { "inverse" } else { "normal" }
+            );
+            let input = self.prompt_completing(&workspace, Completion::Turns);
+            // the solution written before the time ran out is the one submitted
+            if start_time.elapsed() >= FMC_DURATION {
+                return format!(
+                    "Time's up, {}",
+                    check_fewest_moves(size, scramble, &solution)
+                );
+            }
+            error.clear();
+            let lines = if on_inverse {
+                &mut inverse
+            } else {
+                &mut normal
+            };
+            match input.trim() {
+                "" => (),
+                "niss" => on_inverse = !on_inverse,
+                "undo" => {
+                    if lines.pop().is_none() {
+                        error = "There are no turns to remove on this side".to_string();
+                    }
+                }
+                "done" => return check_fewest_moves(size, scramble, &solution),
+                "quit" => return "The fewest moves challenge was given up".to_string(),
+                input => match Turn::parse_algorithm(input.split_whitespace().collect()) {
+                    Some(algo) if algo.iter().all(|turn| turn.get_layer() <= size) => {
+                        lines.push(algo);
+                    }
+                    Some(_) => error = "The cube doesn't have these layers".to_string(),
+                    None => error = format!("Invalid turns `{input}`"),
+                },
             }
-            _ => result.push(Turn::new(
-                parse_or_return!(&turn.to_string()),
-                TurnDir::Clockwise,
-            )),
         }
     }
-    Some(result)
 }
 
-/// Runs the main app loop until the user input "Q"
-pub fn main_app_loop() {
+/// Returns whether `solution` solves a cube of size `size` scrambled with `scramble`, with its
+/// number of turns in the half turn metric, see `Screen::fewest_moves`.
+fn check_fewest_moves(size: usize, scramble: &[Turn], solution: &[Turn]) -> String {
+    let mut cube = Cube::new(size);
+    cube.apply_algorithm(scramble.to_vec());
+    cube.apply_algorithm(solution.to_vec());
+    let algo = Turn::algo_string(solution);
+    let moves = Turn::htm_count(solution, size);
+    if cube.is_solved() {
+        format!("Solution of {moves} moves (HTM): {algo}")
+    } else {
+        format!("Not a solution of the scramble, {moves} moves (HTM): {algo}")
+    }
+}
+
+/// Returns what the solver sees after `turn` turned `before` into `after`: the value of
+/// `heuristic`, the exact distance of a 2x2 if its depth table is loaded, and the pieces that the
+/// turn put in their solved place, like
+/// `R: single-l0 heuristic 3, 4 quarter turns from solved, placed UFR (2/8 pieces solved)`.
+fn explain_turn(before: &Cube, turn: &Turn, after: &Cube, heuristic: Heuristic) -> String {
+    let mut explanation = format!(
+        "{turn}: {} heuristic {}",
+        heuristic.name(),
+        heuristic.evaluate(after)
+    );
+    if let (2, Some(depth_table)) = (after.size(), search::depth_table_2x2()) {
+        if let Some(distance) = search::distance_2x2(after, depth_table) {
+            explanation.push_str(&format!(", {distance} quarter turns from solved"));
+        }
+    }
+
+    let size = after.size();
+    let groups = Cube::piece_groups(size);
+    let (before, after) = (before.facelet_codes(), after.facelet_codes());
+    let is_placed =
+        |codes: &[u8], solved: &[u8], group: &[usize]| group.iter().all(|&i| codes[i] == solved[i]);
+    let count_placed = |codes: &[u8], solved: &[u8]| {
+        let placed = groups
+            .iter()
+            .filter(|group| is_placed(codes, solved, group));
+        placed.count()
+    };
+    // the pieces are placed relative to the solved cube in the orientation the cube is nearest
+    // to, since a cube can be solved in any orientation
+    let solved = Cube::new(size)
+        .all_orientations()
+        .iter()
+        .map(Cube::facelet_codes)
+        .max_by_key(|solved| count_placed(&after, solved))
+        .expect("Error when orienting the solved cube");
+    let placed: Vec<String> = groups
+        .iter()
+        .filter(|group| !is_placed(&before, &solved, group) && is_placed(&after, &solved, group))
+        .map(|group| piece_name(size, group))
+        .collect();
+    if placed.is_empty() {
+        explanation.push_str(", placed no piece");
+    } else {
+        explanation.push_str(&format!(", placed {}", placed.join(" ")));
+    }
+    let num_placed = count_placed(&after, &solved);
+    explanation.push_str(&format!(" ({num_placed}/{} pieces solved)", groups.len()));
+    explanation
+}
+
+/// Returns the name of the piece of a cube of size `size` with the facelets `group` (see
+/// `Cube::piece_groups`) by the faces it's on, like `UFR` for a corner or `UF` for an edge, or
+/// like `U center`.
+fn piece_name(size: usize, group: &[usize]) -> String {
+    // the faces are named in the usual order, up or down first, then front or back
+    const NAME_ORDER: [FaceDir; 6] = [
+        FaceDir::Up,
+        FaceDir::Down,
+        FaceDir::Front,
+        FaceDir::Back,
+        FaceDir::Right,
+        FaceDir::Left,
+    ];
+    let mut faces: Vec<FaceDir> = group
+        .iter()
+        .map(|&i| FaceDir::FACELET_ORDER[i / (size * size)])
+        .collect();
+    faces.sort_by_key(|face_dir| NAME_ORDER.iter().position(|f| f == face_dir));
+    let name: String = faces.iter().map(FaceDir::to_string).collect();
+    match faces.len() {
+        1 => format!("{name} center"),
+        _ => name,
+    }
+}
+
+/// Returns `Ok` if the colors of a 2x2 or 3x3 typed in make a cube that can be solved, otherwise
+/// the problem with them.
+fn check_colors(cube: &Cube) -> Result<(), String> {
+    let facelets = cube.facelet_string();
+    let stickers = cube.size() * cube.size();
+    for letter in "WRBYOG".chars() {
+        let count = facelets.chars().filter(|&c| c == letter).count();
+        if count != stickers {
+            return Err(format!(
+                "There are {count} stickers of color {letter}, there must be {stickers}"
+            ));
+        }
+    }
+    match cube.is_solvable() {
+        Some(true) => Ok(()),
+        Some(false) => Err("These pieces can't be solved, check the colors again".to_string()),
+        None => Err("Only the colors of a 2x2 or 3x3 can be checked".to_string()),
+    }
+}
+
+/// Reads the colors of a 2x2 or 3x3 of size `size` from images, see
+/// `photo::cube_from_images`.
+#[cfg(feature = "image")]
+fn cube_from_images(size: usize, paths: &[&Path]) -> io::Result<Cube> {
+    rubiks::cube::photo::cube_from_images(size, paths)
+}
+#[cfg(not(feature = "image"))]
+fn cube_from_images(_size: usize, _paths: &[&Path]) -> io::Result<Cube> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the app is built without the image feature",
+    ))
+}
+
+/// Largest size of cube that the app can be switched to. Bigger cubes have stickers smaller
+/// than a character.
+const MAX_SIZE: usize = 20;
+
+/// Runs the main app loop with a cube of size `size` and the settings of `config` until the user
+/// presses "Q"
+pub fn main_app_loop(size: usize, config: &Config) {
+    // Ctrl+C cancels the running search if there is one, otherwise exits.
+    let cancel_token = Arc::new(AtomicBool::new(false));
+    let searching = Arc::new(AtomicBool::new(false));
+    {
+        let (cancel_token, searching) = (cancel_token.clone(), searching.clone());
+        ctrlc::set_handler(move || {
+            if searching.load(Ordering::SeqCst) {
+                cancel_token.store(true, Ordering::SeqCst);
+            } else {
+                // the handler runs outside of the main loop, so restore the terminal here
+                let _ = execute!(
+                    stdout(),
+                    Show,
+                    DisableMouseCapture,
+                    EnableLineWrap,
+                    LeaveAlternateScreen
+                );
+                let _ = terminal::disable_raw_mode();
+                std::process::exit(130);
+            }
+        })
+        .expect("Error when setting the Ctrl+C handler");
+    }
+
     // creates new cube.
-    let mut cube = Cube::new(2);
+    let mut cube = Cube::new(size);
+    // number of turns applied since the cube was reset or scrambled
+    let mut move_count = 0;
+    // the turns applied since the cube was reset or scrambled, to undo and redo them
+    let mut history = History::default();
+    // everything that happened to the cube, to save it to a file
+    let mut session_log = SessionLog::new(size);
+    // the times of the scrambles solved by hand, in this session and the ones before. if the
+    // file can't be read, the solves aren't saved so that it isn't overwritten
+    let (mut solve_history, save_solves, solves_message) =
+        match SolveHistory::load(Path::new(SOLVES_PATH)) {
+            Ok(solve_history) => (solve_history, true, String::new()),
+            Err(error) => (
+                SolveHistory::default(),
+                false,
+                format!("Error when reading {SOLVES_PATH}, the solves won't be saved: {error}"),
+            ),
+        };
+    // the statistics of the user over all sessions, not saved either if the file can't be read
+    let (mut profile, save_profile, profile_message) = match Profile::load(Path::new(PROFILE_PATH))
+    {
+        Ok(profile) => (profile, true, String::new()),
+        Err(error) => (
+            Profile::default(),
+            false,
+            format!("Error when reading {PROFILE_PATH}, the profile won't be saved: {error}"),
+        ),
+    };
+    // the last solution found, with the hash of the cube it solves
+    let mut last_solution: Option<(u64, Vec<Turn>)> = None;
+    // what S and Shift+H solve the cube with
+    let (mut solver, mut heuristic) = (config.solver, config.heuristic);
+    // whether the solutions found by S and G are played right away
+    let mut auto_play = false;
+    // whether each turn played by A or Space is explained, see `explain_turn`
+    let mut explain = false;
+    // the solution whose first turn was given as a hint, with the hash of the cube it solves
+    let mut hint: Option<(u64, Vec<Turn>)> = None;
+    // the turns of the solution left to step through with Space, with the hash of the cube they
+    // solve
+    let mut steps: Option<(u64, Vec<Turn>)> = None;
 
-    let (x_scale, y_scale, img_w, img_h) = (10.0, 5.0, 80, 29);
+    let (x_scale, y_scale, img_w, img_h) = (10.0, 5.0, config.width, config.height);
     let rotate_speed = 10_f32.to_radians();
+    // rotation for each column or row the mouse is dragged by. the rows are about twice as high
+    // as the columns are wide
+    let (drag_speed_x, drag_speed_y) = (3_f32.to_radians(), 6_f32.to_radians());
+    let (zoom_speed, fov_speed, init_fov) = (1.1, 10_f32.to_radians(), 30_f32.to_radians());
     let mut cube_render = CubeRender::new(&cube, x_scale, y_scale, img_w, img_h);
+    // the scales only give the shape of the characters, the stickers get smaller on bigger cubes
+    cube_render.set_fit(ImageFit::Cube);
+
+    let screen = Screen::enter(img_h);
+    // the output of the last command, shown under the help
+    let mut message = [solves_message, profile_message]
+        .into_iter()
+        .filter(|message| !message.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    // where the mouse was when the view was last rotated by dragging
+    let mut drag_position = None;
+    // key of the command typed after ":", run on the next loop as if it was pressed
+    let mut command_key = None;
 
-    // loop forever until the user types "q".
+    // loop forever until the user presses "q".
     loop {
-        // prints the cube.
-        cube_render.render_cube();
+        // stop stepping through the solution once the cube was changed some other way
+        if steps
+            .as_ref()
+            .is_some_and(|(hash, _)| *hash != cube.zobrist_hash())
+        {
+            steps = None;
+            cube_render.set_highlight(None);
+        }
+
+        // time the scramble if it was just solved by hand
+        if cube.is_solved() {
+            if let Some(time) = session_log.solve_time() {
+                session_log.log(SessionEvent::Solved(time));
+                let personal_best = solve_history.add(time);
+                message = format!(
+                    "Solved in {}{}\n{solve_history}",
+                    format_time(Some(time)),
+                    if personal_best {
+                        ", a personal best!"
+                    } else {
+                        ""
+                    }
+                );
+                if save_solves {
+                    if let Err(error) = solve_history.save(Path::new(SOLVES_PATH)) {
+                        message = format!("Error when saving the solve to {SOLVES_PATH}: {error}");
+                    }
+                }
+                profile.add_solve(cube.size(), time, move_count);
+                if save_profile {
+                    if let Err(error) = profile.save(Path::new(PROFILE_PATH)) {
+                        message =
+                            format!("Error when saving the profile to {PROFILE_PATH}: {error}");
+                    }
+                }
+            }
+        }
 
-        // prints prompt.
-        println!(
-            "Q to quit. X to reset. C to check if the cube is solved. M to scramble the cube."
+        let solved = if cube.is_solved() {
+            "Solved"
+        } else {
+            "Not solved"
+        };
+        let recent_turns = session_log.recent_turns(RECENT_TURNS);
+        let status = format!(
+            "{size}x{size} | Moves: {move_count} | {solved} | ao5: {} | ao12: {} | Last: {}",
+            format_time(solve_history.current_average(5)),
+            format_time(solve_history.current_average(12)),
+            Turn::algo_string(&recent_turns),
+            size = cube.size()
         );
-        println!("U/D/R/L/F/B to turn the corresponding face clockwise. Add ' to turn counter-clockwise.");
-        println!("V + W/A/S/D to rotate the view");
-        println!("S to find the solution for the cube using IDA*");
-        print!("TYPE COMMAND: ");
-        stdout().flush().expect("Error when printing text");
-
-        // read line
-        let mut cmd = String::new();
-        stdin()
-            .read_line(&mut cmd)
-            .expect("Error when reading command");
+        screen.draw(&cube_render, &status, &message);
+
+        // run the key of the command typed after ":" if there's one, otherwise wait for a key
+        // press, spinning the cube if it takes a while. dragging the mouse rotates the view, and
+        // scrolling zooms
+        let event = match command_key.take() {
+            Some(key) => Event::Key(KeyEvent::new(key, KeyModifiers::NONE)),
+            None => {
+                if !event::poll(IDLE_DURATION).expect("Error when reading command") {
+                    screen.spin_until_input(&mut cube_render, &status, &message);
+                }
+                match event::read().expect("Error when reading command") {
+                    // the keys of the config run their command instead, unlike Ctrl with them
+                    Event::Key(mut key) => {
+                        if let KeyCode::Char(c) = key.code {
+                            if !key.modifiers.contains(KeyModifiers::CONTROL) {
+                                key.code = config.keys.get(&c).copied().unwrap_or(key.code);
+                            }
+                        }
+                        Event::Key(key)
+                    }
+                    event => event,
+                }
+            }
+        };
+        let key = match event {
+            Event::Key(key) => key,
+            Event::Mouse(mouse) => {
+                match mouse.kind {
+                    MouseEventKind::Down(MouseButton::Left) => {
+                        drag_position = Some((mouse.column, mouse.row));
+                    }
+                    MouseEventKind::Drag(MouseButton::Left) => {
+                        if let Some((column, row)) = drag_position {
+                            let dx = mouse.column as f32 - column as f32;
+                            let dy = mouse.row as f32 - row as f32;
+                            cube_render.rotate_yaw(dx * drag_speed_x);
+                            cube_render.rotate_pitch(-dy * drag_speed_y);
+                        }
+                        drag_position = Some((mouse.column, mouse.row));
+                    }
+                    MouseEventKind::Up(MouseButton::Left) => drag_position = None,
+                    MouseEventKind::ScrollUp => cube_render.zoom(zoom_speed),
+                    MouseEventKind::ScrollDown => cube_render.zoom(1.0 / zoom_speed),
+                    _ => (),
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        message.clear();
 
         // match command
-        match cmd.trim().to_uppercase().as_str() {
-            "Q" => break, // if the command is "Q", break the main loop thus exiting.
-            "X" => {
-                // if the command is "X", set `cube` to a new cube, then update render
-                cube = Cube::new(2);
+        match key.code {
+            // Ctrl+C isn't a signal in raw mode, so it comes as a key
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+            KeyCode::Char('q' | 'Q') | KeyCode::Esc => break,
+
+            // the faces turn as soon as their key is pressed, counter-clockwise with Shift
+            KeyCode::Char(c) if face_turn(c).is_some() => {
+                let algo = [face_turn(c).unwrap()];
+                move_count += algo.len();
+                screen.play(&mut cube_render, &mut cube, &algo, &status);
+                history.push(&algo);
+                session_log.log(SessionEvent::Turns(algo.to_vec()));
+            }
+
+            KeyCode::Char(':') => {
+                // if the key is ":", prompts a command and run it with the key that does the
+                // same on the next loop
+                let command = screen.prompt_completing(":", Completion::Commands);
+                match parse_command(&command) {
+                    Ok((key, input)) => {
+                        *screen.command_input.borrow_mut() = input;
+                        command_key = Some(key);
+                    }
+                    Err(command_message) => message = command_message,
+                }
+            }
+
+            KeyCode::Enter => {
+                // prompts an algorithm and applies it. if we can't parse it
+                // (`Turn::parse_algorithm` returns `None`), tell the user and leave the cube as
+                // it is.
+                let algo = screen.prompt_completing("Type the turns to apply: ", Completion::Turns);
+                match Turn::parse_algorithm(algo.split_whitespace().collect()) {
+                    None => message = "Invalid turns".to_string(),
+                    Some(algo) if algo.iter().any(|turn| turn.get_layer() > cube.size()) => {
+                        message = "The cube doesn't have these layers".to_string();
+                    }
+                    Some(algo) => {
+                        move_count += algo.len();
+                        screen.play(&mut cube_render, &mut cube, &algo, &status);
+                        history.push(&algo);
+                        profile.add_algorithm(&algo);
+                        if save_profile {
+                            if let Err(error) = profile.save(Path::new(PROFILE_PATH)) {
+                                message = format!(
+                                    "Error when saving the profile to {PROFILE_PATH}: {error}"
+                                );
+                            }
+                        }
+                        session_log.log(SessionEvent::Turns(algo));
+                    }
+                }
+            }
+
+            KeyCode::Char('<') => {
+                // if the key is "<", prompts an algorithm file then applies its algorithms one
+                // by one, from a solved cube each with --reset, and solves the cube after each
+                // with --solve
+                let input = screen.prompt(
+                    "Type the path of an algorithm file, then --reset or --solve if needed: ",
+                );
+                let mut words = input.split_whitespace();
+                let Some(path) = words.next() else {
+                    message = "No file given".to_string();
+                    continue;
+                };
+                let options: Vec<&str> = words.collect();
+                if let Some(option) = options
+                    .iter()
+                    .find(|option| !["--reset", "--solve"].contains(option))
+                {
+                    message = format!("Invalid option `{option}`");
+                    continue;
+                }
+                let (reset, solve_each) =
+                    (options.contains(&"--reset"), options.contains(&"--solve"));
+                let algorithms = match read_algorithm_file(Path::new(path), cube.size()) {
+                    Ok(algorithms) => algorithms,
+                    Err(e) => {
+                        message = format!("Can't read {path}: {e}");
+                        continue;
+                    }
+                };
+
+                let mut results = Vec::with_capacity(algorithms.len());
+                for (line, algo) in algorithms {
+                    if reset {
+                        cube = Cube::new(cube.size());
+                        move_count = 0;
+                        history.clear();
+                        session_log.log(SessionEvent::Reset(cube.size()));
+                        cube_render.update_colors(&cube);
+                    }
+                    move_count += algo.len();
+                    screen.play(&mut cube_render, &mut cube, &algo, &status);
+                    history.push(&algo);
+                    let solved = if cube.is_solved() {
+                        "solved"
+                    } else {
+                        "not solved"
+                    };
+                    let mut result =
+                        format!("Line {line}: {} ({solved})", Turn::algo_string(&algo));
+                    session_log.log(SessionEvent::Turns(algo));
+                    if solve_each {
+                        let config = SearchConfig {
+                            move_set: MoveSet::default_for(cube.size()),
+                            cancel_token: Some(cancel_token.clone()),
+                            fallback: true,
+                            ..SearchConfig::default()
+                        };
+                        let solution = screen.in_line_mode(|| {
+                            cancel_token.store(false, Ordering::SeqCst);
+                            searching.store(true, Ordering::SeqCst);
+                            let result = solve(&cube, &config, solver, heuristic);
+                            searching.store(false, Ordering::SeqCst);
+                            result.and_then(|result| result.solution)
+                        });
+                        match solution {
+                            Some(solution) => {
+                                result += &format!(", solution: {}", Turn::algo_string(&solution));
+                                session_log.log(SessionEvent::Solution(solution));
+                            }
+                            None => result += ", no solution found",
+                        }
+                    }
+                    results.push(result);
+                }
+                message = results.join("\n");
+            }
+
+            KeyCode::Char('z' | 'Z') => {
+                // if the key is "Z", undo the last turn, or the last algorithm as a whole
+                match history.undo() {
+                    Some(algo) => {
+                        move_count = move_count.saturating_sub(algo.len());
+                        screen.play(&mut cube_render, &mut cube, &algo, &status);
+                        message = format!("Undid {}", Turn::algo_string(&algo));
+                        session_log.log(SessionEvent::Undo(algo));
+                    }
+                    None => message = "Nothing to undo".to_string(),
+                }
+            }
+
+            KeyCode::Char('y' | 'Y') => {
+                // if the key is "Y", apply again what was last undone
+                match history.redo() {
+                    Some(algo) => {
+                        move_count += algo.len();
+                        screen.play(&mut cube_render, &mut cube, &algo, &status);
+                        message = format!("Redid {}", Turn::algo_string(&algo));
+                        session_log.log(SessionEvent::Redo(algo));
+                    }
+                    None => message = "Nothing to redo".to_string(),
+                }
+            }
+
+            KeyCode::Char('x' | 'X') => {
+                // if the key is "X", set `cube` to a new cube, then update render
+                cube = Cube::new(cube.size());
+                move_count = 0;
+                history.clear();
+                session_log.log(SessionEvent::Reset(cube.size()));
+                cube_render.update_colors(&cube);
+            }
+
+            KeyCode::Char('#') => {
+                // if the key is "#", prompts a size and start over with a solved cube of that
+                // size
+                let size = screen.prompt(&format!("Type the size of the cube (2 to {MAX_SIZE}): "));
+                match size.trim().parse() {
+                    Ok(size) if (2..=MAX_SIZE).contains(&size) => {
+                        cube = Cube::new(size);
+                        move_count = 0;
+                        history.clear();
+                        session_log.log(SessionEvent::Reset(size));
+                        cube_render.update_colors(&cube);
+                        message = format!("Switched to a {size}x{size} cube");
+                    }
+                    _ => message = format!("The size must be a number from 2 to {MAX_SIZE}"),
+                }
+            }
+
+            KeyCode::Char('@') => {
+                // if the key is "@", type the colors of a real cube and start over from them
+                if !(2..=3).contains(&cube.size()) {
+                    message = "Only the colors of a 2x2 or 3x3 can be typed in".to_string();
+                    continue;
+                }
+                if let Some(entered) = screen.enter_colors(&mut cube_render, &cube) {
+                    cube = entered;
+                    move_count = 0;
+                    history.clear();
+                    session_log.log(SessionEvent::Loaded(cube.facelet_string()));
+                    message = "Loaded the colors, S to solve them".to_string();
+                }
+                cube_render.update_colors(&cube);
+            }
+
+            KeyCode::Char('&') => {
+                // if the key is "&", prompts the images of the cube and show the colors read from
+                // them to check them before they're loaded, like with "@"
+                if !(2..=3).contains(&cube.size()) {
+                    message = "Only the colors of a 2x2 or 3x3 can be read".to_string();
+                    continue;
+                }
+                let paths = screen.prompt(
+                    "Type the image of the net, or the 6 images of the faces in the order U R F D L B: ",
+                );
+                let paths: Vec<&Path> = paths.split_whitespace().map(Path::new).collect();
+                let read = match cube_from_images(cube.size(), &paths) {
+                    Ok(read) => read,
+                    Err(error) => {
+                        message = format!("Error when reading the images: {error}");
+                        continue;
+                    }
+                };
+                if let Some(entered) = screen.enter_colors(&mut cube_render, &read) {
+                    cube = entered;
+                    move_count = 0;
+                    history.clear();
+                    session_log.log(SessionEvent::Loaded(cube.facelet_string()));
+                    message = "Loaded the colors, S to solve them".to_string();
+                }
+                cube_render.update_colors(&cube);
+            }
+
+            KeyCode::Char('m' | 'M') => {
+                // if the key is "M", prompts a number of turns, a seed, --official or --daily
+                // then scramble.
+                let input = screen.prompt(&format!(
+                    "Type number of turns to scramble ({} by default), --seed S, --official or --daily: ",
+                    config.scramble_length
+                ));
+                let options =
+                    match ScrambleOptions::parse(&input.split_whitespace().collect::<Vec<_>>()) {
+                        Ok(options) => options,
+                        Err(msg) => {
+                            message = msg;
+                            continue;
+                        }
+                    };
+                let algo = match options.scramble(&mut cube, config.scramble_length) {
+                    Ok(algo) => algo,
+                    Err(msg) => {
+                        message = msg;
+                        continue;
+                    }
+                };
+                message = match options.daily {
+                    Some(date) => {
+                        session_log.log(SessionEvent::Daily(date.to_string()));
+                        format!("Daily scramble of {date}: {}", Turn::algo_string(&algo))
+                    }
+                    None => format!("Scramble sequence: {}", Turn::algo_string(&algo)),
+                };
+                move_count = 0;
+                history.clear();
+                session_log.log(SessionEvent::Scramble(algo));
+                cube_render.update_colors(&cube);
+            }
+
+            KeyCode::Char('(') => {
+                // if the key is "(", prompts the scramble like "M", then race two players on it
+                // with a copy of the cube each. the cube of the app isn't changed
+                let input = screen.prompt(&format!(
+                    "Type number of turns of the race's scramble ({} by default), --seed S, --official or --daily: ",
+                    config.scramble_length
+                ));
+                let options =
+                    match ScrambleOptions::parse(&input.split_whitespace().collect::<Vec<_>>()) {
+                        Ok(options) => options,
+                        Err(msg) => {
+                            message = msg;
+                            continue;
+                        }
+                    };
+                let mut scrambled = Cube::new(cube.size());
+                let algo = match options.scramble(&mut scrambled, config.scramble_length) {
+                    Ok(algo) => algo,
+                    Err(msg) => {
+                        message = msg;
+                        continue;
+                    }
+                };
+                // two half-width renders, like the ones of W
+                let mut race_render = cube_render.clone();
+                race_render.set_image_size(img_w / 2 - 1, img_h);
+                let times = screen.race(&race_render, &scrambled);
+                let results: Vec<String> = times
+                    .iter()
+                    .enumerate()
+                    .map(|(player, time)| match time {
+                        Some(time) => {
+                            format!("Player {} in {}", player + 1, format_time(Some(*time)))
+                        }
+                        None => format!("Player {} didn't finish", player + 1),
+                    })
+                    .collect();
+                let winner = match times {
+                    [Some(a), Some(b)] if a == b => "It's a tie".to_string(),
+                    [Some(a), Some(b)] => format!("Player {} wins", if a < b { 1 } else { 2 }),
+                    [Some(_), None] => "Player 1 wins".to_string(),
+                    [None, Some(_)] => "Player 2 wins".to_string(),
+                    [None, None] => "Nobody solved the cube".to_string(),
+                };
+                message = format!(
+                    "{winner}: {}\nScramble of the race: {}",
+                    results.join(", "),
+                    Turn::algo_string(&algo)
+                );
+            }
+
+            KeyCode::Char('/') => {
+                // if the key is "/", undo the turns since the last scramble and the scramble
+                // itself at once, and show the inverse of the scramble to practice it by hand
+                let Some(scramble) = session_log.last_scramble() else {
+                    message = "There's no scramble to undo since the last reset".to_string();
+                    continue;
+                };
+                let inverse = Turn::inverse_algorithm(scramble);
+                let mut algo = Turn::inverse_algorithm(&session_log.recent_turns(usize::MAX));
+                algo.extend(&inverse);
+                cube.apply_algorithm(algo.clone());
+                message = format!("Inverse of the scramble: {}", Turn::algo_string(&inverse));
+                move_count = 0;
+                history.push(&algo);
+                // played like a solution, so that it isn't timed as a solve
+                session_log.log(SessionEvent::Played(algo));
                 cube_render.update_colors(&cube);
             }
-            //
-            "C" => {
-                // if command is "c", checks if cube is solved and prints the result
-                // accordingly.
+
+            KeyCode::Char('c' | 'C') => {
+                // if the key is "C", switch to the next palette, including the custom one if
+                // there's a palette file
+                let mut palettes = vec![
+                    ("standard", Palette::STANDARD),
+                    ("deuteranopia-safe", Palette::DEUTERANOPIA),
+                    ("high contrast", Palette::HIGH_CONTRAST),
+                ];
+                match Palette::from_file(Path::new(PALETTE_PATH)) {
+                    Ok(palette) => palettes.push(("custom", palette)),
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+                    Err(e) => message = format!("Can't read {PALETTE_PATH}: {e}\n"),
+                }
+                let next = palettes
+                    .iter()
+                    .position(|(_, palette)| *palette == Palette::current())
+                    .map_or(0, |i| (i + 1) % palettes.len());
+                let (name, palette) = palettes[next];
+                palette.set_current();
+                message.push_str(&format!("Using the {name} colors"));
+            }
+
+            KeyCode::Char('v' | 'V') => {
+                // if the key is "V", switch between the 3D view and the net
+                cube_render.set_mode(match cube_render.mode() {
+                    RenderMode::View => RenderMode::Net,
+                    RenderMode::Net => RenderMode::View,
+                });
+            }
+
+            KeyCode::Char('i' | 'I') => {
+                // if the key is "I", show or hide the names of the faces and the axes
+                cube_render.set_labels(!cube_render.labels());
+            }
+
+            KeyCode::Char('k' | 'K') => {
+                // if the key is "K", switch between half blocks and braille dots
+                cube_render.set_pixel_style(match cube_render.pixel_style() {
+                    PixelStyle::HalfBlocks => PixelStyle::Braille,
+                    PixelStyle::Braille => PixelStyle::HalfBlocks,
+                });
+            }
+
+            KeyCode::Char('*') => {
+                // if the key is "*", show or hide the letters of the stickers
+                cube_render.set_letters(!cube_render.letters());
+            }
+
+            KeyCode::Char('h') => {
+                // if the key is "h", dim the solved stickers, or stop dimming them
+                cube_render.set_show_unsolved(!cube_render.show_unsolved());
+            }
+
+            KeyCode::Char('e' | 'E') => {
+                // if the key is "E", save the current view and the net of the cube as SVG images
+                let view = cube_render.render_svg(SVG_STICKER_SIZE);
+                let net = rendering::render_net_svg(&cube, SVG_STICKER_SIZE);
+                message = match fs::write(VIEW_SVG_PATH, view)
+                    .and_then(|_| fs::write(NET_SVG_PATH, net))
+                {
+                    Ok(()) => format!("Saved {VIEW_SVG_PATH} and {NET_SVG_PATH}"),
+                    Err(e) => format!("Can't save the images: {e}"),
+                };
+            }
+
+            KeyCode::Char('>') => {
+                // if the key is ">", save the log of the session
+                message = match session_log.write_csv(Path::new(SESSION_LOG_PATH)) {
+                    Ok(()) => format!("Saved the session to {SESSION_LOG_PATH}"),
+                    Err(e) => format!("Can't save the session: {e}"),
+                };
+            }
+
+            KeyCode::Char('+' | '=') => cube_render.zoom(zoom_speed),
+            KeyCode::Char('-') => cube_render.zoom(1.0 / zoom_speed),
+            KeyCode::Char('o' | 'O') => {
+                cube_render.set_perspective(match cube_render.perspective() {
+                    Some(_) => None,
+                    None => Some(init_fov),
+                })
+            }
+            KeyCode::Char('[') => {
+                cube_render.set_perspective(cube_render.perspective().map(|fov| fov - fov_speed))
+            }
+            KeyCode::Char(']') => {
+                cube_render.set_perspective(cube_render.perspective().map(|fov| fov + fov_speed))
+            }
+
+            KeyCode::Char(',') => cube_render.rotate_roll(-rotate_speed),
+            KeyCode::Char('.') => cube_render.rotate_roll(rotate_speed),
+            KeyCode::Char('1') => screen.move_view(&mut cube_render, CameraView::Front, &status),
+            KeyCode::Char('2') => screen.move_view(&mut cube_render, CameraView::Corner, &status),
+            KeyCode::Char('3') => screen.move_view(&mut cube_render, CameraView::Top, &status),
+            KeyCode::Char('0') => {
+                // if the key is "0", make the visible faces or the whole cube fill the image
+                cube_render.set_fit(match cube_render.fit() {
+                    ImageFit::VisibleFaces => ImageFit::Cube,
+                    _ => ImageFit::VisibleFaces,
+                });
+            }
+            KeyCode::Up => cube_render.rotate_pitch(rotate_speed),
+            KeyCode::Down => cube_render.rotate_pitch(-rotate_speed),
+            KeyCode::Left => cube_render.rotate_yaw(rotate_speed),
+            KeyCode::Right => cube_render.rotate_yaw(-rotate_speed),
+
+            KeyCode::Char('s' | 'S') => {
+                // if the key is "S", solve the cube with the chosen solver, by default the fastest
+                // one for its size
+                let config = SearchConfig {
+                    move_set: MoveSet::default_for(cube.size()),
+                    cancel_token: Some(cancel_token.clone()),
+                    print_progress: true,
+                    fallback: true,
+                    ..SearchConfig::default()
+                };
+                let result = screen.in_line_mode(|| {
+                    cancel_token.store(false, Ordering::SeqCst);
+                    searching.store(true, Ordering::SeqCst);
+                    let result = solve(&cube, &config, solver, heuristic);
+                    searching.store(false, Ordering::SeqCst);
+                    result
+                });
+                let Some(result) = result else {
+                    message = format!("The {} solver can't solve this cube", solver.name());
+                    continue;
+                };
+                message = result.to_string();
+                if let Some(solution) = &result.solution {
+                    // the solution reversed scrambles a solved cube to the current one
+                    let setup = Turn::inverse_algorithm(solution);
+                    let url = Turn::alg_cubing_url(cube.size(), &setup, solution);
+                    message.push_str(&format!("\nReplay: {url}"));
+                    session_log.log(SessionEvent::Solution(solution.clone()));
+                }
+                last_solution = result.solution.map(|algo| (cube.zobrist_hash(), algo));
+                if auto_play {
+                    if let Some((_, solution)) = last_solution.take() {
+                        move_count += solution.len();
+                        screen.play(&mut cube_render, &mut cube, &solution, &status);
+                        history.push(&solution);
+                        session_log.log(SessionEvent::Played(solution));
+                    }
+                }
+            }
+
+            KeyCode::Char('g' | 'G') => {
+                // if the key is "G", prompts the generators, then run IDA* with their turns.
+                let generators = screen
+                    .prompt_completing("Type the faces or slices to turn: ", Completion::Turns);
+                let Some(generators) =
+                    Turn::parse_algorithm(generators.split_whitespace().collect())
+                else {
+                    message = "Invalid faces or slices".to_string();
+                    continue;
+                };
+                if generators.iter().any(|turn| turn.get_layer() > cube.size()) {
+                    message = "The cube doesn't have these layers".to_string();
+                    continue;
+                }
+                let config = SearchConfig {
+                    move_set: MoveSet::generated_by(&generators),
+                    cancel_token: Some(cancel_token.clone()),
+                    print_progress: true,
+                    ..SearchConfig::default()
+                };
+                let result = screen.in_line_mode(|| {
+                    cancel_token.store(false, Ordering::SeqCst);
+                    searching.store(true, Ordering::SeqCst);
+                    // slice turns move the centers, so the cube can end up solved in any
+                    // orientation
+                    let result = parallel_idastar(cube.clone(), &search::all_l0, &config);
+                    searching.store(false, Ordering::SeqCst);
+                    result
+                });
+                message = result.to_string();
+                if let Some(solution) = &result.solution {
+                    session_log.log(SessionEvent::Solution(solution.clone()));
+                }
+                last_solution = result.solution.map(|algo| (cube.zobrist_hash(), algo));
+                if auto_play {
+                    if let Some((_, solution)) = last_solution.take() {
+                        move_count += solution.len();
+                        screen.play(&mut cube_render, &mut cube, &solution, &status);
+                        history.push(&solution);
+                        session_log.log(SessionEvent::Played(solution));
+                    }
+                }
+            }
+
+            KeyCode::Char('a' | 'A') => {
+                // if the key is "A", play the last solution if the cube is still the one it
+                // solves
+                match &last_solution {
+                    Some((hash, algo)) if *hash == cube.zobrist_hash() => {
+                        move_count += algo.len();
+                        let explanations = screen.play_solution(
+                            &mut cube_render,
+                            &mut cube,
+                            algo,
+                            &status,
+                            explain.then_some(heuristic),
+                        );
+                        let shown = explanations.len().saturating_sub(EXPLANATION_LINES);
+                        message = explanations[shown..].join("\n");
+                        history.push(algo);
+                        session_log.log(SessionEvent::Played(algo.clone()));
+                    }
+                    _ => message = "No solution for this cube, find one with S or G".to_string(),
+                }
+            }
+
+            KeyCode::Char('H') => {
+                // if the key is "H", show the first turn of a solution, using the last one found
+                // if it's for this cube, and how many turns it has if "H" is pressed again
                 if cube.is_solved() {
-                    println!("The cube is solved");
+                    message = "The cube is already solved".to_string();
+                    continue;
+                }
+                let hash = cube.zobrist_hash();
+                if let Some((_, solution)) = hint.as_ref().filter(|(h, _)| *h == hash) {
+                    message = format!("Hint: {} ({} turns left)", solution[0], solution.len());
+                    continue;
+                }
+                let solution = match &last_solution {
+                    Some((h, solution)) if *h == hash => solution.clone(),
+                    _ => {
+                        let config = SearchConfig {
+                            move_set: MoveSet::default_for(cube.size()),
+                            cancel_token: Some(cancel_token.clone()),
+                            print_progress: true,
+                            fallback: true,
+                            ..SearchConfig::default()
+                        };
+                        let result = screen.in_line_mode(|| {
+                            cancel_token.store(false, Ordering::SeqCst);
+                            searching.store(true, Ordering::SeqCst);
+                            let result = solve(&cube, &config, solver, heuristic);
+                            searching.store(false, Ordering::SeqCst);
+                            result
+                        });
+                        match result {
+                            Some(SearchResult {
+                                solution: Some(solution),
+                                ..
+                            }) => solution,
+                            Some(result) => {
+                                message = result.to_string();
+                                continue;
+                            }
+                            None => {
+                                message =
+                                    format!("The {} solver can't solve this cube", solver.name());
+                                continue;
+                            }
+                        }
+                    }
+                };
+                message = format!(
+                    "Hint: {}, press H again for how many turns are left",
+                    solution[0]
+                );
+                hint = Some((hash, solution));
+            }
+
+            KeyCode::Char('^') => {
+                // if the key is "^", prompts the solver that S uses
+                let names: Vec<&str> = Solver::ALL.iter().map(Solver::name).collect();
+                let name = screen.prompt(&format!("Type the solver ({}): ", names.join(", ")));
+                message = match Solver::from_name(name.trim()) {
+                    Some(chosen) => {
+                        solver = chosen;
+                        format!("Solving with {}", solver.name())
+                    }
+                    None => format!("Unknown solver, the solvers are {}", names.join(", ")),
+                };
+            }
+
+            KeyCode::Char('$') => {
+                // if the key is "$", prompts the heuristic of IDA* when S uses it
+                let names: Vec<&str> = Heuristic::ALL.iter().map(Heuristic::name).collect();
+                let name = screen.prompt(&format!("Type the heuristic ({}): ", names.join(", ")));
+                message = match Heuristic::from_name(name.trim()) {
+                    Some(chosen) => {
+                        heuristic = chosen;
+                        format!("IDA* uses the {} heuristic", heuristic.name())
+                    }
+                    None => format!("Unknown heuristic, the heuristics are {}", names.join(", ")),
+                };
+            }
+
+            KeyCode::Char('|') => {
+                // if the key is "|", prompts solvers, with the heuristic of IDA* after a colon,
+                // then solve the cube with each of them and show their results in a table
+                let input = screen.prompt(
+                    "Type the solvers to compare, like two-phase idastar:coordinate (every solver by default): ",
+                );
+                let runs = match parse_solvers(&input, heuristic) {
+                    Ok(runs) if runs.is_empty() => Solver::ALL
+                        .into_iter()
+                        .filter(|solver| solver.supports(cube.size()))
+                        .map(|solver| (solver, heuristic))
+                        .collect(),
+                    Ok(runs) => runs,
+                    Err(msg) => {
+                        message = msg;
+                        continue;
+                    }
+                };
+                if let Some((unsupported, _)) = runs
+                    .iter()
+                    .find(|(solver, _)| !solver.supports(cube.size()))
+                {
+                    message = format!("The {} solver can't solve this cube", unsupported.name());
+                    continue;
+                }
+                let config = SearchConfig {
+                    move_set: MoveSet::default_for(cube.size()),
+                    cancel_token: Some(cancel_token.clone()),
+                    print_progress: true,
+                    ..SearchConfig::default()
+                };
+                let results: Vec<_> = screen.in_line_mode(|| {
+                    runs.iter()
+                        .map(|&(solver, heuristic)| {
+                            println!("Solving with {}", solver.name());
+                            // Ctrl+C only skips the solver that is running
+                            cancel_token.store(false, Ordering::SeqCst);
+                            searching.store(true, Ordering::SeqCst);
+                            let start_time = Instant::now();
+                            let result = solve(&cube, &config, solver, heuristic);
+                            searching.store(false, Ordering::SeqCst);
+                            (result, start_time.elapsed())
+                        })
+                        .collect()
+                });
+                let mut rows = vec![format!(
+                    "{:<10} {:<12} {:>6} {:>12} {:>9}  {:<8} Status",
+                    "Solver", "Heuristic", "Length", "Nodes", "Time", "Optimal"
+                )];
+                for (&(solver, heuristic), (result, time)) in runs.iter().zip(results) {
+                    // IDA* is the only solver that uses the heuristic
+                    let heuristic = match solver {
+                        Solver::IdaStar => heuristic.name(),
+                        Solver::Auto if cube.size() > 3 => heuristic.name(),
+                        _ => "-",
+                    };
+                    let (length, nodes, optimal, status) = match &result {
+                        Some(result) => (
+                            result
+                                .solution_len
+                                .map_or("-".to_string(), |len| len.to_string()),
+                            result.node_visited.to_string(),
+                            match (&result.solution, result.possibly_suboptimal) {
+                                (None, _) => "-",
+                                (Some(_), false) => "yes",
+                                (Some(_), true) => "maybe",
+                            },
+                            result.status.name(),
+                        ),
+                        None => ("-".to_string(), "-".to_string(), "-", "no-solution"),
+                    };
+                    rows.push(format!(
+                        "{:<10} {heuristic:<12} {length:>6} {nodes:>12} {:>8.3}s  {optimal:<8} {status}",
+                        solver.name(),
+                        time.as_secs_f64(),
+                    ));
+                }
+                message = rows.join("\n");
+            }
+
+            KeyCode::Char('?') => {
+                // if the key is "?", show the help with the settings in use until a key is
+                // pressed
+                let mut keys: Vec<String> = config
+                    .keys
+                    .iter()
+                    .map(|(c, key)| {
+                        let (name, _, _) = COMMANDS
+                            .iter()
+                            .find(|(_, command_key, _)| command_key == key)
+                            .expect("Error when finding the command of a key");
+                        format!("{c} for :{name}")
+                    })
+                    .collect();
+                keys.sort();
+                let settings = [
+                    ("Size", format!("{0}x{0}", cube.size())),
+                    ("Solver of S", solver.name().to_string()),
+                    ("Heuristic of IDA*", heuristic.name().to_string()),
+                    ("Colors", Palette::current().name().to_string()),
+                    ("Turns of a scramble", config.scramble_length.to_string()),
+                    (
+                        "Speed of the turns",
+                        format!("{:.1} per second", screen.turn_speed()),
+                    ),
+                    (
+                        "Play the solutions as soon as they're found",
+                        if auto_play { "yes" } else { "no" }.to_string(),
+                    ),
+                    (
+                        "Explain the turns played",
+                        if explain { "yes" } else { "no" }.to_string(),
+                    ),
+                    (
+                        "Keys of the config",
+                        if keys.is_empty() {
+                            "none".to_string()
+                        } else {
+                            keys.join(", ")
+                        },
+                    ),
+                ];
+                screen.show_pages(&help_lines(&settings));
+            }
+
+            KeyCode::Char('%') => {
+                // if the key is "%", show the number of solves, the best time and the averages
+                message = solve_history.to_string();
+            }
+
+            KeyCode::Char('{') => {
+                // if the key is "{", prompts the scramble like "M", then time a solve of a real
+                // cube scrambled with it. the cube of the app isn't changed
+                let input = screen.prompt(&format!(
+                    "Type number of turns of the scramble to time ({} by default), --seed S, --official or --daily: ",
+                    config.scramble_length
+                ));
+                let options =
+                    match ScrambleOptions::parse(&input.split_whitespace().collect::<Vec<_>>()) {
+                        Ok(options) => options,
+                        Err(msg) => {
+                            message = msg;
+                            continue;
+                        }
+                    };
+                let mut scrambled = Cube::new(cube.size());
+                let algo = match options.scramble(&mut scrambled, config.scramble_length) {
+                    Ok(algo) => algo,
+                    Err(msg) => {
+                        message = msg;
+                        continue;
+                    }
+                };
+                let Some(time) = screen.time_solve(&cube_render, &scrambled, &algo) else {
+                    message = "The solve wasn't timed".to_string();
+                    continue;
+                };
+                let personal_best = solve_history.add(time);
+                message = format!(
+                    "Solved in {}{}\n{solve_history}",
+                    format_time(Some(time)),
+                    if personal_best {
+                        ", a personal best!"
+                    } else {
+                        ""
+                    }
+                );
+                if save_solves {
+                    if let Err(error) = solve_history.save(Path::new(SOLVES_PATH)) {
+                        message = format!("Error when saving the solve to {SOLVES_PATH}: {error}");
+                    }
+                }
+            }
+
+            KeyCode::Char('}') => {
+                // if the key is "}", prompts the scramble like "M", then run the fewest moves
+                // challenge on it. the cube of the app isn't changed
+                let input = screen.prompt(&format!(
+                    "Type number of turns of the scramble to solve in the fewest moves ({} by default), --seed S, --official or --daily: ",
+                    config.scramble_length
+                ));
+                let options =
+                    match ScrambleOptions::parse(&input.split_whitespace().collect::<Vec<_>>()) {
+                        Ok(options) => options,
+                        Err(msg) => {
+                            message = msg;
+                            continue;
+                        }
+                    };
+                let mut scrambled = Cube::new(cube.size());
+                let algo = match options.scramble(&mut scrambled, config.scramble_length) {
+                    Ok(algo) => algo,
+                    Err(msg) => {
+                        message = msg;
+                        continue;
+                    }
+                };
+                message = screen.fewest_moves(&cube_render, cube.size(), &algo);
+            }
+
+            KeyCode::Char(')') => {
+                // if the key is ")", show the statistics of the user over all sessions
+                message = profile.to_string();
+            }
+
+            KeyCode::Char(';') => {
+                // if the key is ";", switch between explaining the turns played by A and Space
+                // and not
+                explain = !explain;
+                message = if explain {
+                    "A and Space explain each turn: the heuristic of IDA*, the distance of the 2x2 if it's known and the pieces placed".to_string()
                 } else {
-                    println!("The cube is not solved");
-                }
-            }
-
-            "M" => {
-                // if the command is "M", prompts a number then scramble.
-                print!("Type number of turns to scramble: ");
-                stdout().flush().expect("Error when printing text");
-                let mut k = String::new();
-                stdin()
-                    .read_line(&mut k)
-                    .expect("Error when reading command");
-                let k: usize = k.trim().parse().expect("Can't parse to a number :(");
-                let algo = cube.scramble(k);
-                print!("Scramble sequence: ");
-                println!("{}", Turn::algo_string(&algo));
-                cube_render.update_colors(&cube);
+                    "A and Space don't explain the turns".to_string()
+                };
             }
 
-            "VW" => cube_render.rotate_pitch(rotate_speed),
-            "VS" => cube_render.rotate_pitch(-rotate_speed),
-            "VA" => cube_render.rotate_yaw(rotate_speed),
-            "VD" => cube_render.rotate_yaw(-rotate_speed),
+            KeyCode::Char('!') => {
+                // if the key is "!", switch between playing the solutions as soon as they're
+                // found and waiting for A
+                auto_play = !auto_play;
+                message = if auto_play {
+                    "The solutions are played as soon as they're found".to_string()
+                } else {
+                    "The solutions are played with A".to_string()
+                };
+            }
 
-            "S" => {
-                // if the command is "S", run IDA*
-                let result = idastar(cube.clone(), &search::single_l0, true);
-                println!("{result}");
+            KeyCode::Char('~') => {
+                // if the key is "~", prompts how many turns to play per second
+                let speed = screen.prompt(&format!(
+                    "Type the number of turns to play per second (up to {MAX_TURN_SPEED}): "
+                ));
+                message = match speed.trim().parse() {
+                    Ok(speed) if screen.set_turn_speed(speed) => {
+                        format!("Playing {speed} turns per second")
+                    }
+                    _ => format!("The speed must be a number above 0 and up to {MAX_TURN_SPEED}"),
+                };
+            }
+
+            KeyCode::Char(' ') => {
+                // if the key is Space, highlight the first turn of the last solution, then play
+                // the highlighted turn and highlight the next one at every press
+                let mut explanation = String::new();
+                let turns = match steps.take() {
+                    Some((_, turns)) => {
+                        move_count += 1;
+                        let before = cube.clone();
+                        screen.play(&mut cube_render, &mut cube, &turns[..1], &status);
+                        if explain {
+                            explanation = explain_turn(&before, &turns[0], &cube, heuristic) + "\n";
+                        }
+                        history.push(&turns[..1]);
+                        session_log.log(SessionEvent::Played(turns[..1].to_vec()));
+                        turns[1..].to_vec()
+                    }
+                    None => match &last_solution {
+                        Some((hash, algo)) if *hash == cube.zobrist_hash() => algo.clone(),
+                        _ => {
+                            message = "No solution for this cube, find one with S or G".to_string();
+                            continue;
+                        }
+                    },
+                };
+                cube_render.set_highlight(turns.first().copied());
+                message = match turns.first() {
+                    Some(turn) => format!("{explanation}Next turn: {turn} ({} left)", turns.len()),
+                    None => format!("{explanation}The solution is done"),
+                };
+                if !turns.is_empty() {
+                    steps = Some((cube.zobrist_hash(), turns));
+                }
+            }
+
+            KeyCode::Char('p' | 'P') => {
+                // if the key is "P", search for the algorithm that makes the current cube
+                // (for example a pattern) from a solved cube. every face is turned, since the
+                // stickers have to match exactly, even on the 2x2
+                let config = SearchConfig {
+                    move_set: MoveSet::HalfTurn,
+                    cancel_token: Some(cancel_token.clone()),
+                    print_progress: true,
+                    ..SearchConfig::default()
+                };
+                let result = screen.in_line_mode(|| {
+                    cancel_token.store(false, Ordering::SeqCst);
+                    searching.store(true, Ordering::SeqCst);
+                    let result = search::find_pattern_algorithm(&cube, &config);
+                    searching.store(false, Ordering::SeqCst);
+                    result
+                });
+                message = result.to_string();
             }
 
-            // if it's none of the above:
-            // check if we can parse the input into a list of moves. if we can't parse
-            // (`parse_algorithm` returns `None`), prompt the user and execute the innter loop
-            // again, thus prompting the user's input again. else if we can parse it then apply
-            // the turns to the cube.
-            _ => match parse_algorithm(cmd.split_whitespace().collect()) {
-                None => {
-                    println!("Invalid command");
+            KeyCode::Char('"') => {
+                // if the key is '"', let the user pick a pattern, then apply its algorithm to
+                // the cube, or search for a shorter algorithm making it from a solved cube
+                let picked = screen.pick_pattern(&mut cube_render, cube.size());
+                cube_render.update_colors(&cube);
+                let Some((index, action)) = picked else {
                     continue;
+                };
+                let (name, algo) = search::PATTERNS_3X3[index];
+                let algo = Turn::parse_algorithm(algo.split_whitespace().collect())
+                    .expect("Error when reading the algorithm of a pattern");
+                match action {
+                    PatternAction::Apply => {
+                        move_count += algo.len();
+                        screen.play(&mut cube_render, &mut cube, &algo, &status);
+                        history.push(&algo);
+                        message = format!("{name}: {}", Turn::algo_string(&algo));
+                        session_log.log(SessionEvent::Turns(algo));
+                    }
+                    PatternAction::FindShorter => {
+                        let mut pattern = Cube::new(cube.size());
+                        pattern.apply_algorithm(algo.clone());
+                        let config = SearchConfig {
+                            move_set: MoveSet::HalfTurn,
+                            cancel_token: Some(cancel_token.clone()),
+                            print_progress: true,
+                            ..SearchConfig::default()
+                        };
+                        let result = screen.in_line_mode(|| {
+                            cancel_token.store(false, Ordering::SeqCst);
+                            searching.store(true, Ordering::SeqCst);
+                            let result = search::find_pattern_algorithm(&pattern, &config);
+                            searching.store(false, Ordering::SeqCst);
+                            result
+                        });
+                        message = match &result.solution {
+                            Some(found) if found.len() < algo.len() => format!(
+                                "{name} in {} turns instead of {}: {}",
+                                found.len(),
+                                algo.len(),
+                                Turn::algo_string(found)
+                            ),
+                            Some(_) => format!(
+                                "No shorter algorithm makes {name} than {}",
+                                Turn::algo_string(&algo)
+                            ),
+                            None => format!("{name}: {result}"),
+                        };
+                    }
                 }
-                Some(algo) => {
-                    cube.apply_algorithm(algo);
-                    cube_render.update_colors(&cube);
+            }
+
+            KeyCode::Char('w' | 'W') => {
+                // if the key is "W", prompts an algorithm and show the cube before and after it
+                // side by side, without applying it
+                let algo = screen.prompt_completing(
+                    "Type the turns to compare the cube with: ",
+                    Completion::Turns,
+                );
+                let algo = match Turn::parse_algorithm(algo.split_whitespace().collect()) {
+                    None => {
+                        message = "Invalid turns".to_string();
+                        continue;
+                    }
+                    Some(algo) if algo.iter().any(|turn| turn.get_layer() > cube.size()) => {
+                        message = "The cube doesn't have these layers".to_string();
+                        continue;
+                    }
+                    Some(algo) => algo,
+                };
+                let mut after_cube = cube.clone();
+                after_cube.apply_algorithm(algo.clone());
+
+                // two half-width renders, which fit the cube in their width
+                let mut before = cube_render.clone();
+                before.set_image_size(img_w / 2 - 1, img_h);
+                let mut after = before.clone();
+                after.update_colors(&after_cube);
+                let lines = rendering::render_side_by_side(&before, &after);
+                let caption = format!(
+                    "Before and after {}, press any key to go back",
+                    Turn::algo_string(&algo)
+                );
+                screen.draw_lines(&lines, &status, &caption);
+                while !matches!(
+                    event::read().expect("Error when reading command"),
+                    Event::Key(key) if key.kind == KeyEventKind::Press
+                ) {}
+            }
+
+            KeyCode::Char('j' | 'J') => {
+                // if the key is "J", prompts an algorithm and draw on the net where it moves the
+                // pieces, without applying it, or remove the arrows if it's empty
+                let algo = screen
+                    .prompt_completing("Type the turns to draw the arrows of: ", Completion::Turns);
+                let algo = match Turn::parse_algorithm(algo.split_whitespace().collect()) {
+                    None => {
+                        message = "Invalid turns".to_string();
+                        continue;
+                    }
+                    Some(algo) if algo.iter().any(|turn| turn.get_layer() > cube.size()) => {
+                        message = "The cube doesn't have these layers".to_string();
+                        continue;
+                    }
+                    Some(algo) => algo,
+                };
+                cube_render.set_arrows(&algo);
+                if !algo.is_empty() {
+                    cube_render.set_mode(RenderMode::Net);
+                    message = format!("Arrows of {}", Turn::algo_string(&algo));
                 }
-            },
+            }
+
+            KeyCode::Char('n' | 'N') => {
+                // if the key is "N", look up the distance to solved in the depth table
+                message = match (cube.size(), search::depth_table_2x2()) {
+                    (2, Some(depth_table)) => match search::distance_2x2(&cube, depth_table) {
+                        Some(distance) => {
+                            format!("The cube is {distance} quarter turns from solved")
+                        }
+                        None => "The cube isn't solvable".to_string(),
+                    },
+                    (2, None) => "The depth table isn't built yet, build it with T".to_string(),
+                    _ => "The distance is only known for the 2x2".to_string(),
+                };
+            }
+
+            KeyCode::Char('t' | 'T') => {
+                // if the key is "T", build the exact depth table used by "S" for the 2x2
+                message = match screen.in_line_mode(search::build_depth_table_2x2) {
+                    Ok(_) => "The depth table is built".to_string(),
+                    Err(e) => format!("Can't build the depth table: {e}"),
+                };
+            }
+
+            _ => (),
         }
     }
+
+    screen.leave();
 }
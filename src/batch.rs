@@ -0,0 +1,29 @@
+use std::{fs, io, path::Path};
+
+use rubiks::cube::Turn;
+
+/// Reads a file with an algorithm per line, like `R U R' U'`, and returns each algorithm with the
+/// number of its line. Empty lines and lines starting with `//` are skipped, so that algorithm
+/// sheets can have titles and notes.
+///
+/// Returns an `InvalidData` error with the first line that isn't an algorithm, or that turns
+/// layers that a cube of size `size` doesn't have.
+pub fn read_algorithm_file(path: &Path, size: usize) -> io::Result<Vec<(usize, Vec<Turn>)>> {
+    let mut algorithms = Vec::new();
+    for (i, line) in fs::read_to_string(path)?.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        let algo = Turn::parse_algorithm(line.split_whitespace().collect())
+            .filter(|algo| algo.iter().all(|turn| turn.get_layer() <= size))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid algorithm on line {}: `{line}`", i + 1),
+                )
+            })?;
+        algorithms.push((i + 1, algo));
+    }
+    Ok(algorithms)
+}
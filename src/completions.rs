@@ -0,0 +1,223 @@
+use rubiks::search::{Heuristic, Solver};
+
+/// Shells that `script` writes completions for.
+pub const SHELLS: [&str; 3] = ["bash", "zsh", "fish"];
+
+/// Subcommands of `rubiks` with their flags. Without a subcommand, the app takes `APP_FLAGS`.
+const SUBCOMMANDS: [(&str, &[&str]); 5] = [
+    (
+        "solve",
+        &[
+            "--size",
+            "--timeout",
+            "--solver",
+            "--heuristic",
+            "--facelets",
+            "--url",
+            "--json",
+            "--verify",
+        ],
+    ),
+    (
+        "scramble",
+        &[
+            "--size",
+            "--length",
+            "--seed",
+            "--official",
+            "--daily",
+            "--facelets",
+            "--json",
+        ],
+    ),
+    (
+        "apply",
+        &[
+            "--file",
+            "--size",
+            "--reset",
+            "--render",
+            "--solve",
+            "--solver",
+            "--heuristic",
+            "--timeout",
+            "--facelets",
+        ],
+    ),
+    ("pipe", &[]),
+    ("completions", &[]),
+];
+const APP_FLAGS: [&str; 3] = ["--size", "--no-color", "--gui"];
+/// Flags that every subcommand and the app take.
+const GLOBAL_FLAGS: [&str; 6] = ["--config", "-q", "--quiet", "-v", "--verbose", "--help"];
+/// Flags followed by a value, which other flags can't be completed in place of.
+const VALUE_FLAGS: [&str; 8] = [
+    "--size",
+    "--timeout",
+    "--solver",
+    "--heuristic",
+    "--length",
+    "--seed",
+    "--file",
+    "--config",
+];
+
+/// Returns the script that completes the subcommands, the flags and the names of the solvers and
+/// the heuristics of `rubiks` in `shell`, one of `SHELLS`, or `None` for other shells.
+pub fn script(shell: &str) -> Option<String> {
+    match shell {
+        "bash" => Some(bash_script()),
+        "zsh" => Some(zsh_script()),
+        "fish" => Some(fish_script()),
+        _ => None,
+    }
+}
+
+fn solver_names() -> String {
+    Solver::ALL.map(|solver| solver.name()).join(" ")
+}
+
+fn heuristic_names() -> String {
+    Heuristic::ALL.map(|heuristic| heuristic.name()).join(" ")
+}
+
+/// Returns the words completed without a subcommand: the subcommands and the flags of the app.
+fn top_level_words() -> String {
+    let subcommands = SUBCOMMANDS.iter().map(|(name, _)| *name);
+    subcommands
+        .chain(APP_FLAGS)
+        .chain(GLOBAL_FLAGS)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns `flags` and the global flags.
+fn flags_of(flags: &[&str]) -> String {
+    flags
+        .iter()
+        .copied()
+        .chain(GLOBAL_FLAGS)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn bash_script() -> String {
+    let subcommand_cases: String = SUBCOMMANDS
+        .iter()
+        .map(|(name, flags)| match *name {
+            "completions" => format!("        {name}) words=\"{}\" ;;\n", SHELLS.join(" ")),
+            _ => format!("        {name}) words=\"{}\" ;;\n", flags_of(flags)),
+        })
+        .collect();
+    format!(
+        r#"_rubiks() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    local prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    local words
+    case "$prev" in
+        --solver) COMPREPLY=($(compgen -W "{solvers}" -- "$cur")); return ;;
+        --heuristic) COMPREPLY=($(compgen -W "{heuristics}" -- "$cur")); return ;;
+        --config|--file) COMPREPLY=($(compgen -f -- "$cur")); return ;;
+        {value_flags}) return ;;
+    esac
+    if [ "$COMP_CWORD" -eq 1 ]; then
+        words="{top_level}"
+    else
+        case "${{COMP_WORDS[1]}}" in
+{subcommand_cases}        *) words="{app_flags}" ;;
+        esac
+    fi
+    COMPREPLY=($(compgen -W "$words" -- "$cur"))
+}}
+complete -F _rubiks rubiks
+"#,
+        solvers = solver_names(),
+        heuristics = heuristic_names(),
+        value_flags = VALUE_FLAGS.join("|"),
+        top_level = top_level_words(),
+        app_flags = flags_of(&APP_FLAGS),
+    )
+}
+
+fn zsh_script() -> String {
+    let subcommand_cases: String = SUBCOMMANDS
+        .iter()
+        .map(|(name, flags)| match *name {
+            "completions" => format!("        {name}) compadd -- {} ;;\n", SHELLS.join(" ")),
+            _ => format!("        {name}) compadd -- {} ;;\n", flags_of(flags)),
+        })
+        .collect();
+    format!(
+        r#"#compdef rubiks
+
+_rubiks() {{
+    case "$words[CURRENT-1]" in
+        --solver) compadd -- {solvers}; return ;;
+        --heuristic) compadd -- {heuristics}; return ;;
+        --config|--file) _files; return ;;
+        {value_flags}) return ;;
+    esac
+    if (( CURRENT == 2 )); then
+        compadd -- {top_level}
+        return
+    fi
+    case "$words[2]" in
+{subcommand_cases}        *) compadd -- {app_flags} ;;
+    esac
+}}
+
+_rubiks "$@"
+"#,
+        solvers = solver_names(),
+        heuristics = heuristic_names(),
+        value_flags = VALUE_FLAGS.join("|"),
+        top_level = top_level_words(),
+        app_flags = flags_of(&APP_FLAGS),
+    )
+}
+
+/// Returns the fish option of `flag`: `-l name` for `--name` and `-s x` for `-x`.
+fn fish_option(flag: &str) -> String {
+    match flag.strip_prefix("--") {
+        Some(name) => format!("-l {name}"),
+        None => format!("-s {}", &flag[1..]),
+    }
+}
+
+fn fish_script() -> String {
+    let subcommand_names: Vec<&str> = SUBCOMMANDS.iter().map(|(name, _)| *name).collect();
+    let mut lines = vec![
+        "complete -c rubiks -f".to_string(),
+        format!(
+            "complete -c rubiks -n '__fish_use_subcommand' -a '{}'",
+            subcommand_names.join(" ")
+        ),
+    ];
+    // the global flags are completed whatever the subcommand
+    let fish_line = |condition: Option<&str>, flag: &str| {
+        let value = match flag {
+            "--solver" => format!(" -x -a '{}'", solver_names()),
+            "--heuristic" => format!(" -x -a '{}'", heuristic_names()),
+            "--config" | "--file" => " -r -F".to_string(),
+            flag if VALUE_FLAGS.contains(&flag) => " -x".to_string(),
+            _ => String::new(),
+        };
+        let condition = condition.map_or(String::new(), |condition| format!(" -n '{condition}'"));
+        format!("complete -c rubiks{condition} {}{value}", fish_option(flag))
+    };
+    for flag in APP_FLAGS {
+        lines.push(fish_line(Some("__fish_use_subcommand"), flag));
+    }
+    for (name, flags) in SUBCOMMANDS {
+        let condition = format!("__fish_seen_subcommand_from {name}");
+        lines.extend(flags.iter().map(|flag| fish_line(Some(&condition), flag)));
+    }
+    for flag in GLOBAL_FLAGS {
+        lines.push(fish_line(None, flag));
+    }
+    lines.push(format!(
+        "complete -c rubiks -n '__fish_seen_subcommand_from completions' -a '{}'",
+        SHELLS.join(" ")
+    ));
+    lines.join("\n") + "\n"
+}
@@ -0,0 +1,171 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crossterm::event::KeyCode;
+use serde::Deserialize;
+
+use rubiks::{
+    cube::palette::Palette,
+    search::{Heuristic, Solver},
+};
+
+use crate::app::command_key;
+
+/// Narrowest image of the cube, so that two half-width images side by side, like the ones of a
+/// race, are still at least a character wide.
+const MIN_WIDTH: usize = 4;
+
+/// Settings of the app, read from a TOML file by `Config::load`. Every setting is optional, the
+/// ones that aren't in the file keep their default values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// Size of the cube when `--size` isn't given.
+    pub size: usize,
+    /// Size of the image of the cube in the terminal, in characters. The width is at least
+    /// `MIN_WIDTH`.
+    pub width: usize,
+    pub height: usize,
+    /// Colors of the stickers, set as the current palette when the config is loaded.
+    pub palette: Palette,
+    /// Keys that run a command instead of what they usually do, with the key that usually runs
+    /// the command.
+    pub keys: HashMap<char, KeyCode>,
+    /// What the cube is solved with when it isn't chosen in the app or with `--solver`.
+    pub solver: Solver,
+    pub heuristic: Heuristic,
+    /// Number of random turns of a scramble when it isn't given.
+    pub scramble_length: usize,
+}
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            size: 2,
+            width: 80,
+            height: 29,
+            palette: Palette::STANDARD,
+            keys: HashMap::new(),
+            solver: Solver::default(),
+            heuristic: Heuristic::default(),
+            scramble_length: 20,
+        }
+    }
+}
+
+/// The config as it's written in the file, like
+///
+/// ```toml
+/// size = 3
+/// width = 100
+/// height = 36
+/// palette = "deuteranopia-safe"   # or high-contrast, or the path of a palette file
+/// solver = "two-phase"
+/// heuristic = "coordinate"
+/// scramble_length = 25
+///
+/// [keys]
+/// solve = "S"                     # the commands are named like after ":"
+/// ```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    size: Option<usize>,
+    width: Option<usize>,
+    height: Option<usize>,
+    palette: Option<String>,
+    keys: Option<BTreeMap<String, char>>,
+    solver: Option<String>,
+    heuristic: Option<String>,
+    scramble_length: Option<usize>,
+}
+
+impl Config {
+    /// Returns where the config is read from without `--config`:
+    /// `~/.config/rubiks-solver/config.toml`, or in `$XDG_CONFIG_HOME` if it's set.
+    pub fn default_path() -> Option<PathBuf> {
+        let config_dir = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(config_dir.join("rubiks-solver").join("config.toml"))
+    }
+
+    /// Reads the config at `path`, or at `Config::default_path` if it isn't given, where the
+    /// default config is used if there's no file. Returns an `InvalidData` error with the first
+    /// setting that is invalid.
+    pub fn load(path: Option<&Path>) -> io::Result<Config> {
+        let text = match path {
+            Some(path) => fs::read_to_string(path)?,
+            None => match Config::default_path().map(fs::read_to_string) {
+                Some(Ok(text)) => text,
+                None => return Ok(Config::default()),
+                Some(Err(e)) if e.kind() == io::ErrorKind::NotFound => {
+                    log::debug!("There's no config file, using the default settings");
+                    return Ok(Config::default());
+                }
+                Some(Err(e)) => return Err(e),
+            },
+        };
+        log::info!(
+            "Reading the config from {}",
+            path.map(Path::to_path_buf)
+                .or_else(Config::default_path)
+                .unwrap_or_default()
+                .display()
+        );
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let file: ConfigFile = toml::from_str(&text).map_err(|e| invalid(e.to_string()))?;
+
+        let mut config = Config::default();
+        if let Some(size) = file.size {
+            if size < 2 {
+                return Err(invalid(format!("the size must be at least 2, got {size}")));
+            }
+            config.size = size;
+        }
+        if let Some(width) = file.width.filter(|&width| width < MIN_WIDTH) {
+            return Err(invalid(format!(
+                "the width must be at least {MIN_WIDTH}, got {width}"
+            )));
+        }
+        if file.height == Some(0) {
+            return Err(invalid("the height can't be 0".to_string()));
+        }
+        config.width = file.width.unwrap_or(config.width);
+        config.height = file.height.unwrap_or(config.height);
+        if let Some(name) = file.palette {
+            config.palette = match Palette::from_name(&name) {
+                Some(palette) => palette,
+                // a relative path is relative to the config file
+                None => {
+                    let config_dir = path
+                        .map(Path::to_path_buf)
+                        .or_else(Config::default_path)
+                        .and_then(|path| path.parent().map(Path::to_path_buf))
+                        .unwrap_or_default();
+                    Palette::from_file(&config_dir.join(&name))
+                        .map_err(|e| invalid(format!("can't read the palette {name}: {e}")))?
+                }
+            };
+        }
+        for (name, key) in file.keys.unwrap_or_default() {
+            let command = command_key(&name)
+                .ok_or_else(|| invalid(format!("unknown command `{name}` in the keys")))?;
+            if config.keys.insert(key, command).is_some() {
+                return Err(invalid(format!("the key `{key}` runs several commands")));
+            }
+        }
+        if let Some(name) = file.solver {
+            config.solver = Solver::from_name(&name)
+                .ok_or_else(|| invalid(format!("unknown solver `{name}`")))?;
+        }
+        if let Some(name) = file.heuristic {
+            config.heuristic = Heuristic::from_name(&name)
+                .ok_or_else(|| invalid(format!("unknown heuristic `{name}`")))?;
+        }
+        config.scramble_length = file.scramble_length.unwrap_or(config.scramble_length);
+        Ok(config)
+    }
+}
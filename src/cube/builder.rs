@@ -0,0 +1,181 @@
+//! `CubeBuilder`, which makes a cube of any size with the colors of a `Scheme`, turned by an
+//! algorithm or by a seeded scramble.
+
+use std::{error::Error, fmt::Display};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use super::{Color, Cube, Face, FaceDir, Turn};
+
+/// Which color each face of a solved cube has.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scheme {
+    /// Yellow up and orange front, the colors of `Cube::new`.
+    #[default]
+    YellowTop,
+    /// White up and green front, how the WCA scrambles are held.
+    WcaWhiteTop,
+}
+impl Scheme {
+    /// Returns the color of the face at `face_dir` on a solved cube.
+    ///
+    /// Both schemes are the same cube held differently, so the faces opposite each other have the
+    /// same colors and the solvers handle either.
+    pub fn color(&self, face_dir: FaceDir) -> Color {
+        match self {
+            Scheme::YellowTop => Cube::get_init_color(face_dir),
+            Scheme::WcaWhiteTop => match face_dir {
+                FaceDir::Up => Color::White,
+                FaceDir::Down => Color::Yellow,
+                FaceDir::Front => Color::Green,
+                FaceDir::Back => Color::Blue,
+                FaceDir::Right => Color::Red,
+                FaceDir::Left => Color::Orange,
+            },
+        }
+    }
+}
+
+/// Why `CubeBuilder::build` couldn't make the cube.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// The size is less than 2.
+    Size(usize),
+    /// A turn of the algorithm turns a layer that the cube doesn't have.
+    Layer(Turn),
+}
+impl Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::Size(size) => write!(f, "the size must be at least 2, got {size}"),
+            BuildError::Layer(turn) => write!(
+                f,
+                "{turn} turns layer {}, which the cube doesn't have",
+                turn.get_layer()
+            ),
+        }
+    }
+}
+impl Error for BuildError {}
+
+/// Makes a cube step by step, see `Cube::builder`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CubeBuilder {
+    size: usize,
+    scheme: Scheme,
+    /// Number of random turns of the scramble, and the seed of their random numbers.
+    scramble: Option<(usize, u64)>,
+    algorithm: Vec<Turn>,
+}
+impl Default for CubeBuilder {
+    fn default() -> Self {
+        CubeBuilder {
+            size: 3,
+            scheme: Scheme::default(),
+            scramble: None,
+            algorithm: Vec::new(),
+        }
+    }
+}
+impl CubeBuilder {
+    /// Sets the size of the cube, 3 if it isn't set.
+    pub fn size(mut self, size: usize) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets the colors of the faces, `Scheme::YellowTop` if they aren't set.
+    pub fn scheme(mut self, scheme: Scheme) -> Self {
+        self.scheme = scheme;
+        self
+    }
+
+    /// Scrambles the cube with `length` random turns like `Cube::scramble`, always the same ones
+    /// for the same `seed`, whatever the version of rand, since they come from `ChaCha8Rng`.
+    pub fn scrambled(mut self, length: usize, seed: u64) -> Self {
+        self.scramble = Some((length, seed));
+        self
+    }
+
+    /// Applies `algo` to the cube, after the scramble if there's one.
+    pub fn algorithm(mut self, algo: &[Turn]) -> Self {
+        self.algorithm.extend_from_slice(algo);
+        self
+    }
+
+    /// Makes the cube, or returns why it can't be made.
+    pub fn build(self) -> Result<Cube, BuildError> {
+        if self.size < 2 {
+            return Err(BuildError::Size(self.size));
+        }
+        if let Some(turn) = self
+            .algorithm
+            .iter()
+            .find(|turn| turn.get_layer() > self.size)
+        {
+            return Err(BuildError::Layer(*turn));
+        }
+        let mut cube = Cube::new(self.size);
+        cube.faces = cube
+            .dir_order
+            .map(|face_dir| Face::new(self.size, self.scheme.color(face_dir)));
+        if let Some((length, seed)) = self.scramble {
+            cube.scramble_with_rng(length, &mut ChaCha8Rng::seed_from_u64(seed));
+        }
+        cube.apply_algorithm(self.algorithm);
+        Ok(cube)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::TurnDir;
+
+    #[test]
+    fn rejects_small_sizes() {
+        assert_eq!(
+            Cube::builder().size(1).build().err(),
+            Some(BuildError::Size(1))
+        );
+        assert!(Cube::builder().size(2).build().is_ok());
+    }
+
+    #[test]
+    fn rejects_missing_layers() {
+        let turn = Turn::new_slice(FaceDir::Right, TurnDir::Clockwise, 3);
+        let builder = Cube::builder().size(2).algorithm(&[turn]);
+        assert_eq!(builder.build().err(), Some(BuildError::Layer(turn)));
+        assert!(Cube::builder().size(3).algorithm(&[turn]).build().is_ok());
+    }
+
+    #[test]
+    fn wca_scheme_is_solved() {
+        let cube = Cube::builder()
+            .size(3)
+            .scheme(Scheme::WcaWhiteTop)
+            .build()
+            .unwrap();
+        assert!(cube.is_solved());
+        assert_eq!(cube.get_face(&FaceDir::Up).colors[[1, 1]], Color::White);
+
+        let algo = Turn::parse_algorithm("R U F'".split(' ').collect()).unwrap();
+        let mut scrambled = Cube::builder()
+            .size(3)
+            .scheme(Scheme::WcaWhiteTop)
+            .algorithm(&algo)
+            .build()
+            .unwrap();
+        assert!(!scrambled.is_solved());
+        scrambled.apply_algorithm(Turn::inverse_algorithm(&algo));
+        assert!(scrambled.is_solved());
+    }
+
+    #[test]
+    fn same_seed_same_scramble() {
+        let build = |seed| Cube::builder().scrambled(20, seed).build().unwrap();
+        assert_eq!(build(7).facelet_codes(), build(7).facelet_codes());
+        assert_ne!(build(7).facelet_codes(), build(8).facelet_codes());
+    }
+}
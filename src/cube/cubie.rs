@@ -0,0 +1,558 @@
+//! The 2x2 and the 3x3 as the permutation and orientation of their corners and edges
+//! (`CubieCube`), and the coordinates of those that the solvers of `search` index their tables by.
+
+use std::sync::OnceLock;
+
+use rand::{seq::SliceRandom, Rng};
+
+use super::{Color, Cube, FaceDir, Turn, TurnDir};
+
+/// Number of corner pieces.
+pub const NUM_CORNERS: usize = 8;
+/// Number of edge pieces (on a 3x3).
+pub const NUM_EDGES: usize = 12;
+
+/// Number of values of the corner orientation coordinate, 3^7.
+pub const NUM_TWIST: usize = 2187;
+/// Number of values of the edge orientation coordinate, 2^11.
+pub const NUM_FLIP: usize = 2048;
+/// Number of values of the UD-slice coordinate, 12 choose 4.
+pub const NUM_SLICE: usize = 495;
+/// Number of values of the corner permutation coordinate, 8!.
+pub const NUM_CORNER_PERM: usize = 40320;
+/// Number of values of the permutation coordinate of the 8 edges of the U and D faces, 8!.
+pub const NUM_UD_EDGE_PERM: usize = 40320;
+/// Number of values of the permutation coordinate of the 4 UD-slice edges, 4!.
+pub const NUM_SLICE_PERM: usize = 24;
+
+/// Facelets of each corner position, as (face, row, column) on a 3x3.
+///
+/// Corner positions are URF, UFL, ULB, UBR, DFR, DLF, DBL, DRB (Kociemba's ordering). The first
+/// facelet of each corner is the one on the U or D face, and the others follow clockwise.
+const CORNER_FACELETS: [[(FaceDir, usize, usize); 3]; NUM_CORNERS] = [
+    [
+        (FaceDir::Up, 2, 2),
+        (FaceDir::Right, 0, 0),
+        (FaceDir::Front, 0, 2),
+    ],
+    [
+        (FaceDir::Up, 2, 0),
+        (FaceDir::Front, 0, 0),
+        (FaceDir::Left, 0, 2),
+    ],
+    [
+        (FaceDir::Up, 0, 0),
+        (FaceDir::Left, 0, 0),
+        (FaceDir::Back, 0, 2),
+    ],
+    [
+        (FaceDir::Up, 0, 2),
+        (FaceDir::Back, 0, 0),
+        (FaceDir::Right, 0, 2),
+    ],
+    [
+        (FaceDir::Down, 0, 2),
+        (FaceDir::Front, 2, 2),
+        (FaceDir::Right, 2, 0),
+    ],
+    [
+        (FaceDir::Down, 0, 0),
+        (FaceDir::Left, 2, 2),
+        (FaceDir::Front, 2, 0),
+    ],
+    [
+        (FaceDir::Down, 2, 0),
+        (FaceDir::Back, 2, 2),
+        (FaceDir::Left, 2, 0),
+    ],
+    [
+        (FaceDir::Down, 2, 2),
+        (FaceDir::Right, 2, 2),
+        (FaceDir::Back, 2, 0),
+    ],
+];
+
+/// Facelets of each edge position, as (face, row, column) on a 3x3.
+///
+/// Edge positions are UR, UF, UL, UB, DR, DF, DL, DB, FR, FL, BL, BR (Kociemba's ordering).
+const EDGE_FACELETS: [[(FaceDir, usize, usize); 2]; NUM_EDGES] = [
+    [(FaceDir::Up, 1, 2), (FaceDir::Right, 0, 1)],
+    [(FaceDir::Up, 2, 1), (FaceDir::Front, 0, 1)],
+    [(FaceDir::Up, 1, 0), (FaceDir::Left, 0, 1)],
+    [(FaceDir::Up, 0, 1), (FaceDir::Back, 0, 1)],
+    [(FaceDir::Down, 1, 2), (FaceDir::Right, 2, 1)],
+    [(FaceDir::Down, 0, 1), (FaceDir::Front, 2, 1)],
+    [(FaceDir::Down, 1, 0), (FaceDir::Left, 2, 1)],
+    [(FaceDir::Down, 2, 1), (FaceDir::Back, 2, 1)],
+    [(FaceDir::Front, 1, 2), (FaceDir::Right, 1, 0)],
+    [(FaceDir::Front, 1, 0), (FaceDir::Left, 1, 2)],
+    [(FaceDir::Back, 1, 2), (FaceDir::Left, 1, 0)],
+    [(FaceDir::Back, 1, 0), (FaceDir::Right, 1, 2)],
+];
+
+/// Index of the first UD-slice edge (FR). The slice edges are FR, FL, BL and BR.
+const FIRST_SLICE_EDGE: u8 = 8;
+
+/// The cube on the level of pieces (cubies) instead of stickers.
+///
+/// This is the representation used by optimal and two-phase solvers, since turns become small
+/// permutations and the state can be summarized by a few integer coordinates. On a 2x2 only the
+/// corners are meaningful.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct CubieCube {
+    /// `cp[i]` is the corner at position `i`.
+    pub cp: [u8; NUM_CORNERS],
+    /// `co[i]` is the orientation of the corner at position `i`, how many times it's twisted
+    /// clockwise.
+    pub co: [u8; NUM_CORNERS],
+    /// `ep[i]` is the edge at position `i`.
+    pub ep: [u8; NUM_EDGES],
+    /// `eo[i]` is 1 if the edge at position `i` is flipped, 0 otherwise.
+    pub eo: [u8; NUM_EDGES],
+}
+impl CubieCube {
+    /// The solved cube.
+    pub const SOLVED: CubieCube = CubieCube {
+        cp: [0, 1, 2, 3, 4, 5, 6, 7],
+        co: [0; NUM_CORNERS],
+        ep: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+        eo: [0; NUM_EDGES],
+    };
+
+    /// Reads the pieces of a 2x2 or 3x3 `cube`.
+    ///
+    /// On a 3x3, the centers tell which color belongs to which face. A 2x2 has no centers, so the
+    /// colors of the down left back corner are taken as the colors of its faces, which is the
+    /// corner that the solver never moves. Returns `None` for other sizes or if a piece can't be
+    /// identified.
+    pub fn from_cube(cube: &Cube) -> Option<CubieCube> {
+        let size = cube.size;
+        let sticker = |&(face_dir, row, col): &(FaceDir, usize, usize)| {
+            // scale the 3x3 coordinates to the size of the cube
+            let scale = |i: usize| i * (size - 1) / 2;
+            cube.get_face(&face_dir).colors[[scale(row), scale(col)]]
+        };
+
+        // the color of each face, in `FaceDir::FACELET_ORDER`
+        let face_colors: [Color; 6] = match size {
+            3 => FaceDir::FACELET_ORDER.map(|face_dir| sticker(&(face_dir, 1, 1))),
+            2 => {
+                let [d, b, l] = CORNER_FACELETS[6].map(|facelet| sticker(&facelet));
+                [
+                    Cube::get_opposite_color(d),
+                    Cube::get_opposite_color(l),
+                    Cube::get_opposite_color(b),
+                    d,
+                    l,
+                    b,
+                ]
+            }
+            _ => return None,
+        };
+        // the face each color belongs to, as an index in `FaceDir::FACELET_ORDER`
+        let face_of = |color: Color| face_colors.iter().position(|&c| c == color);
+        let facelet_face =
+            |&(face_dir, _, _): &(FaceDir, usize, usize)| FaceDir::facelet_index(face_dir);
+
+        let mut cubie_cube = CubieCube::SOLVED;
+        for (i, facelets) in CORNER_FACELETS.iter().enumerate() {
+            let faces = facelets
+                .iter()
+                .map(|f| face_of(sticker(f)))
+                .collect::<Option<Vec<usize>>>()?;
+            // the orientation is where the U or D colored facelet is
+            let ori = faces.iter().position(|&f| {
+                f == FaceDir::facelet_index(FaceDir::Up)
+                    || f == FaceDir::facelet_index(FaceDir::Down)
+            })?;
+            let (face1, face2) = (faces[(ori + 1) % 3], faces[(ori + 2) % 3]);
+            let j = CORNER_FACELETS.iter().position(|corner| {
+                facelet_face(&corner[1]) == face1 && facelet_face(&corner[2]) == face2
+            })?;
+            cubie_cube.cp[i] = j as u8;
+            cubie_cube.co[i] = ori as u8;
+        }
+
+        if size == 3 {
+            for (i, facelets) in EDGE_FACELETS.iter().enumerate() {
+                let face0 = face_of(sticker(&facelets[0]))?;
+                let face1 = face_of(sticker(&facelets[1]))?;
+                let (j, flipped) = EDGE_FACELETS.iter().enumerate().find_map(|(j, edge)| {
+                    let (e0, e1) = (facelet_face(&edge[0]), facelet_face(&edge[1]));
+                    if (e0, e1) == (face0, face1) {
+                        Some((j, false))
+                    } else if (e1, e0) == (face0, face1) {
+                        Some((j, true))
+                    } else {
+                        None
+                    }
+                })?;
+                cubie_cube.ep[i] = j as u8;
+                cubie_cube.eo[i] = flipped as u8;
+            }
+        }
+        Some(cubie_cube)
+    }
+
+    /// Returns a 2x2 or 3x3 `Cube` with these pieces, colored with `Cube::INIT_CONFIG`.
+    ///
+    /// Reading a 2x2 with `from_cube` and converting it back recolors it so that the down left
+    /// back corner has the standard colors, which doesn't change which turns solve it.
+    pub fn to_cube(self, size: usize) -> Cube {
+        let mut cube = Cube::new(size);
+        let mut set_sticker = |(face_dir, row, col): (FaceDir, usize, usize),
+                               color_dir: FaceDir| {
+            let scale = |i: usize| i * (size - 1) / 2;
+            cube.get_face_mut(&face_dir).colors[[scale(row), scale(col)]] =
+                Cube::get_init_color(color_dir);
+        };
+
+        for (i, facelets) in CORNER_FACELETS.iter().enumerate() {
+            let (piece, ori) = (self.cp[i] as usize, self.co[i] as usize);
+            for k in 0..3 {
+                set_sticker(facelets[(k + ori) % 3], CORNER_FACELETS[piece][k].0);
+            }
+        }
+        if size == 3 {
+            for (i, facelets) in EDGE_FACELETS.iter().enumerate() {
+                let (piece, ori) = (self.ep[i] as usize, self.eo[i] as usize);
+                for k in 0..2 {
+                    set_sticker(facelets[(k + ori) % 2], EDGE_FACELETS[piece][k].0);
+                }
+            }
+        }
+        cube
+    }
+
+    /// Returns a random solvable cube, where every state is as likely as the others, like the
+    /// scrambles of competitions. A 2x2 only uses the corners.
+    pub fn random(rng: &mut impl Rng) -> CubieCube {
+        let mut cubie_cube = CubieCube::SOLVED;
+        cubie_cube.cp.shuffle(rng);
+        cubie_cube.ep.shuffle(rng);
+        // swapping two edges gives them the parity of the corners
+        if permutation_parity(&cubie_cube.cp) != permutation_parity(&cubie_cube.ep) {
+            cubie_cube.ep.swap(0, 1);
+        }
+        cubie_cube.set_twist(rng.gen_range(0..NUM_TWIST));
+        cubie_cube.set_flip(rng.gen_range(0..NUM_FLIP));
+        cubie_cube
+    }
+
+    /// Returns the cube obtained by applying `other` to `self`.
+    pub fn multiply(&self, other: &CubieCube) -> CubieCube {
+        let mut res = CubieCube::SOLVED;
+        for i in 0..NUM_CORNERS {
+            let from = other.cp[i] as usize;
+            res.cp[i] = self.cp[from];
+            res.co[i] = (self.co[from] + other.co[i]) % 3;
+        }
+        for i in 0..NUM_EDGES {
+            let from = other.ep[i] as usize;
+            res.ep[i] = self.ep[from];
+            res.eo[i] = (self.eo[from] + other.eo[i]) % 2;
+        }
+        res
+    }
+
+    /// Returns the clockwise turns of each face, in `FaceDir::FACELET_ORDER`.
+    ///
+    /// The turns are read from `Cube::turn_layer`, so both representations always agree.
+    pub fn face_turns() -> &'static [CubieCube; 6] {
+        static FACE_TURNS: OnceLock<[CubieCube; 6]> = OnceLock::new();
+        FACE_TURNS.get_or_init(|| {
+            FaceDir::FACELET_ORDER.map(|face_dir| {
+                let mut cube = Cube::new(3);
+                cube.turn_layer(&Turn::new(face_dir, TurnDir::Clockwise), 1);
+                CubieCube::from_cube(&cube).unwrap()
+            })
+        })
+    }
+
+    /// Returns the cube obtained by applying `turn` to `self`. Panics if `turn` turns a slice,
+    /// which moves the centers.
+    pub fn apply_turn(&self, turn: &Turn) -> CubieCube {
+        if turn.get_layer() != 1 {
+            panic!("slice turns can't be applied to a cubie cube");
+        }
+        let face_turn = &CubieCube::face_turns()[FaceDir::facelet_index(turn.face_dir)];
+        let num_quarter_turns = match turn.turn_dir {
+            TurnDir::Clockwise => 1,
+            TurnDir::Half => 2,
+            TurnDir::CounterClockwise => 3,
+        };
+        (0..num_quarter_turns).fold(*self, |cube, _| cube.multiply(face_turn))
+    }
+
+    /// Returns the cube obtained by applying the turns of `algo` to `self` in order.
+    pub fn apply_algorithm(&self, algo: &[Turn]) -> CubieCube {
+        algo.iter().fold(*self, |cube, turn| cube.apply_turn(turn))
+    }
+
+    /// Returns the corner orientation coordinate, in `0..NUM_TWIST`.
+    pub fn twist(&self) -> usize {
+        self.co[..NUM_CORNERS - 1]
+            .iter()
+            .fold(0, |twist, &o| 3 * twist + o as usize)
+    }
+
+    /// Sets the corner orientations so that `twist()` returns `twist`.
+    pub fn set_twist(&mut self, mut twist: usize) {
+        let mut parity = 0;
+        for i in (0..NUM_CORNERS - 1).rev() {
+            self.co[i] = (twist % 3) as u8;
+            parity += self.co[i];
+            twist /= 3;
+        }
+        self.co[NUM_CORNERS - 1] = (3 - parity % 3) % 3;
+    }
+
+    /// Returns the edge orientation coordinate, in `0..NUM_FLIP`.
+    pub fn flip(&self) -> usize {
+        self.eo[..NUM_EDGES - 1]
+            .iter()
+            .fold(0, |flip, &o| 2 * flip + o as usize)
+    }
+
+    /// Sets the edge orientations so that `flip()` returns `flip`.
+    pub fn set_flip(&mut self, mut flip: usize) {
+        let mut parity = 0;
+        for i in (0..NUM_EDGES - 1).rev() {
+            self.eo[i] = (flip % 2) as u8;
+            parity += self.eo[i];
+            flip /= 2;
+        }
+        self.eo[NUM_EDGES - 1] = parity % 2;
+    }
+
+    /// Returns the UD-slice coordinate, in `0..NUM_SLICE`.
+    ///
+    /// The coordinate only depends on which positions the four slice edges (FR, FL, BL, BR) are
+    /// at, not on their order. It's `0` when they're all in the slice.
+    pub fn slice(&self) -> usize {
+        let mut slice = 0;
+        let mut x = 0;
+        for j in (0..NUM_EDGES).rev() {
+            if self.ep[j] >= FIRST_SLICE_EDGE {
+                slice += binomial(NUM_EDGES - 1 - j, x + 1);
+                x += 1;
+            }
+        }
+        slice
+    }
+
+    /// Sets the edge permutation so that `slice()` returns `slice`.
+    pub fn set_slice(&mut self, mut slice: usize) {
+        let mut x = 4;
+        let mut next_slice_edge = FIRST_SLICE_EDGE;
+        let mut next_other_edge = 0;
+        for j in 0..NUM_EDGES {
+            if x > 0 && slice >= binomial(NUM_EDGES - 1 - j, x) {
+                slice -= binomial(NUM_EDGES - 1 - j, x);
+                self.ep[j] = next_slice_edge;
+                next_slice_edge += 1;
+                x -= 1;
+            } else {
+                self.ep[j] = next_other_edge;
+                next_other_edge += 1;
+            }
+        }
+    }
+
+    /// Returns the corner permutation coordinate, in `0..NUM_CORNER_PERM`.
+    pub fn corner_perm(&self) -> usize {
+        permutation_coord(&self.cp, 0)
+    }
+
+    /// Sets the corner permutation so that `corner_perm()` returns `coord`.
+    pub fn set_corner_perm(&mut self, coord: usize) {
+        set_permutation_coord(&mut self.cp, 0, coord);
+    }
+
+    /// Returns the permutation coordinate of the U and D edges, in `0..NUM_UD_EDGE_PERM`.
+    ///
+    /// Only meaningful when the UD-slice edges are in the slice (`slice()` is `0`), which is the
+    /// case in the second phase of the two-phase algorithm.
+    pub fn ud_edge_perm(&self) -> usize {
+        permutation_coord(&self.ep[..FIRST_SLICE_EDGE as usize], 0)
+    }
+
+    /// Sets the permutation of the U and D edges so that `ud_edge_perm()` returns `coord`.
+    pub fn set_ud_edge_perm(&mut self, coord: usize) {
+        set_permutation_coord(&mut self.ep[..FIRST_SLICE_EDGE as usize], 0, coord);
+    }
+
+    /// Returns the permutation coordinate of the UD-slice edges, in `0..NUM_SLICE_PERM`.
+    ///
+    /// Only meaningful when the UD-slice edges are in the slice (`slice()` is `0`).
+    pub fn slice_perm(&self) -> usize {
+        permutation_coord(&self.ep[FIRST_SLICE_EDGE as usize..], FIRST_SLICE_EDGE)
+    }
+
+    /// Sets the permutation of the UD-slice edges so that `slice_perm()` returns `coord`.
+    pub fn set_slice_perm(&mut self, coord: usize) {
+        set_permutation_coord(
+            &mut self.ep[FIRST_SLICE_EDGE as usize..],
+            FIRST_SLICE_EDGE,
+            coord,
+        );
+    }
+
+    /// Returns true if the cube can be solved with turns.
+    ///
+    /// Every piece has to appear exactly once, the corner twists have to add up to a multiple of
+    /// 3, the edge flips to a multiple of 2, and the corner and edge permutations need the same
+    /// parity.
+    pub fn is_solvable(&self) -> bool {
+        self.are_corners_solvable()
+            && is_permutation(&self.ep)
+            && self.eo.iter().map(|&o| o as usize).sum::<usize>() % 2 == 0
+            && permutation_parity(&self.cp) == permutation_parity(&self.ep)
+    }
+
+    /// Returns true if the corners can be solved with turns, which is all there is to a 2x2.
+    ///
+    /// Every corner has to appear exactly once and the corner twists have to add up to a multiple
+    /// of 3. Without edges, any permutation of the corners can be solved.
+    pub fn are_corners_solvable(&self) -> bool {
+        is_permutation(&self.cp) && self.co.iter().map(|&o| o as usize).sum::<usize>() % 3 == 0
+    }
+}
+
+/// Returns true if every piece of `perm` is in `0..perm.len()` and appears exactly once.
+fn is_permutation(perm: &[u8]) -> bool {
+    let mut seen = vec![false; perm.len()];
+    perm.iter()
+        .all(|&p| (p as usize) < perm.len() && !std::mem::replace(&mut seen[p as usize], true))
+}
+
+/// Returns the coordinate of `perm`, which is a permutation of the pieces
+/// `first..first + perm.len()`.
+fn permutation_coord(perm: &[u8], first: u8) -> usize {
+    let mut perm = perm.to_vec();
+    let mut coord = 0;
+    for j in (1..perm.len()).rev() {
+        let mut k = 0;
+        while perm[j] != first + j as u8 {
+            perm[..=j].rotate_left(1);
+            k += 1;
+        }
+        coord = (j + 1) * coord + k;
+    }
+    coord
+}
+
+/// Sets `perm` to the permutation of the pieces `first..first + perm.len()` that
+/// `permutation_coord` maps to `coord`.
+fn set_permutation_coord(perm: &mut [u8], first: u8, mut coord: usize) {
+    for (j, piece) in perm.iter_mut().enumerate() {
+        *piece = first + j as u8;
+    }
+    for j in 0..perm.len() {
+        let k = coord % (j + 1);
+        coord /= j + 1;
+        perm[..=j].rotate_right(k);
+    }
+}
+
+/// Returns the parity of `perm` (0 if even, 1 if odd), which has to be a permutation of
+/// `0..perm.len()`.
+fn permutation_parity(perm: &[u8]) -> usize {
+    let mut inversions = 0;
+    for i in 0..perm.len() {
+        for j in i + 1..perm.len() {
+            if perm[i] > perm[j] {
+                inversions += 1;
+            }
+        }
+    }
+    inversions % 2
+}
+
+/// Returns n choose k, or 0 if k > n.
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1, |res, i| res * (n - i) / (i + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns_match_the_cube() {
+        assert_eq!(CubieCube::from_cube(&Cube::new(3)), Some(CubieCube::SOLVED));
+        assert_eq!(CubieCube::from_cube(&Cube::new(2)), Some(CubieCube::SOLVED));
+        let mut cube = Cube::new(3);
+        let mut cubie_cube = CubieCube::SOLVED;
+        for i in [3, 0, 2, 4, 1, 5, 3, 3, 0] {
+            cube.turn_layer(&Turn::new(FaceDir::FACELET_ORDER[i], TurnDir::Clockwise), 1);
+            cubie_cube = cubie_cube.multiply(&CubieCube::face_turns()[i]);
+            assert_eq!(CubieCube::from_cube(&cube), Some(cubie_cube));
+        }
+    }
+
+    #[test]
+    fn coordinates_round_trip() {
+        for twist in 0..NUM_TWIST {
+            let mut cube = CubieCube::SOLVED;
+            cube.set_twist(twist);
+            assert_eq!(cube.twist(), twist);
+        }
+        for flip in 0..NUM_FLIP {
+            let mut cube = CubieCube::SOLVED;
+            cube.set_flip(flip);
+            assert_eq!(cube.flip(), flip);
+        }
+        for slice in 0..NUM_SLICE {
+            let mut cube = CubieCube::SOLVED;
+            cube.set_slice(slice);
+            assert_eq!(cube.slice(), slice);
+        }
+        for corner_perm in (0..NUM_CORNER_PERM).step_by(7) {
+            let mut cube = CubieCube::SOLVED;
+            cube.set_corner_perm(corner_perm);
+            assert_eq!(cube.corner_perm(), corner_perm);
+        }
+        assert_eq!(CubieCube::SOLVED.twist(), 0);
+        assert_eq!(CubieCube::SOLVED.flip(), 0);
+    }
+
+    #[test]
+    fn converts_back_to_the_cube() {
+        let algo = Turn::parse_algorithm("R U2 F' L D' B2 R' U".split(' ').collect()).unwrap();
+        let mut cube = Cube::new(3);
+        cube.apply_algorithm(algo.clone());
+        let cubie_cube = CubieCube::SOLVED.apply_algorithm(&algo);
+        assert_eq!(CubieCube::from_cube(&cube), Some(cubie_cube));
+        assert_eq!(cubie_cube.to_cube(3).facelet_codes(), cube.facelet_codes());
+    }
+
+    #[test]
+    fn phase_2_coordinates_round_trip() {
+        for coord in (0..NUM_UD_EDGE_PERM).step_by(7) {
+            let mut cube = CubieCube::SOLVED;
+            cube.set_ud_edge_perm(coord);
+            assert_eq!(cube.ud_edge_perm(), coord);
+        }
+        for coord in 0..NUM_SLICE_PERM {
+            let mut cube = CubieCube::SOLVED;
+            cube.set_slice_perm(coord);
+            assert_eq!(cube.slice_perm(), coord);
+        }
+    }
+
+    #[test]
+    fn only_turned_cubes_are_solvable() {
+        let algo = Turn::parse_algorithm("R U2 F' L D' B2 R' U".split(' ').collect()).unwrap();
+        assert!(CubieCube::SOLVED.apply_algorithm(&algo).is_solvable());
+        let mut twisted = CubieCube::SOLVED;
+        twisted.co[0] = 1;
+        assert!(!twisted.is_solvable());
+        let mut swapped = CubieCube::SOLVED;
+        swapped.ep.swap(0, 1);
+        assert!(!swapped.is_solvable());
+    }
+}
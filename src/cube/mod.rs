@@ -1,3 +1,11 @@
+//! A Rubik's cube of any size as the colors of its faces, made by a `builder`, with the turns of
+//! its layers, and how it's drawn (`rendering`) with the colors of a `palette`.
+
+pub mod builder;
+pub mod cubie;
+pub mod palette;
+#[cfg(feature = "image")]
+pub mod photo;
 pub mod rendering;
 use core::panic;
 
@@ -5,49 +13,137 @@ use std::collections::HashMap;
 use std::fmt::Display;
 use std::iter::once;
 
+use builder::CubeBuilder;
 use ndarray::{Array, Array1, Array2, ArrayView1, Axis};
-use rand::{rngs::ThreadRng, Rng};
+use palette::{ColorMode, Palette};
+use rand::Rng;
 
 /// Possible colors on the cube.
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Color {
+///
+/// Its `Display` is the first letter of its name, like in `Cube::facelet_string`, without the
+/// escape codes that color the `Display` of the cube in terminals.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Color {
+    /// Written `W`.
     White,
+    /// Written `R`.
     Red,
+    /// Written `B`.
     Blue,
+    /// Written `Y`.
     Yellow,
+    /// Written `O`.
     Orange,
+    /// Written `G`.
     Green,
 }
 
-// Implement `Display` for `Color`, so that we can print the colors to the console.
+impl Color {
+    /// An array of all the colors, indexed by their code. Useful to convert a code back to a color.
+    pub const ALL_COLORS: [Color; 6] = [
+        Color::White,
+        Color::Red,
+        Color::Blue,
+        Color::Yellow,
+        Color::Orange,
+        Color::Green,
+    ];
+
+    /// Returns the color's code, a number in `0..6`, like in `Cube::facelet_codes`.
+    pub fn code(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Returns the name of the color in lowercase, like `white`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Color::White => "white",
+            Color::Red => "red",
+            Color::Blue => "blue",
+            Color::Yellow => "yellow",
+            Color::Orange => "orange",
+            Color::Green => "green",
+        }
+    }
+
+    /// Returns the first letter of the name of the color.
+    pub fn letter(&self) -> char {
+        match self {
+            Color::White => 'W',
+            Color::Red => 'R',
+            Color::Blue => 'B',
+            Color::Yellow => 'Y',
+            Color::Orange => 'O',
+            Color::Green => 'G',
+        }
+    }
+
+    /// Returns the RGB value of the color in the current palette, see `Palette::current`.
+    pub fn to_rgb(&self) -> (u8, u8, u8) {
+        Palette::current().rgb(*self)
+    }
+
+    /// Returns the ASCII character that fills the stickers of the color with
+    /// `ColorMode::NoColor`, different for each color so the stickers can be told apart.
+    fn fill_char(&self) -> char {
+        match self {
+            Color::White => '.',
+            Color::Red => '#',
+            Color::Blue => '%',
+            Color::Yellow => '=',
+            Color::Orange => '+',
+            Color::Green => '@',
+        }
+    }
+
+    /// Returns the color that has `code`, or `None` if the code isn't in `0..6`.
+    pub fn from_code(code: u8) -> Option<Color> {
+        Color::ALL_COLORS.get(code as usize).copied()
+    }
+
+    /// Returns the color whose letter (see `letter`) is `c`, in any case.
+    pub fn from_char(c: char) -> Option<Color> {
+        Color::ALL_COLORS
+            .into_iter()
+            .find(|color| color.letter() == c.to_ascii_uppercase())
+    }
+
+    /// Returns the letter of the color with the escape codes that color it in the terminal with
+    /// the current `ColorMode`.
+    fn colored(&self) -> String {
+        let letter = self.letter();
+        match ColorMode::current() {
+            ColorMode::NoColor => letter.to_string(),
+            color_mode => format!("{}{letter}\x1b[0m", color_mode.escape_code(*self)),
+        }
+    }
+}
+
 impl Display for Color {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s = match self {
-            Color::White => "\x1b[30;47mW\x1b[0m",
-            Color::Red => "\x1b[30;41mR\x1b[0m",
-            Color::Blue => "\x1b[30;44mB\x1b[0m",
-            Color::Yellow => "\x1b[30;103mY\x1b[0m",
-            Color::Orange => "\x1b[30;43mO\x1b[0m",
-            Color::Green => "\x1b[30;42mG\x1b[0m",
-        };
-        write!(f, "{}", s)
+        write!(f, "{}", self.letter())
     }
 }
 
 /// Possible turn directions.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum TurnDir {
+    /// A quarter turn, clockwise when looking at the face, written without a suffix like `R`.
     Clockwise,
+    /// A quarter turn the other way, written with an apostrophe like `R'`.
     CounterClockwise,
+    /// A 180-degree turn, which is its own reversal.
+    Half,
 }
 impl TurnDir {
     /// Returns the reversal of the direction.
     ///
-    /// If `self` is `Clockwise then returns `CounterClockwise` and vice versa.
+    /// If `self` is `Clockwise then returns `CounterClockwise` and vice versa. `Half` stays `Half`.
     fn get_reversed(&self) -> TurnDir {
         match self {
             TurnDir::Clockwise => TurnDir::CounterClockwise,
             TurnDir::CounterClockwise => TurnDir::Clockwise,
+            TurnDir::Half => TurnDir::Half,
         }
     }
 }
@@ -72,23 +168,33 @@ impl Face {
         }
     }
 
-    /// Rotates the color matrix 90-degree clockwise or counter-clockwise, depending on `turn_dir`.
+    /// Rotates the color matrix 90-degree clockwise or counter-clockwise, or 180-degree, depending
+    /// on `turn_dir`.
     fn rotate(&mut self, turn_dir: TurnDir) {
-        // transpose array
-        self.colors.swap_axes(0, 1);
-        // reverse column/row based on `turn_dir`
+        // a quarter turn transposes the array, then reverses its columns or its rows
         match turn_dir {
             // invert the 0th axis which is the Y axis, meaning reversing each column
-            TurnDir::Clockwise => self.colors.invert_axis(Axis(1)),
+            TurnDir::Clockwise => {
+                self.colors.swap_axes(0, 1);
+                self.colors.invert_axis(Axis(1));
+            }
             // invert the 1st axis which is the X axis, meaning reversing each row
-            TurnDir::CounterClockwise => self.colors.invert_axis(Axis(0)),
+            TurnDir::CounterClockwise => {
+                self.colors.swap_axes(0, 1);
+                self.colors.invert_axis(Axis(0));
+            }
+            // rotating 180-degree is reversing both the rows and the columns
+            TurnDir::Half => {
+                self.colors.invert_axis(Axis(0));
+                self.colors.invert_axis(Axis(1));
+            }
         }
     }
 
     /// Returns the ith row or column (depending on `is_column`) of the color matrix.
     ///
     /// `i` starts from `0`, and is ordered left to right and top to bottom.
-    fn get_slice(&self, i: usize, is_column: bool) -> ArrayView1<Color> {
+    fn get_slice(&self, i: usize, is_column: bool) -> ArrayView1<'_, Color> {
         if !is_column {
             return self.colors.row(i);
         }
@@ -121,7 +227,7 @@ impl Display for Face {
         let mut s = String::new();
         for y in 0..self.size {
             for x in 0..self.size {
-                s.push_str(&format!("{}", self.colors[[y, x]]));
+                s.push_str(&self.colors[[y, x]].colored());
             }
             if y != self.size - 1 {
                 s.push('\n');
@@ -132,25 +238,30 @@ impl Display for Face {
 }
 
 /// Possible axes of the cube.
-///
-/// `X` is going from left to right.
-/// `Y` is going bottom to top.
-/// `Z` is going from back to front.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum CubeAxis {
+    /// Going from left to right.
     X,
+    /// Going from bottom to top.
     Y,
+    /// Going from back to front.
     Z,
 }
 
 /// Possible directions of the faces.
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum FaceDir {
+    /// `U`
     Up,
+    /// `D`
     Down,
+    /// `R`
     Right,
+    /// `L`
     Left,
+    /// `F`
     Front,
+    /// `B`
     Back,
 }
 impl FaceDir {
@@ -165,6 +276,54 @@ impl FaceDir {
         FaceDir::Back,
     ];
 
+    /// The order of the faces when the cube is flattened into a list of facelets.
+    ///
+    /// This is the same order as Kociemba's facelet strings (U, R, F, D, L, B). Each face is then
+    /// read row by row in the same orientation as its color matrix.
+    pub const FACELET_ORDER: [FaceDir; 6] = [
+        FaceDir::Up,
+        FaceDir::Right,
+        FaceDir::Front,
+        FaceDir::Down,
+        FaceDir::Left,
+        FaceDir::Back,
+    ];
+
+    /// Returns the index of the face direction in `FaceDir::FACELET_ORDER`.
+    pub fn facelet_index(face_dir: FaceDir) -> usize {
+        FaceDir::FACELET_ORDER
+            .iter()
+            .position(|&fd| fd == face_dir)
+            .unwrap()
+    }
+
+    /// Parses a string to a `FaceDir`.
+    ///
+    /// Trims, ignore case of the input. Returns `None` if the input is not in "UDLRFB".
+    pub fn from_string(s: &str) -> Option<FaceDir> {
+        match s.trim().to_uppercase().as_str() {
+            "U" => Some(FaceDir::Up),
+            "D" => Some(FaceDir::Down),
+            "L" => Some(FaceDir::Left),
+            "R" => Some(FaceDir::Right),
+            "F" => Some(FaceDir::Front),
+            "B" => Some(FaceDir::Back),
+            _ => None,
+        }
+    }
+
+    /// Returns the direction opposite to this one.
+    pub fn get_opposite(&self) -> FaceDir {
+        match self {
+            FaceDir::Up => FaceDir::Down,
+            FaceDir::Down => FaceDir::Up,
+            FaceDir::Right => FaceDir::Left,
+            FaceDir::Left => FaceDir::Right,
+            FaceDir::Front => FaceDir::Back,
+            FaceDir::Back => FaceDir::Front,
+        }
+    }
+
     /// Returns the array of directions surrounding (orthogonal to) the axis.
     /// The ordering of these directions in the array depends on `turn_dir`.
     ///
@@ -227,8 +386,9 @@ impl FaceDir {
     /// Rotate the face direction in `turn_dir` along `axis`.
     fn apply_rotation(&mut self, axis: CubeAxis, turn_dir: TurnDir) {
         let surrounding_dirs = FaceDir::get_dir_surrounding_axis(axis, turn_dir);
+        let steps = if turn_dir == TurnDir::Half { 2 } else { 1 };
         if let Some(i) = surrounding_dirs.iter().position(|fd| fd == self) {
-            *self = surrounding_dirs[(i + 1) % 4];
+            *self = surrounding_dirs[(i + steps) % 4];
         }
     }
 }
@@ -246,17 +406,34 @@ impl Display for FaceDir {
     }
 }
 
-#[derive(Clone)]
+/// A turn of one layer of the cube, written in the usual notation like `R`, `U'`, `F2`, or `2R`
+/// for the second layer from the right face (see `Turn::parse_algorithm`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Turn {
     face_dir: FaceDir,
     turn_dir: TurnDir,
+    /// The layer that is turned, counted from the face, starting at 1 (the face itself).
+    layer: usize,
 }
 impl Turn {
-    pub fn new(face_dir: FaceDir, turn_dir: TurnDir) -> Turn {
-        Turn { face_dir, turn_dir }
+    /// Creates the turn of the face itself, e.g. `R` or `U'`.
+    pub const fn new(face_dir: FaceDir, turn_dir: TurnDir) -> Turn {
+        Turn::new_slice(face_dir, turn_dir, 1)
     }
 
-    fn random_turn(rng: &mut ThreadRng) -> Turn {
+    /// Creates the turn of the `layer`th layer from the face, in the direction of the face, e.g.
+    /// the M slice of a 3x3 is the 2nd layer from the left face.
+    pub const fn new_slice(face_dir: FaceDir, turn_dir: TurnDir, layer: usize) -> Turn {
+        Turn {
+            face_dir,
+            turn_dir,
+            layer,
+        }
+    }
+
+    /// Returns a random quarter turn of a cube of size `size`, of one of the outer half of its
+    /// layers, since the middle layer of odd cubes is the same as turning the two faces.
+    fn random_turn(rng: &mut impl Rng, size: usize) -> Turn {
         // make random turn
         let face_dir = FaceDir::ALL_FACE_DIR[rng.gen_range(0..6)];
         let turn_dir = if rng.gen_bool(0.5) {
@@ -264,33 +441,262 @@ impl Turn {
         } else {
             TurnDir::CounterClockwise
         };
-        Turn { face_dir, turn_dir }
+        let layer = rng.gen_range(1..=usize::max(1, size / 2));
+        Turn::new_slice(face_dir, turn_dir, layer)
+    }
+
+    /// Returns the face that this turn turns.
+    pub fn get_face_dir(&self) -> FaceDir {
+        self.face_dir
+    }
+
+    /// Returns the direction that this turn turns in.
+    pub fn get_turn_dir(&self) -> TurnDir {
+        self.turn_dir
+    }
+
+    /// Returns the layer that this turn turns, 1 being the face itself.
+    pub fn get_layer(&self) -> usize {
+        self.layer
+    }
+
+    /// Returns the turn that undoes this turn.
+    pub fn get_reversed(&self) -> Turn {
+        Turn::new_slice(self.face_dir, self.turn_dir.get_reversed(), self.layer)
     }
 
     /// check if other is this turn but reversed
     pub fn is_reversed(&self, other: &Turn) -> bool {
-        self.face_dir == other.face_dir && self.turn_dir == other.turn_dir.get_reversed()
+        self.face_dir == other.face_dir
+            && self.layer == other.layer
+            && self.turn_dir == other.turn_dir.get_reversed()
+    }
+
+    /// Checks if this turn and `other` turn the same layer, whatever their directions.
+    fn is_same_layer(&self, other: &Turn) -> bool {
+        self.face_dir == other.face_dir && self.layer == other.layer
+    }
+
+    /// Checks if applying this turn after `prev_turns` is redundant, i.e. the resulting sequence
+    /// can be written with as few turns in a canonical form that the solvers still try.
+    ///
+    /// Besides the reverse of the previous turn, this prunes:
+    /// - same layer runs other than a single turn or two clockwise turns (`R R R` is `R'`,
+    ///   `R' R'` is `R R`, and `R2 R` is `R'`),
+    /// - parallel layers turned in the wrong order, since they commute (`D U` is `U D`, so we only
+    ///   keep the order of `FaceDir::FACELET_ORDER`, then of the layers).
+    pub fn is_redundant_after(&self, prev_turns: &[Turn]) -> bool {
+        let Some(prev) = prev_turns.last() else {
+            return false;
+        };
+        if prev.is_same_layer(self) {
+            if self.turn_dir == TurnDir::Half || prev.turn_dir == TurnDir::Half {
+                return true;
+            }
+            let is_third_in_a_row =
+                prev_turns.len() >= 2 && prev_turns[prev_turns.len() - 2].is_same_layer(self);
+            return self.turn_dir == TurnDir::CounterClockwise
+                || prev.turn_dir == TurnDir::CounterClockwise
+                || is_third_in_a_row;
+        }
+        let is_parallel =
+            prev.face_dir == self.face_dir || prev.face_dir == self.face_dir.get_opposite();
+        is_parallel
+            && (FaceDir::facelet_index(self.face_dir), self.layer)
+                < (FaceDir::facelet_index(prev.face_dir), prev.layer)
+    }
+
+    /// Returns the permutation of the facelets that this turn applies to a cube of size `size`.
+    ///
+    /// The result `perm` satisfies `after[i] == before[perm[i]]`, where `before` and `after` are the
+    /// facelets (in `FaceDir::FACELET_ORDER`) before and after the turn. This lets callers apply the
+    /// turn to their own compact representation of the stickers without going through `Cube`.
+    pub fn facelet_permutation(&self, size: usize) -> Vec<usize> {
+        let num_facelets = 6 * size * size;
+        let mut perm = vec![0; num_facelets];
+        // follow each facelet by marking it with a color different from every other facelet.
+        for from in 0..num_facelets {
+            let mut codes = vec![Color::White.code(); num_facelets];
+            codes[from] = Color::Red.code();
+            let mut cube = Cube::from_facelet_codes(size, &codes).unwrap();
+            cube.apply_turn(self);
+            let to = cube
+                .facelet_codes()
+                .iter()
+                .position(|&c| c == Color::Red.code())
+                .unwrap();
+            perm[to] = from;
+        }
+        perm
+    }
+
+    /// Returns the permutation of the facelets that `algo` applies to a cube of size `size`, in the
+    /// same form as `facelet_permutation`.
+    pub fn algorithm_permutation(algo: &[Turn], size: usize) -> Vec<usize> {
+        let mut perm: Vec<usize> = (0..6 * size * size).collect();
+        for turn in algo {
+            let turn_perm = turn.facelet_permutation(size);
+            perm = turn_perm.iter().map(|&i| perm[i]).collect();
+        }
+        perm
+    }
+
+    /// Returns where `algo` moves the pieces of a cube of size `size`, as pairs of facelet indices
+    /// (as in `Cube::facelet_codes`) from before to after it, one for each piece that moves.
+    ///
+    /// The pair of a piece follows its first facelet (see `Cube::piece_groups`), so a piece that
+    /// is only twisted or flipped in place goes to another facelet of itself.
+    pub fn piece_moves(algo: &[Turn], size: usize) -> Vec<(usize, usize)> {
+        let perm = Turn::algorithm_permutation(algo, size);
+        let mut dest = vec![0; perm.len()];
+        for (to, &from) in perm.iter().enumerate() {
+            dest[from] = to;
+        }
+        Cube::piece_groups(size)
+            .iter()
+            .map(|group| (group[0], dest[group[0]]))
+            .filter(|(from, to)| from != to)
+            .collect()
+    }
+
+    /// Checks if the sticker at `row`, `col` of the face at `face_dir` of a cube of size `size` is
+    /// on the layer that this turn moves, including the centers that only rotate in place.
+    fn moves_sticker(&self, size: usize, face_dir: &FaceDir, row: usize, col: usize) -> bool {
+        let cubelet = Cube::get_sticker_cubelet(size, face_dir, row, col);
+        let i = match self.face_dir.get_axis() {
+            CubeAxis::X => 0,
+            CubeAxis::Y => 1,
+            CubeAxis::Z => 2,
+        };
+        let layer_index = if self.face_dir.is_positive() {
+            size - self.layer
+        } else {
+            self.layer - 1
+        };
+        cubelet[i] == layer_index
+    }
+
+    /// Takes a list of strings, parses and returns a list of `FaceDir` and `TurnDir` that the list
+    /// of strings represents
+    ///
+    /// The format is `[layer]face_dir[' or 2]`. `face_dir` indicates the face (U, D, L, R, F, or
+    /// B), or the middle slice of a 3x3 (M, E, or S, which turn like L, D, and F). `layer` is the
+    /// layer to turn counting from the face, the face itself by default. "'" indicates a
+    /// counter-clockwise turn and "2" indicates a 180-degree turn. Returns None if the turns are
+    /// invalid.
+    pub fn parse_algorithm(turns: Vec<&str>) -> Option<Vec<Turn>> {
+        // create a macro to make parsing and return `None` if the element is invalid quicker.
+        macro_rules! parse_or_return {
+            ($e:expr) => {
+                match Turn::parse_layer($e) {
+                    Some(face_dir_and_layer) => face_dir_and_layer,
+                    None => return None,
+                }
+            };
+        }
+
+        // loop through the list of strings, and add to result the parsed move.
+        let mut result = Vec::with_capacity(turns.len());
+        for turn in turns.into_iter() {
+            // check for possible post-fixes, which are stripped by char so that a turn ending
+            // with another character, like a typographic apostrophe, is invalid instead of cut in
+            // the middle of it.
+            let (turn, turn_dir) = if let Some(trimmed_turn) = turn.strip_suffix('\'') {
+                (trimmed_turn, TurnDir::CounterClockwise)
+            } else if let Some(trimmed_turn) = turn.strip_suffix('2') {
+                (trimmed_turn, TurnDir::Half)
+            } else {
+                (turn, TurnDir::Clockwise)
+            };
+            let (face_dir, layer) = parse_or_return!(turn);
+            result.push(Turn::new_slice(face_dir, turn_dir, layer));
+        }
+        Some(result)
+    }
+
+    /// Parses the layer of a turn without its direction, e.g. "R", "2R" or "M", into its face
+    /// and its layer counted from the face.
+    fn parse_layer(s: &str) -> Option<(FaceDir, usize)> {
+        let slice = match s.to_uppercase().as_str() {
+            "M" => Some(FaceDir::Left),
+            "E" => Some(FaceDir::Down),
+            "S" => Some(FaceDir::Front),
+            _ => None,
+        };
+        if let Some(face_dir) = slice {
+            return Some((face_dir, 2));
+        }
+        let face_start = s.find(|c: char| !c.is_ascii_digit())?;
+        let layer = match face_start {
+            0 => 1,
+            _ => s[..face_start].parse().ok().filter(|&layer| layer > 0)?,
+        };
+        Some((FaceDir::from_string(&s[face_start..])?, layer))
     }
 
-    pub fn algo_string(algo: &Vec<Turn>) -> String {
+    /// Writes an algorithm as its turns separated by spaces, like `R U R' U'`, the reverse of
+    /// `parse_algorithm`.
+    pub fn algo_string(algo: &[Turn]) -> String {
         algo.iter()
             .map(|t| t.to_string())
             .collect::<Vec<String>>()
             .join(" ")
     }
+
+    /// Returns the algorithm that undoes `algo`: its turns reversed, from the last one to the
+    /// first one.
+    pub fn inverse_algorithm(algo: &[Turn]) -> Vec<Turn> {
+        algo.iter().rev().map(Turn::get_reversed).collect()
+    }
+
+    /// Returns the number of turns of `algo` on a cube of size `size` in the half turn metric,
+    /// where a quarter or half turn of a face is one turn and a turn of an inner slice is two,
+    /// like the faces on each side of it, as in fewest moves competitions.
+    pub fn htm_count(algo: &[Turn], size: usize) -> usize {
+        algo.iter()
+            .map(|turn| match turn.layer {
+                1 => 1,
+                layer if layer == size => 1,
+                _ => 2,
+            })
+            .sum()
+    }
+
+    /// Returns the link of alg.cubing.net that replays `algo` in 3D on a cube of size `size`
+    /// scrambled by `setup`. The notation is the same as ours, with `_` for the spaces and `-`
+    /// for the primes.
+    pub fn alg_cubing_url(size: usize, setup: &[Turn], algo: &[Turn]) -> String {
+        let encode = |algo: &[Turn]| Turn::algo_string(algo).replace(' ', "_").replace('\'', "-");
+        format!(
+            "https://alg.cubing.net/?puzzle={size}x{size}x{size}&setup={}&alg={}",
+            encode(setup),
+            encode(algo)
+        )
+    }
 }
 impl Display for Turn {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut s = self.face_dir.to_string();
-        if self.turn_dir == TurnDir::CounterClockwise {
-            s.push('\'');
+        let mut s = match self.layer {
+            1 => self.face_dir.to_string(),
+            layer => format!("{layer}{}", self.face_dir),
+        };
+        match self.turn_dir {
+            TurnDir::Clockwise => (),
+            TurnDir::CounterClockwise => s.push('\''),
+            TurnDir::Half => s.push('2'),
         }
         write!(f, "{}", s)
     }
 }
 
+/// Number of facelet color codes packed in a word by `Cube::packed_facelets`.
+const NIBBLES_PER_WORD: usize = 16;
+
+/// Function called by a cube after each of its turns with the turn and the facelets it moved, see
+/// `Cube::on_turn`.
+pub type TurnListener = Box<dyn FnMut(&Turn, &[usize]) + Send + Sync>;
+
 /// struct that models a cube
-#[derive(Clone)]
 pub struct Cube {
     /// array of `Face`, each element coresponding to a face on the cube.
     /// the direction of each element depends on `dir_order`, which depends on `Cube::INIT_CONFIG`
@@ -299,6 +705,20 @@ pub struct Cube {
     dir_order: [FaceDir; 6],
     /// Size the cube.
     size: usize,
+    /// Functions called after each turn, see `Cube::on_turn`.
+    listeners: Vec<TurnListener>,
+}
+impl Clone for Cube {
+    /// Clones the stickers of the cube. The listeners aren't cloned, so that the copies made by
+    /// the searches don't call them.
+    fn clone(&self) -> Self {
+        Cube {
+            faces: self.faces.clone(),
+            dir_order: self.dir_order,
+            size: self.size,
+            listeners: Vec::new(),
+        }
+    }
 }
 impl Cube {
     /// Holds information about which face has which color initially.
@@ -311,6 +731,28 @@ impl Cube {
         (FaceDir::Back, Color::Red),
     ];
 
+    /// Returns the color of the face at `face_dir` according to `Cube::INIT_CONFIG`.
+    fn get_init_color(face_dir: FaceDir) -> Color {
+        let (_, color) = Cube::INIT_CONFIG
+            .iter()
+            .find(|(fd, _)| *fd == face_dir)
+            .unwrap();
+        *color
+    }
+
+    /// Returns the color on the face opposite to the face that has `color`, according to
+    /// `Cube::INIT_CONFIG`.
+    fn get_opposite_color(color: Color) -> Color {
+        let (face_dir, _) = Cube::INIT_CONFIG.iter().find(|(_, c)| *c == color).unwrap();
+        Cube::get_init_color(face_dir.get_opposite())
+    }
+
+    /// Returns a builder of a cube, which sets its size, its colors and its scramble, like
+    /// `Cube::builder().size(3).scheme(Scheme::WcaWhiteTop).scrambled(20, seed).build()`.
+    pub fn builder() -> CubeBuilder {
+        CubeBuilder::default()
+    }
+
     /// Create a new cube with `Cube::INIT_COFIG` configurations.
     pub fn new(size: usize) -> Cube {
         let mut faces = Vec::with_capacity(6);
@@ -323,9 +765,212 @@ impl Cube {
             faces: faces.try_into().unwrap(),
             dir_order: dir_order.try_into().unwrap(),
             size,
+            listeners: Vec::new(),
+        }
+    }
+
+    /// Creates a cube of size `size` from the color codes of its facelets, ordered by
+    /// `FaceDir::FACELET_ORDER`.
+    ///
+    /// Returns `None` if the number of facelets is wrong or a code isn't a valid color. The cube
+    /// isn't checked to be solvable.
+    pub fn from_facelet_codes(size: usize, codes: &[u8]) -> Option<Cube> {
+        if codes.len() != 6 * size * size {
+            return None;
+        }
+        let mut cube = Cube::new(size);
+        for (face_dir, face_codes) in FaceDir::FACELET_ORDER.iter().zip(codes.chunks(size * size)) {
+            let colors = face_codes
+                .iter()
+                .map(|&c| Color::from_code(c))
+                .collect::<Option<Vec<Color>>>()?;
+            cube.get_face_mut(face_dir).colors =
+                Array::from_shape_vec((size, size), colors).unwrap();
+        }
+        Some(cube)
+    }
+
+    /// Returns the color codes of all the facelets, ordered by `FaceDir::FACELET_ORDER`.
+    pub fn facelet_codes(&self) -> Vec<u8> {
+        FaceDir::FACELET_ORDER
+            .iter()
+            .flat_map(|face_dir| self.get_face(face_dir).colors.iter().map(|c| c.code()))
+            .collect()
+    }
+
+    /// Returns the colors of all the facelets, in the order of `facelet_codes`.
+    pub fn facelet_colors(&self) -> Vec<Color> {
+        FaceDir::FACELET_ORDER
+            .iter()
+            .flat_map(|face_dir| self.get_face(face_dir).colors.iter().copied())
+            .collect()
+    }
+
+    /// Returns the colors of all the facelets as the letters of their colors, like `WWWWRRRR...`
+    /// for a solved 2x2, in the order of `facelet_codes`.
+    pub fn facelet_string(&self) -> String {
+        FaceDir::FACELET_ORDER
+            .iter()
+            .flat_map(|face_dir| self.get_face(face_dir).colors.iter().map(Color::letter))
+            .collect()
+    }
+
+    /// Creates a cube of size `size` from the letters of the colors of its facelets, the reverse
+    /// of `facelet_string`.
+    ///
+    /// Returns `None` if the number of facelets is wrong or a letter isn't a color. The cube
+    /// isn't checked to be solvable, see `is_solvable`.
+    pub fn from_facelet_string(size: usize, letters: &str) -> Option<Cube> {
+        let codes = letters
+            .chars()
+            .map(|letter| Color::from_char(letter).map(|color| color.code()))
+            .collect::<Option<Vec<u8>>>()?;
+        Cube::from_facelet_codes(size, &codes)
+    }
+
+    /// Creates a cube from the letters of the colors of its facelets like `from_facelet_string`,
+    /// finding its size from their number.
+    ///
+    /// Returns `None` if the number of facelets isn't the one of a cube of size 2 or more, or if
+    /// a letter isn't a color.
+    pub fn from_facelets(letters: &str) -> Option<Cube> {
+        // 6 faces of size * size facelets
+        let size = ((letters.len() / 6) as f64).sqrt().round() as usize;
+        Cube::from_facelet_string(size, letters).filter(|_| size >= 2)
+    }
+
+    /// Returns the position of the cubelet that the sticker at `row`, `col` of the face at
+    /// `face_dir` is on.
+    ///
+    /// The position is `[x, y, z]`, each in `0..size`, with the down left back cubelet at
+    /// `[0, 0, 0]` (see `CubeAxis` for the direction of the axes).
+    fn get_sticker_cubelet(size: usize, face_dir: &FaceDir, row: usize, col: usize) -> [usize; 3] {
+        let last = size - 1;
+        match face_dir {
+            FaceDir::Up => [col, last, row],
+            FaceDir::Down => [col, 0, last - row],
+            FaceDir::Right => [last, last - row, last - col],
+            FaceDir::Left => [0, last - row, col],
+            FaceDir::Front => [col, last - row, last],
+            FaceDir::Back => [last - col, last - row, 0],
         }
     }
 
+    /// Returns the letter of the sticker at `row`, `col` of the face at `face_dir` in the Speffz
+    /// lettering scheme used by blindfold solvers, or `None` if the sticker has no letter.
+    ///
+    /// The corners and the edges each get the letters A to X, four per face in the order U, L,
+    /// F, R, B, D. On each face seen from the front (with B above U and F above D), the corners
+    /// are lettered clockwise from the top left one, and the edges clockwise from the top one.
+    /// Only the corners and the middle edges of odd cubes have letters.
+    pub fn speffz_letter(size: usize, face_dir: &FaceDir, row: usize, col: usize) -> Option<char> {
+        let face_offset = match face_dir {
+            FaceDir::Up => 0,
+            FaceDir::Left => 4,
+            FaceDir::Front => 8,
+            FaceDir::Right => 12,
+            FaceDir::Back => 16,
+            FaceDir::Down => 20,
+        };
+        // the faces of `Face` are already seen from the front, with their first row on top
+        let (last, middle) = (size - 1, size / 2);
+        let is_middle = |i| size % 2 == 1 && size > 1 && i == middle;
+        let position = match (row, col) {
+            _ if size < 2 => return None,
+            (0, 0) => 0,
+            (0, c) if c == last => 1,
+            (r, c) if r == last && c == last => 2,
+            (r, 0) if r == last => 3,
+            (0, c) if is_middle(c) => 0,
+            (r, c) if is_middle(r) && c == last => 1,
+            (r, c) if r == last && is_middle(c) => 2,
+            (r, 0) if is_middle(r) => 3,
+            _ => return None,
+        };
+        Some((b'A' + face_offset + position) as char)
+    }
+
+    /// Returns the facelet indices (as in `facelet_codes`) grouped by the piece they belong to.
+    ///
+    /// Corners have 3 facelets, edges have 2 and centers have 1. The groups are ordered by the
+    /// position of their cubelet, and the facelets in each group by `FaceDir::FACELET_ORDER`.
+    pub fn piece_groups(size: usize) -> Vec<Vec<usize>> {
+        let mut groups: HashMap<[usize; 3], Vec<usize>> = HashMap::new();
+        let mut i = 0;
+        for face_dir in FaceDir::FACELET_ORDER.iter() {
+            for row in 0..size {
+                for col in 0..size {
+                    let cubelet = Cube::get_sticker_cubelet(size, face_dir, row, col);
+                    groups.entry(cubelet).or_default().push(i);
+                    i += 1;
+                }
+            }
+        }
+        let mut groups: Vec<([usize; 3], Vec<usize>)> = groups.into_iter().collect();
+        groups.sort();
+        groups.into_iter().map(|(_, group)| group).collect()
+    }
+
+    /// Returns the size of the cube.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the approximate number of bytes that the cube takes, including its stickers.
+    pub fn memory_size(&self) -> usize {
+        std::mem::size_of::<Cube>() + 6 * self.size * self.size * std::mem::size_of::<Color>()
+    }
+
+    /// Returns the Zobrist hash of the cube's stickers.
+    ///
+    /// Every (facelet, color) pair gets a pseudo-random key, and the hash is the XOR of the keys of
+    /// all the stickers. Equal cubes always have the same hash and different cubes almost never do,
+    /// so the hash can be used as a key for caches and transposition tables.
+    pub fn zobrist_hash(&self) -> u64 {
+        Cube::hash_facelet_codes(&self.facelet_codes())
+    }
+
+    /// Returns a hash that is the same for every cube that only differs from this one by a whole
+    /// cube rotation or by which color is which, i.e. for the states symmetric to this one.
+    ///
+    /// Symmetric states take the same number of turns to solve when the turns of every face are
+    /// allowed, so a search can skip a state once it has seen a symmetric one. The hash is the
+    /// smallest Zobrist hash of the 24 orientations of the cube, each recolored with the colors
+    /// numbered in the order they first appear.
+    pub fn symmetric_hash(&self) -> u64 {
+        self.all_orientations()
+            .iter()
+            .map(|cube| {
+                let mut recolored_codes = [None; 6];
+                let mut num_colors = 0;
+                let codes: Vec<u8> = cube
+                    .facelet_codes()
+                    .iter()
+                    .map(|&code| {
+                        *recolored_codes[code as usize].get_or_insert_with(|| {
+                            num_colors += 1;
+                            num_colors - 1
+                        })
+                    })
+                    .collect();
+                Cube::hash_facelet_codes(&codes)
+            })
+            .min()
+            .expect("Error when hashing the orientations of the cube")
+    }
+
+    /// Returns the Zobrist hash of the color codes of the facelets (see `zobrist_hash`).
+    fn hash_facelet_codes(codes: &[u8]) -> u64 {
+        codes.iter().enumerate().fold(0, |hash, (i, &code)| {
+            // generate the key of the facelet and color with splitmix64, so we don't need to
+            // store a table of random numbers for every cube size.
+            let mut key = (i as u64 * 6 + code as u64).wrapping_add(0x9e3779b97f4a7c15);
+            key = (key ^ (key >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            key = (key ^ (key >> 27)).wrapping_mul(0x94d049bb133111eb);
+            hash ^ key ^ (key >> 31)
+        })
+    }
+
     fn get_dir_index(&self, face_dir: &FaceDir) -> usize {
         self.dir_order.iter().position(|fd| fd == face_dir).unwrap()
     }
@@ -359,7 +1004,16 @@ impl Cube {
     /// A band of a face is the colors that are rotated when we turn that face that aren't on the face
     /// itself.
     fn rotate_band(&mut self, turn: &Turn, layer: usize) {
+        if turn.turn_dir == TurnDir::Half {
+            // the band is rotated as two clockwise turns
+            let quarter_turn = Turn::new(turn.face_dir, TurnDir::Clockwise);
+            self.rotate_band(&quarter_turn, layer);
+            self.rotate_band(&quarter_turn, layer);
+            return;
+        }
         let (axis_of_rotation, rotate_dir) = turn.face_dir.get_rotate_axis_and_dir(turn.turn_dir);
+        // the turn is a quarter turn from here
+        let is_clockwise = rotate_dir == TurnDir::Clockwise;
         let is_positive = turn.face_dir.is_positive();
 
         // sometimes, the band includes the slice at the end of a face.
@@ -395,16 +1049,15 @@ impl Cube {
         let check_is_reversed = |&d| match axis_of_rotation {
             CubeAxis::X => {
                 d == FaceDir::Back
-                    || match rotate_dir {
-                        TurnDir::Clockwise => d == FaceDir::Down,
-                        TurnDir::CounterClockwise => d == FaceDir::Up,
+                    || if is_clockwise {
+                        d == FaceDir::Down
+                    } else {
+                        d == FaceDir::Up
                     }
             }
             CubeAxis::Y => false,
-            CubeAxis::Z => match rotate_dir {
-                TurnDir::Clockwise => d == FaceDir::Down || d == FaceDir::Up,
-                TurnDir::CounterClockwise => d == FaceDir::Right || d == FaceDir::Left,
-            },
+            CubeAxis::Z if is_clockwise => d == FaceDir::Down || d == FaceDir::Up,
+            CubeAxis::Z => d == FaceDir::Right || d == FaceDir::Left,
         };
 
         // loop through all the face surrounding the axis that we are turning.
@@ -423,14 +1076,14 @@ impl Cube {
                 self.size - layer
             };
             let is_column = check_is_column(d);
-            let curr_slice = self.get_face(&d).get_slice(i, is_column).to_owned();
+            let curr_slice = self.get_face(d).get_slice(i, is_column).to_owned();
 
             // we set the appropriate slice the `prev_slice` if it exists.
             if let Some(mut ps) = prev_slice {
                 if check_is_reversed(d) {
                     ps.invert_axis(Axis(0));
                 }
-                self.get_face_mut(&d).set_slice(i, is_column, &ps.view());
+                self.get_face_mut(d).set_slice(i, is_column, &ps.view());
             }
 
             // updates `prev_slice`
@@ -443,10 +1096,131 @@ impl Cube {
         if layer == 0 {
             panic!("layer must be nonzero. index starts at one (rubiks cube notation convention).");
         }
+        #[cfg(feature = "debug-invariants")]
+        let before = self.clone();
         if layer == 1 {
             self.get_face_mut(&turn.face_dir).rotate(turn.turn_dir);
         }
         self.rotate_band(turn, layer);
+        #[cfg(feature = "debug-invariants")]
+        self.check_invariants(&before, turn, layer);
+    }
+
+    /// Panics with a report if turning `layer` with `turn` broke the cube, which was `before`:
+    /// a turn only moves stickers, so it can't change how many stickers each color has or the
+    /// colors of the pieces, and a 2x2 or 3x3 that could be solved still can. Only with the
+    /// `debug-invariants` feature, to catch mistakes when changing how the layers are turned.
+    #[cfg(feature = "debug-invariants")]
+    fn check_invariants(&self, before: &Cube, turn: &Turn, layer: usize) {
+        let color_counts = |cube: &Cube| {
+            let mut counts = [0; 6];
+            for color in cube.facelet_colors() {
+                counts[color.code() as usize] += 1;
+            }
+            counts
+        };
+        // the colors of each piece, sorted so that moving or twisting a piece doesn't change them
+        let pieces = |cube: &Cube| {
+            let codes = cube.facelet_codes();
+            let mut pieces: Vec<Vec<u8>> = Cube::piece_groups(self.size)
+                .iter()
+                .map(|group| {
+                    let mut colors: Vec<u8> = group.iter().map(|&i| codes[i]).collect();
+                    colors.sort();
+                    colors
+                })
+                .collect();
+            pieces.sort();
+            pieces
+        };
+
+        let mut problems = Vec::new();
+        let counts = color_counts(before).into_iter().zip(color_counts(self));
+        for (code, (count_before, count_after)) in counts.enumerate() {
+            if count_before != count_after {
+                problems.push(format!(
+                    "{} has {count_after} stickers instead of {count_before}",
+                    Color::ALL_COLORS[code].name()
+                ));
+            }
+        }
+        let (pieces_before, pieces_after) = (pieces(before), pieces(self));
+        if pieces_before != pieces_after {
+            let letters = |piece: &Vec<u8>| -> String {
+                piece
+                    .iter()
+                    .filter_map(|&code| Color::from_code(code).map(|color| color.letter()))
+                    .collect()
+            };
+            let lost: Vec<String> = pieces_before
+                .iter()
+                .filter(|piece| !pieces_after.contains(piece))
+                .map(letters)
+                .collect();
+            let made: Vec<String> = pieces_after
+                .iter()
+                .filter(|piece| !pieces_before.contains(piece))
+                .map(letters)
+                .collect();
+            problems.push(format!(
+                "the pieces {} became {}",
+                lost.join(" "),
+                made.join(" ")
+            ));
+        }
+        if before.is_solvable() == Some(true) && self.is_solvable() != Some(true) {
+            problems.push("the cube can't be solved anymore".to_string());
+        }
+        if !problems.is_empty() {
+            panic!(
+                "turning layer {layer} with {turn} broke the {size}x{size} cube:\n  {}\nbefore: {}\nafter:  {}",
+                problems.join("\n  "),
+                before.facelet_string(),
+                self.facelet_string(),
+                size = self.size,
+            );
+        }
+    }
+
+    /// Applies `turn` to its own layer (see `Turn::new_slice`). Panics if the cube doesn't have
+    /// that layer.
+    pub fn apply_turn(&mut self, turn: &Turn) {
+        if turn.layer > self.size {
+            panic!(
+                "can't turn layer {} of a cube of size {}",
+                turn.layer, self.size
+            );
+        }
+        if turn.layer > 1 && turn.layer == self.size {
+            // the last layer from a face is the opposite face, turned the other way
+            let opposite_turn =
+                Turn::new(turn.face_dir.get_opposite(), turn.turn_dir.get_reversed());
+            self.turn_layer(&opposite_turn, 1);
+        } else {
+            self.turn_layer(turn, turn.layer);
+        }
+        if !self.listeners.is_empty() {
+            let moved: Vec<usize> = turn
+                .facelet_permutation(self.size)
+                .iter()
+                .enumerate()
+                .filter(|&(to, &from)| to != from)
+                .map(|(to, _)| to)
+                .collect();
+            for listener in self.listeners.iter_mut() {
+                listener(turn, &moved);
+            }
+        }
+    }
+
+    /// Calls `listener` after each turn of the cube by `apply_turn`, and so by `apply_algorithm`
+    /// and `scramble`, with the turn and the indices of the facelets it moved (as in
+    /// `facelet_codes`), so that a view or a log can follow the cube without comparing its
+    /// stickers. Rotations of the whole cube aren't turns.
+    ///
+    /// The listeners stay with this cube: its clones don't have them.
+    pub fn on_turn(&mut self, listener: impl FnMut(&Turn, &[usize]) + Send + Sync + 'static) {
+        self.listeners.push(Box::new(listener));
     }
 
     /// Returns true if all the faces on the cube each consist of only one color.
@@ -454,19 +1228,83 @@ impl Cube {
         self.faces.iter().all(|face| face.is_single_color())
     }
 
+    /// Returns whether a 2x2 or 3x3 can be solved with turns, e.g. after its colors were typed
+    /// in: every piece has to be there once, twisted and flipped like on a cube that was only
+    /// turned. Returns `None` for other sizes.
+    pub fn is_solvable(&self) -> Option<bool> {
+        let cubie_cube = cubie::CubieCube::from_cube(self);
+        match self.size {
+            2 => Some(cubie_cube.is_some_and(|cubie_cube| cubie_cube.are_corners_solvable())),
+            3 => Some(cubie_cube.is_some_and(|cubie_cube| cubie_cube.is_solvable())),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the face at `face_dir` consists of only one color.
+    pub fn is_face_solved(&self, face_dir: &FaceDir) -> bool {
+        self.get_face(face_dir).is_single_color()
+    }
+
+    /// Returns true if the first `num_layers` layers under the face at `face_dir` are solved.
+    ///
+    /// That is, the face is a single color, and on each of the 4 faces around it, the stickers in
+    /// those layers are a single color. On odd sized cubes, they also have to match the center of
+    /// their face, so that the layers are lined up with the rest of the cube.
+    pub fn are_layers_solved(&self, face_dir: &FaceDir, num_layers: usize) -> bool {
+        if !self.is_face_solved(face_dir) {
+            return false;
+        }
+        let axis = match face_dir.get_axis() {
+            CubeAxis::X => 0,
+            CubeAxis::Y => 1,
+            CubeAxis::Z => 2,
+        };
+        // distance of a cubelet from the face at `face_dir`, counted in layers
+        let layer_of = |cubelet: [usize; 3]| {
+            if face_dir.is_positive() {
+                self.size - 1 - cubelet[axis]
+            } else {
+                cubelet[axis]
+            }
+        };
+        let side_dirs = FaceDir::ALL_FACE_DIR
+            .iter()
+            .filter(|d| d.get_axis() != face_dir.get_axis());
+        for side_dir in side_dirs {
+            let colors = &self.get_face(side_dir).colors;
+            let center = self.size / 2;
+            let mut layer_color = (self.size % 2 == 1).then(|| colors[[center, center]]);
+            for ((row, col), color) in colors.indexed_iter() {
+                let cubelet = Cube::get_sticker_cubelet(self.size, side_dir, row, col);
+                if layer_of(cubelet) >= num_layers {
+                    continue;
+                }
+                match layer_color {
+                    None => layer_color = Some(*color),
+                    Some(c) if c != *color => return false,
+                    Some(_) => (),
+                }
+            }
+        }
+        true
+    }
+
     /// Scramble the cube with `k` random 90-degree turns. Returns the list of turns used to scramble.
     ///
-    /// It's guaranteed that the turns would not cancel the immediately previous turn.
+    /// It's guaranteed that the turns would not cancel the immediately previous turn. Cubes bigger
+    /// than 3x3 get inner layers turned too, see `Turn::random_turn`.
     pub fn scramble(&mut self, k: usize) -> Vec<Turn> {
-        if self.size > 2 {
-            panic!("scrambling not implemented for cubes larger than 2x2")
-        }
+        self.scramble_with_rng(k, &mut rand::thread_rng())
+    }
+
+    /// Scrambles the cube like `scramble`, with the random numbers of `rng`, e.g. a seeded one
+    /// to get the same scramble again.
+    pub fn scramble_with_rng(&mut self, k: usize, rng: &mut impl Rng) -> Vec<Turn> {
         let mut algo = Vec::with_capacity(k);
         let mut prev_turn: Option<Turn> = None;
-        let mut rng = rand::thread_rng();
         for _ in 0..k {
             let turn = loop {
-                let turn_proposal = Turn::random_turn(&mut rng);
+                let turn_proposal = Turn::random_turn(rng, self.size);
                 if let Some(pt) = &prev_turn {
                     if turn_proposal.is_reversed(pt) {
                         continue;
@@ -474,9 +1312,9 @@ impl Cube {
                 }
                 break turn_proposal;
             };
-            algo.push(turn.clone());
+            algo.push(turn);
             // update `prev_turn` and apply random turn
-            self.turn_layer(&turn, 1);
+            self.apply_turn(&turn);
             prev_turn = Some(turn);
         }
         algo
@@ -485,7 +1323,7 @@ impl Cube {
     /// Applies the list of turns to the cube.
     pub fn apply_algorithm(&mut self, algo: Vec<Turn>) {
         for turn in algo.iter() {
-            self.turn_layer(turn, 1);
+            self.apply_turn(turn);
         }
     }
 
@@ -496,23 +1334,114 @@ impl Cube {
         }
     }
 
+    /// Returns the number of facelets whose colors differ between the two cubes.
+    ///
+    /// Panics if the cubes don't have the same size.
     pub fn hamming_distance(&self, other: &Cube) -> usize {
         if self.size != other.size {
             panic!("Can't get hamming distance from 2 different sized cubes!");
         }
-        let mut distance = 0;
-        for i in 0..6 {
-            let self_colors = self.faces[i].colors.iter();
-            let other_colors = other.faces[i].colors.iter();
-            // count the differences between two faces
-            distance += self_colors
-                .zip(other_colors)
-                .filter(|&(sc, oc)| sc != oc)
-                .count();
+        // compare the faces at the same direction, since `faces` of a rotated cube are in a
+        // different order.
+        FaceDir::FACELET_ORDER
+            .iter()
+            .map(|face_dir| {
+                let colors = self.get_face(face_dir).colors.iter();
+                let other_colors = other.get_face(face_dir).colors.iter();
+                colors.zip(other_colors).filter(|(a, b)| a != b).count()
+            })
+            .sum()
+    }
+
+    /// Returns the indices of the facelets (as in `facelet_codes`) whose colors differ between
+    /// the two cubes, whose `hamming_distance` is the number of indices.
+    pub fn diff(&self, other: &Cube) -> Vec<usize> {
+        if self.size != other.size {
+            panic!("Can't diff 2 different sized cubes!");
+        }
+        self.facelet_codes()
+            .iter()
+            .zip(other.facelet_codes().iter())
+            .enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Returns the facelets that differ from the nearest solved cube, in any orientation (see
+    /// `diff`).
+    pub fn unsolved_facelets(&self) -> Vec<usize> {
+        Cube::all_possible_solved_cubes(self.size)
+            .iter()
+            .map(|solved| self.diff(solved))
+            .min_by_key(|diff| diff.len())
+            .unwrap()
+    }
+
+    /// Returns the color codes of the facelets (as in `facelet_codes`) packed as nibbles, 16 per
+    /// word, so that two cubes can be compared with `packed_hamming_distance`.
+    ///
+    /// Packing a cube once and comparing it with many packed cubes is much faster than comparing
+    /// the stickers one by one each time, which is what the heuristics do with the solved cubes.
+    pub fn packed_facelets(&self) -> Vec<u64> {
+        let mut packed = vec![0; (6 * self.size * self.size).div_ceil(NIBBLES_PER_WORD)];
+        for (i, code) in self.facelet_codes().into_iter().enumerate() {
+            packed[i / NIBBLES_PER_WORD] |= (code as u64) << (4 * (i % NIBBLES_PER_WORD));
+        }
+        packed
+    }
+
+    /// Returns the number of stickers that differ between two cubes of the same size packed with
+    /// `packed_facelets`.
+    pub fn packed_hamming_distance(a: &[u64], b: &[u64]) -> usize {
+        a.iter()
+            .zip(b.iter())
+            .map(|(a, b)| {
+                // fold the bits of every differing nibble onto its lowest bit, then count them
+                let diff = a ^ b;
+                let diff = diff | (diff >> 1);
+                let diff = diff | (diff >> 2);
+                (diff & 0x1111_1111_1111_1111).count_ones() as usize
+            })
+            .sum()
+    }
+
+    /// Turns every layer along the face of `turn`, which rotates the whole cube.
+    ///
+    /// Unlike `rotate_whole_cube`, the stickers are moved by turns, so the faces stay in their
+    /// usual directions and orientations, and the result can be compared sticker by sticker.
+    fn turn_all_layers(&mut self, turn: &Turn) {
+        for layer in 1..self.size {
+            self.turn_layer(turn, layer);
         }
-        distance
+        let opposite_turn = Turn::new(turn.face_dir.get_opposite(), turn.turn_dir.get_reversed());
+        self.turn_layer(&opposite_turn, 1);
     }
 
+    /// Returns the cube in each of its 24 orientations, starting with the cube as it is.
+    pub fn all_orientations(&self) -> Vec<Cube> {
+        let x = Turn::new(FaceDir::Right, TurnDir::Clockwise);
+        let y = Turn::new(FaceDir::Up, TurnDir::Clockwise);
+        let z = Turn::new(FaceDir::Front, TurnDir::Clockwise);
+        // rotations that bring each face to the top
+        let to_top: [&[Turn]; 6] = [&[], &[x], &[x, x], &[x, x, x], &[z], &[z, z, z]];
+
+        let mut orientations = Vec::with_capacity(24);
+        for rotations in to_top {
+            let mut cube = self.clone();
+            for rotation in rotations {
+                cube.turn_all_layers(rotation);
+            }
+            // then turn the cube around the top face
+            for _ in 0..4 {
+                orientations.push(cube.clone());
+                cube.turn_all_layers(&y);
+            }
+        }
+        orientations
+    }
+
+    /// Returns the solved cube of size `size` in each of its 24 orientations.
     pub fn all_possible_solved_cubes(size: usize) -> Vec<Cube> {
         let mut res = Vec::with_capacity(24); // there are 6*4=24 possible orientation of the cube
         let mut cube = Cube::new(size);
@@ -577,3 +1506,45 @@ impl Display for Cube {
         write!(f, "{s}")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_turns() {
+        let algo = Turn::parse_algorithm("R U' F2 2R' M".split(' ').collect()).unwrap();
+        assert_eq!(
+            algo,
+            [
+                Turn::new_slice(FaceDir::Right, TurnDir::Clockwise, 1),
+                Turn::new_slice(FaceDir::Up, TurnDir::CounterClockwise, 1),
+                Turn::new_slice(FaceDir::Front, TurnDir::Half, 1),
+                Turn::new_slice(FaceDir::Right, TurnDir::CounterClockwise, 2),
+                Turn::new_slice(FaceDir::Left, TurnDir::Clockwise, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_turns() {
+        // the typographic apostrophe and the accent are more than one byte long
+        for turn in ["R’", "Ré", "", "2", "'", "X", "0R"] {
+            assert_eq!(Turn::parse_algorithm(vec![turn]), None, "{turn:?}");
+        }
+    }
+
+    #[test]
+    fn hamming_distance_matches_the_packed_one() {
+        let mut cube = Cube::new(3);
+        cube.apply_algorithm(Turn::parse_algorithm("R U F' L2 D B".split(' ').collect()).unwrap());
+        let mut rotated = Cube::new(3);
+        rotated.rotate_whole_cube(CubeAxis::X, TurnDir::Clockwise);
+        for other in [Cube::new(3), rotated] {
+            assert_eq!(
+                cube.hamming_distance(&other),
+                Cube::packed_hamming_distance(&cube.packed_facelets(), &other.packed_facelets())
+            );
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! The colors that the stickers are drawn with (`Palette`), and which colors the terminal can
+//! show (`ColorMode`).
+
+use std::{
+    env, fs,
+    io::{self, IsTerminal},
+    path::Path,
+    sync::RwLock,
+};
+
+use super::Color;
+
+/// Colors of the stickers, used by the net `Display` of the cube and by its 3D render.
+///
+/// Each color (indexed by its code, see `Color::code`) has the RGB value used by terminals that
+/// support it, and the closest of the basic ANSI background colors otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    rgb: [(u8, u8, u8); 6],
+    ansi8: [u8; 6],
+}
+impl Palette {
+    /// The usual colors of a cube.
+    pub const STANDARD: Palette = Palette {
+        // white, red, blue, yellow, orange, green
+        rgb: [
+            (255, 255, 255),
+            (183, 18, 52),
+            (0, 70, 173),
+            (255, 213, 0),
+            (255, 88, 0),
+            (0, 155, 72),
+        ],
+        ansi8: [47, 41, 44, 103, 43, 42],
+    };
+    /// Colors from the Okabe-Ito palette, which stay distinct with red-green color blindness. Red
+    /// becomes purple and green becomes teal, so they can't be mistaken for orange and yellow.
+    pub const DEUTERANOPIA: Palette = Palette {
+        rgb: [
+            (255, 255, 255),
+            (204, 121, 167),
+            (0, 114, 178),
+            (240, 228, 66),
+            (230, 159, 0),
+            (0, 158, 115),
+        ],
+        ansi8: [47, 45, 44, 103, 43, 46],
+    };
+    /// Fully saturated colors, with the bright basic colors.
+    pub const HIGH_CONTRAST: Palette = Palette {
+        rgb: [
+            (255, 255, 255),
+            (255, 0, 0),
+            (0, 0, 255),
+            (255, 255, 0),
+            (255, 128, 0),
+            (0, 255, 0),
+        ],
+        ansi8: [107, 101, 104, 103, 43, 102],
+    };
+
+    /// Returns the palette named `name`: `standard`, `deuteranopia-safe` or `high-contrast`.
+    pub fn from_name(name: &str) -> Option<Palette> {
+        match name {
+            "standard" => Some(Palette::STANDARD),
+            "deuteranopia-safe" => Some(Palette::DEUTERANOPIA),
+            "high-contrast" => Some(Palette::HIGH_CONTRAST),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the palette for `from_name`, or `custom` if it's none of those.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Palette::STANDARD => "standard",
+            Palette::DEUTERANOPIA => "deuteranopia-safe",
+            Palette::HIGH_CONTRAST => "high-contrast",
+            _ => "custom",
+        }
+    }
+
+    /// Returns the palette used to print the colors, `Palette::STANDARD` until another one is set.
+    pub fn current() -> Palette {
+        *CURRENT_PALETTE
+            .read()
+            .expect("Error when reading the current palette")
+    }
+
+    /// Returns the RGB value of `color`.
+    pub fn rgb(&self, color: Color) -> (u8, u8, u8) {
+        self.rgb[color.code() as usize]
+    }
+
+    /// Returns `color` as a hex color, like `#b71234`.
+    pub fn hex(&self, color: Color) -> String {
+        let (r, g, b) = self.rgb(color);
+        format!("#{r:02x}{g:02x}{b:02x}")
+    }
+
+    /// Prints the colors with `self` from now on.
+    pub fn set_current(self) {
+        *CURRENT_PALETTE
+            .write()
+            .expect("Error when setting the current palette") = self;
+    }
+
+    /// Reads a palette from a file with a line per color to change, like `red = #b71234`. The
+    /// colors that aren't in the file keep their standard values, and empty lines or lines
+    /// starting with `//` are ignored.
+    pub fn from_file(path: &Path) -> io::Result<Palette> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let mut palette = Palette::STANDARD;
+        for line in fs::read_to_string(path)?.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            let (name, hex) = line
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("expected `color = #rrggbb`, got `{line}`")))?;
+            let code = COLOR_NAMES
+                .iter()
+                .position(|color_name| color_name.eq_ignore_ascii_case(name.trim()))
+                .ok_or_else(|| invalid(format!("unknown color `{}`", name.trim())))?;
+            palette.rgb[code] = parse_hex(hex.trim().trim_matches('"'))
+                .ok_or_else(|| invalid(format!("invalid hex color `{}`", hex.trim())))?;
+        }
+        Ok(palette)
+    }
+}
+
+/// Names of the colors in palette files, indexed by the color codes.
+const COLOR_NAMES: [&str; 6] = ["white", "red", "blue", "yellow", "orange", "green"];
+
+static CURRENT_PALETTE: RwLock<Palette> = RwLock::new(Palette::STANDARD);
+static CURRENT_COLOR_MODE: RwLock<Option<ColorMode>> = RwLock::new(None);
+
+/// Parses a color like `#b71234`.
+fn parse_hex(hex: &str) -> Option<(u8, u8, u8)> {
+    let hex = hex.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// How many colors the terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// 24-bit RGB colors.
+    TrueColor,
+    /// The 256 colors of xterm.
+    Ansi256,
+    /// The 8 basic ANSI colors, which almost every terminal supports.
+    Ansi8,
+    /// No escape codes at all, for terminals without colors and for output written to files.
+    /// The stickers are drawn with the letters of their colors, filled with a different
+    /// character for each color (see `Color::fill_char`).
+    NoColor,
+}
+impl ColorMode {
+    /// Guesses what the terminal supports from the `COLORTERM` and `TERM` environment variables,
+    /// falling back to the basic colors when unsure. There are no colors if the output isn't a
+    /// terminal, if `TERM` is `dumb`, or if `NO_COLOR` is set (see <https://no-color.org>).
+    pub fn detect() -> ColorMode {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+        let no_color = env::var("NO_COLOR").is_ok_and(|no_color| !no_color.is_empty());
+        let color_mode = if no_color || term == "dumb" || !io::stdout().is_terminal() {
+            ColorMode::NoColor
+        } else if colorterm == "truecolor" || colorterm == "24bit" {
+            ColorMode::TrueColor
+        } else if term.contains("256color") {
+            ColorMode::Ansi256
+        } else {
+            ColorMode::Ansi8
+        };
+        log::debug!("Drawing with {color_mode:?} (COLORTERM={colorterm:?}, TERM={term:?})");
+        color_mode
+    }
+
+    /// Returns the mode used to print the colors, detected the first time it's needed unless
+    /// another one was set.
+    pub fn current() -> ColorMode {
+        let color_mode = *CURRENT_COLOR_MODE
+            .read()
+            .expect("Error when reading the current color mode");
+        color_mode.unwrap_or_else(|| {
+            let color_mode = ColorMode::detect();
+            color_mode.set_current();
+            color_mode
+        })
+    }
+
+    /// Prints the colors with `self` from now on, whatever the terminal supports.
+    pub fn set_current(self) {
+        *CURRENT_COLOR_MODE
+            .write()
+            .expect("Error when setting the current color mode") = Some(self);
+    }
+
+    /// Returns the escape code that sets the background to `color` and the foreground to black.
+    pub(super) fn escape_code(self, color: Color) -> String {
+        format!("\x1b[30m{}", self.background_code(color))
+    }
+
+    /// Returns the escape code that sets the background to `color`.
+    pub(super) fn background_code(self, color: Color) -> String {
+        self.shaded_code(color, 1.0, true)
+    }
+
+    /// Returns the escape code that sets the foreground or the background to `color`, with its
+    /// RGB value multiplied by `brightness`. The basic colors can't be shaded, so they're the same
+    /// whatever the brightness.
+    pub(super) fn shaded_code(self, color: Color, brightness: f32, background: bool) -> String {
+        let palette = Palette::current();
+        let code = color.code() as usize;
+        let (r, g, b) = palette.rgb[code];
+        let shade = |c: u8| (c as f32 * brightness.clamp(0.0, 1.0)).round() as u8;
+        self.code(
+            (shade(r), shade(g), shade(b)),
+            palette.ansi8[code],
+            background,
+        )
+    }
+
+    /// Returns the escape code that sets the foreground or the background to the dark gray of the
+    /// borders between the stickers, which is black with the basic colors.
+    pub(super) fn border_code(self, background: bool) -> String {
+        self.code((20, 20, 20), 40, background)
+    }
+
+    /// Returns the escape code of the color `(r, g, b)`, or `ansi8` with the basic colors.
+    fn code(self, (r, g, b): (u8, u8, u8), ansi8: u8, background: bool) -> String {
+        let extended = if background { 48 } else { 38 };
+        match self {
+            ColorMode::TrueColor => format!("\x1b[{extended};2;{r};{g};{b}m"),
+            ColorMode::Ansi256 => format!("\x1b[{extended};5;{}m", ansi256(r, g, b)),
+            // the foreground codes are 10 below the background ones
+            ColorMode::Ansi8 if background => format!("\x1b[{ansi8}m"),
+            ColorMode::Ansi8 => format!("\x1b[{}m", ansi8 - 10),
+            ColorMode::NoColor => String::new(),
+        }
+    }
+}
+
+/// Returns the closest color of the 6x6x6 color cube of the 256 colors.
+fn ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |c: u8| ((c as u16 * 5 + 127) / 255) as u8;
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
@@ -0,0 +1,188 @@
+//! Reads the colors of a cube from photos of its faces.
+
+use std::{io, path::Path};
+
+use image::{imageops, Rgb, RgbImage};
+
+use super::{palette::Palette, Color, Cube, FaceDir};
+
+/// Largest difference from the color of the background for a pixel to count as background, see
+/// `trim_background`.
+const BACKGROUND_TOLERANCE: f32 = 30.0;
+/// Largest number of times the groups of colors are improved by k-means, see `classify`.
+const KMEANS_ITERATIONS: usize = 20;
+
+/// Reads the colors of a 2x2 or 3x3 of size `size` from photos or screenshots: one image of its
+/// net laid out like `rendering::render_net_svg`, or the 6 images of its faces in the order of
+/// `FaceDir::FACELET_ORDER`. Each image must be cropped to the net or the face, but a margin of
+/// one color around it is cut off.
+///
+/// The cube isn't checked to be solvable, so that the colors that were misread can be fixed.
+pub fn cube_from_images(size: usize, paths: &[&Path]) -> io::Result<Cube> {
+    let stickers: Vec<[f32; 3]> = match paths {
+        [path] => {
+            let img = trim_background(open_rgb(path)?);
+            let (face_w, face_h) = (img.width() / 4, img.height() / 3);
+            FaceDir::FACELET_ORDER
+                .iter()
+                .flat_map(|&face_dir| {
+                    let (x, y) = net_position(face_dir);
+                    sample_stickers(&img, x * face_w, y * face_h, face_w, face_h, size)
+                })
+                .collect()
+        }
+        [_, _, _, _, _, _] => {
+            let mut stickers = Vec::new();
+            for path in paths {
+                let img = trim_background(open_rgb(path)?);
+                let (w, h) = (img.width(), img.height());
+                stickers.extend(sample_stickers(&img, 0, 0, w, h, size));
+            }
+            stickers
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "expected the image of the net or the 6 images of the faces",
+            ))
+        }
+    };
+    Ok(Cube::from_facelet_codes(size, &classify(&stickers)).expect("Error when reading colors"))
+}
+
+/// Opens the image at `path` as RGB pixels.
+fn open_rgb(path: &Path) -> io::Result<RgbImage> {
+    let img = image::open(path).map_err(io::Error::other)?.to_rgb8();
+    if img.width() == 0 || img.height() == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the image is empty",
+        ));
+    }
+    Ok(img)
+}
+
+/// Returns where the face at `face_dir` is in the net, in faces from the top left corner.
+fn net_position(face_dir: FaceDir) -> (u32, u32) {
+    match face_dir {
+        FaceDir::Up => (1, 0),
+        FaceDir::Left => (0, 1),
+        FaceDir::Front => (1, 1),
+        FaceDir::Right => (2, 1),
+        FaceDir::Back => (3, 1),
+        FaceDir::Down => (1, 2),
+    }
+}
+
+/// Returns how different two colors are, as the distance between their RGB values.
+fn color_distance(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum::<f32>()
+        .sqrt()
+}
+
+fn pixel_rgb(pixel: &Rgb<u8>) -> [f32; 3] {
+    pixel.0.map(f32::from)
+}
+
+/// Cuts off the rows and columns on the edges of `img` that only have the color of its top left
+/// pixel, like the margin of an exported net or a plain background.
+fn trim_background(img: RgbImage) -> RgbImage {
+    let background = pixel_rgb(img.get_pixel(0, 0));
+    let is_background = |x: u32, y: u32| {
+        color_distance(&pixel_rgb(img.get_pixel(x, y)), &background) < BACKGROUND_TOLERANCE
+    };
+    let (w, h) = (img.width(), img.height());
+    let row_has_cube = |y: u32| (0..w).any(|x| !is_background(x, y));
+    let Some(top) = (0..h).find(|&y| row_has_cube(y)) else {
+        // the whole image is one color
+        return img;
+    };
+    let bottom = (0..h).rev().find(|&y| row_has_cube(y)).unwrap() + 1;
+    let column_has_cube = |x: u32| (top..bottom).any(|y| !is_background(x, y));
+    let left = (0..w).find(|&x| column_has_cube(x)).unwrap();
+    let right = (0..w).rev().find(|&x| column_has_cube(x)).unwrap() + 1;
+    imageops::crop_imm(&img, left, top, right - left, bottom - top).to_image()
+}
+
+/// Returns the mean color of each sticker of a face of `size` by `size` stickers that fills the
+/// rectangle of `w` by `h` pixels at `x`, `y` in `img`, row by row. Only the middle half of each
+/// sticker is used, away from the edges between the stickers.
+fn sample_stickers(img: &RgbImage, x: u32, y: u32, w: u32, h: u32, size: usize) -> Vec<[f32; 3]> {
+    let size = size as u32;
+    let mut stickers = Vec::new();
+    for row in 0..size {
+        for col in 0..size {
+            // the quarters of the sticker on each side are left out, keeping at least a pixel
+            let (x0, x1) = (
+                x + (4 * col + 1) * w / (4 * size),
+                x + (4 * col + 3) * w / (4 * size),
+            );
+            let (y0, y1) = (
+                y + (4 * row + 1) * h / (4 * size),
+                y + (4 * row + 3) * h / (4 * size),
+            );
+            let (x1, y1) = (u32::max(x1, x0 + 1), u32::max(y1, y0 + 1));
+            let mut sum = [0.0; 3];
+            let mut count = 0.0;
+            for py in y0..y1.min(img.height()) {
+                for px in x0..x1.min(img.width()) {
+                    let rgb = pixel_rgb(img.get_pixel(px, py));
+                    sum.iter_mut().zip(rgb).for_each(|(sum, c)| *sum += c);
+                    count += 1.0;
+                }
+            }
+            stickers.push(sum.map(|sum| sum / f32::max(count, 1.0)));
+        }
+    }
+    stickers
+}
+
+/// Groups the colors of the stickers by k-means and returns the code of the color of each
+/// sticker. The groups start at the colors of the current palette, so that each group keeps the
+/// color it started from while it moves to the colors of the actual stickers, which depend on
+/// the light and on the camera.
+fn classify(stickers: &[[f32; 3]]) -> Vec<u8> {
+    let palette = Palette::current();
+    let mut centers = Color::ALL_COLORS.map(|color| {
+        let (r, g, b) = palette.rgb(color);
+        [r, g, b].map(f32::from)
+    });
+    let nearest = |centers: &[[f32; 3]; 6], sticker: &[f32; 3]| {
+        (0..6)
+            .min_by(|&a, &b| {
+                color_distance(&centers[a], sticker)
+                    .total_cmp(&color_distance(&centers[b], sticker))
+            })
+            .unwrap()
+    };
+
+    let mut groups: Vec<usize> = stickers.iter().map(|s| nearest(&centers, s)).collect();
+    for _ in 0..KMEANS_ITERATIONS {
+        for (i, center) in centers.iter_mut().enumerate() {
+            let members: Vec<&[f32; 3]> = stickers
+                .iter()
+                .zip(&groups)
+                .filter(|(_, &group)| group == i)
+                .map(|(sticker, _)| sticker)
+                .collect();
+            // a color that no sticker is nearest to keeps its place
+            if !members.is_empty() {
+                *center = [0, 1, 2].map(|c| {
+                    members.iter().map(|sticker| sticker[c]).sum::<f32>() / members.len() as f32
+                });
+            }
+        }
+        let new_groups: Vec<usize> = stickers.iter().map(|s| nearest(&centers, s)).collect();
+        if new_groups == groups {
+            break;
+        }
+        groups = new_groups;
+    }
+    groups
+        .iter()
+        .map(|&group| Color::ALL_COLORS[group].code())
+        .collect()
+}
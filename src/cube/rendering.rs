@@ -1,6 +1,13 @@
-use ndarray::{arr1, arr2, Array, Array1, Array2, ArrayView2};
+//! `CubeRender` draws a cube in 3D, in the terminal, as SVG, or as the polygons of its stickers
+//! for another renderer, with animated turns and camera moves.
 
-use super::{Color, Cube, CubeAxis, FaceDir};
+use ndarray::{arr1, arr2, s, Array, Array1, Array2, ArrayView2};
+use rayon::prelude::*;
+
+use super::{
+    palette::{ColorMode, Palette},
+    Color, Cube, CubeAxis, FaceDir, Turn, TurnDir,
+};
 
 #[derive(Debug, Clone)]
 struct Vertex {
@@ -22,7 +29,15 @@ impl Vertex {
     fn transform(&mut self, matrix: ArrayView2<f32>) {
         self.coordinate = matrix.dot(&self.coordinate);
     }
-    fn to_img_coordinates(&mut self, x_scale: f32, y_scale: f32, img_w: usize, img_h: usize) {
+    /// Scales the vertex as seen by a camera on the z axis at `camera_distance` from the origin,
+    /// so that the vertices nearer to the camera get farther from the center. The vertices at
+    /// z = 0 stay where they are.
+    fn apply_perspective(&mut self, camera_distance: f32) {
+        let factor = camera_distance / (camera_distance - self.z());
+        self.coordinate[0] *= factor;
+        self.coordinate[1] *= factor;
+    }
+    fn project_to_img(&mut self, x_scale: f32, y_scale: f32, img_w: usize, img_h: usize) {
         self.coordinate[0] = self.coordinate[0] * x_scale + img_w as f32 / 2.0;
         self.coordinate[1] = -self.coordinate[1] * y_scale + img_h as f32 / 2.0;
     }
@@ -34,50 +49,20 @@ impl Vertex {
     }
 }
 
-struct BoundingBoxIterator {
-    x: usize,
-    y: usize,
-    min_x: usize,
-    max_x: usize,
-    max_y: usize,
-}
-impl BoundingBoxIterator {
-    fn new(min_x: usize, min_y: usize, max_x: usize, max_y: usize) -> BoundingBoxIterator {
-        BoundingBoxIterator {
-            x: min_x,
-            y: min_y,
-            min_x,
-            max_x,
-            max_y,
-        }
-    }
-}
-impl Iterator for BoundingBoxIterator {
-    type Item = (usize, usize);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.y <= self.max_y {
-            let (x, y) = (self.x, self.y);
-            self.x += 1;
-            if self.x > self.max_x {
-                self.x = self.min_x;
-                self.y += 1;
-            }
-            Some((x, y))
-        } else {
-            None
-        }
-    }
-}
-
 #[derive(Debug, Clone)]
 struct Quad {
     vertices: [Vertex; 4],
     color: Color,
+    /// The face, row and column of the sticker.
+    sticker: (FaceDir, usize, usize),
 }
 impl Quad {
-    fn new(vertices: [Vertex; 4], color: Color) -> Quad {
-        Quad { vertices, color }
+    fn new(vertices: [Vertex; 4], color: Color, sticker: (FaceDir, usize, usize)) -> Quad {
+        Quad {
+            vertices,
+            color,
+            sticker,
+        }
     }
 
     fn transform(&mut self, matrix: ArrayView2<f32>) {
@@ -86,13 +71,21 @@ impl Quad {
         }
     }
 
-    fn to_img_coordinates(&mut self, x_scale: f32, y_scale: f32, img_w: usize, img_h: usize) {
+    fn apply_perspective(&mut self, camera_distance: f32) {
         for vertex in self.vertices.iter_mut() {
-            vertex.to_img_coordinates(x_scale, y_scale, img_w, img_h);
+            vertex.apply_perspective(camera_distance);
         }
     }
 
-    fn iter_proj_bounding_box(&self) -> BoundingBoxIterator {
+    fn project_to_img(&mut self, x_scale: f32, y_scale: f32, img_w: usize, img_h: usize) {
+        for vertex in self.vertices.iter_mut() {
+            vertex.project_to_img(x_scale, y_scale, img_w, img_h);
+        }
+    }
+
+    /// Returns the smallest and the largest x and y of the pixels that the projected quad may
+    /// cover.
+    fn proj_bounds(&self) -> (usize, usize, usize, usize) {
         let (mut min_x, mut min_y) = (f32::INFINITY, f32::INFINITY);
         let (mut max_x, mut max_y) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
         for vertex in self.vertices.iter() {
@@ -102,7 +95,7 @@ impl Quad {
             max_y = f32::max(y, max_y);
             min_y = f32::min(y, min_y);
         }
-        BoundingBoxIterator::new(
+        (
             min_x as usize,
             min_y as usize,
             max_x as usize,
@@ -130,6 +123,64 @@ impl Quad {
         count % 2 == 1
     }
 
+    /// Returns the coordinate of the middle of the quad along `axis`.
+    fn get_center(&self, axis: CubeAxis) -> f32 {
+        let i = match axis {
+            CubeAxis::X => 0,
+            CubeAxis::Y => 1,
+            CubeAxis::Z => 2,
+        };
+        self.vertices.iter().map(|v| v.coordinate[i]).sum::<f32>() / 4.0
+    }
+
+    /// Checks if the front of the quad faces a camera looking down the z axis, at
+    /// `camera_distance` from the origin for a perspective camera, or infinitely far for an
+    /// orthographic one.
+    ///
+    /// The cube is convex, so the quads facing away from the camera are hidden by the others and
+    /// don't need to be drawn.
+    fn faces_camera(&self, camera_distance: Option<f32>) -> bool {
+        let to_camera = match camera_distance {
+            Some(camera_distance) => arr1(&[0.0, 0.0, camera_distance]) - self.center(),
+            None => arr1(&[0.0, 0.0, 1.0]),
+        };
+        // quads seen edge-on are skipped too, whatever the rounding errors of the rotations
+        self.outward_normal().dot(&to_camera) > 1e-4
+    }
+
+    /// Returns the brightness of the quad lit by `LIGHT_DIRECTION`, with Lambert's cosine law and
+    /// some ambient light so that the faces in the shadow are still visible.
+    fn brightness(&self) -> f32 {
+        let light_direction = arr1(&LIGHT_DIRECTION);
+        let light_direction = &light_direction / light_direction.dot(&light_direction).sqrt();
+        let diffuse = f32::max(0.0, self.outward_normal().dot(&light_direction));
+        AMBIENT_LIGHT + (1.0 - AMBIENT_LIGHT) * diffuse
+    }
+
+    /// Returns the unit normal of the quad that points out of the cube.
+    fn outward_normal(&self) -> Array1<f32> {
+        let [v0, v1, _, v3] = &self.vertices;
+        let normal = cross(
+            &(&v1.coordinate - &v0.coordinate),
+            &(&v3.coordinate - &v0.coordinate),
+        );
+        let normal = &normal / normal.dot(&normal).sqrt();
+        // the stickers face away from the middle of the cube, which stays true for the stickers
+        // of a turning layer since the layer rotates around the middle
+        if normal.dot(&self.center()) < 0.0 {
+            -normal
+        } else {
+            normal
+        }
+    }
+
+    fn center(&self) -> Array1<f32> {
+        self.vertices
+            .iter()
+            .fold(Array1::zeros(3), |sum, vertex| sum + &vertex.coordinate)
+            / 4.0
+    }
+
     /// Returns the -z value of the middle of the quad
     fn get_depth(&self) -> f32 {
         let (mut max_z, mut min_z) = (f32::NEG_INFINITY, f32::INFINITY);
@@ -140,35 +191,518 @@ impl Quad {
         }
         -(max_z + min_z) / 2.0 // the higher the z, the lower the depth, so flip the sign
     }
+
+    /// Returns the -z value of the quad at the projected point `x`, `y`, interpolated between its
+    /// vertices, so that the nearer of two quads that overlap in the image wins at every pixel,
+    /// and the position of the point on the quad, from `[0, 0]` at its first vertex to `[1, 1]`
+    /// at its third one.
+    ///
+    /// The quad is split into two triangles, and the values are interpolated with the
+    /// barycentric coordinates of the point in the one that contains it. Returns the values of
+    /// the middle of the quad if the point isn't in it.
+    fn interpolate_at(&self, x: f32, y: f32) -> (f32, [f32; 2]) {
+        for [a, b, c] in [[0, 1, 2], [0, 2, 3]] {
+            let (xa, ya) = self.vertices[a].get_proj();
+            let (xb, yb) = self.vertices[b].get_proj();
+            let (xc, yc) = self.vertices[c].get_proj();
+            let area = (xb - xa) * (yc - ya) - (xc - xa) * (yb - ya);
+            if area.abs() < f32::EPSILON {
+                continue;
+            }
+            let wb = ((x - xa) * (yc - ya) - (xc - xa) * (y - ya)) / area;
+            let wc = ((xb - xa) * (y - ya) - (x - xa) * (yb - ya)) / area;
+            let wa = 1.0 - wb - wc;
+            if wa >= 0.0 && wb >= 0.0 && wc >= 0.0 {
+                let z = wa * self.vertices[a].z()
+                    + wb * self.vertices[b].z()
+                    + wc * self.vertices[c].z();
+                // position of the vertices on the quad
+                let uv = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+                let [u, v] = [0, 1].map(|i| wa * uv[a][i] + wb * uv[b][i] + wc * uv[c][i]);
+                return (-z, [u, v]);
+            }
+        }
+        (self.get_depth(), [0.5, 0.5])
+    }
+}
+/// Direction the light comes from, in the coordinates of the view: from the top left, in front of
+/// the cube.
+const LIGHT_DIRECTION: [f32; 3] = [-0.4, 0.6, 1.0];
+/// Brightness of the faces that the light doesn't reach.
+const AMBIENT_LIGHT: f32 = 0.45;
+/// Width of the borders between the stickers, as a fraction of a sticker.
+const BORDER_WIDTH: f32 = 0.1;
+/// Factor of the brightness of the stickers that aren't highlighted, see
+/// `CubeRender::set_highlight` and `CubeRender::set_show_unsolved`.
+const DIM_BRIGHTNESS: f32 = 0.35;
+/// Number of columns between two renders drawn side by side, see `render_side_by_side`.
+const SIDE_BY_SIDE_GAP: usize = 2;
+/// Fraction of the half of the image that the cube reaches with `ImageFit::Cube`, and the visible
+/// stickers with `ImageFit::VisibleFaces`.
+const CUBE_FILL: f32 = 0.6;
+const VISIBLE_FACES_FILL: f32 = 0.9;
+
+/// A pixel of the 3D render.
+#[derive(Debug, Clone, Copy)]
+enum Pixel {
+    /// A sticker of this color, shaded with this brightness.
+    Sticker(Color, f32),
+    /// The border around a sticker.
+    Border,
+}
+impl Pixel {
+    /// Returns the escape code that sets the foreground or the background color to the pixel.
+    fn code(&self, color_mode: ColorMode, background: bool) -> String {
+        match self {
+            Pixel::Sticker(color, brightness) => {
+                color_mode.shaded_code(*color, *brightness, background)
+            }
+            Pixel::Border => color_mode.border_code(background),
+        }
+    }
+}
+
+/// How big the cube is drawn in the 3D render.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFit {
+    /// As many characters per sticker as given to `CubeRender::new`, whatever the size of the
+    /// cube.
+    Fixed,
+    /// As big as the image allows for the whole cube in any orientation, so bigger cubes get
+    /// smaller stickers.
+    Cube,
+    /// As big as the image allows for the stickers facing the camera, e.g. the three faces seen
+    /// from a corner, so they're more legible on bigger cubes.
+    VisibleFaces,
+}
+
+/// How the pixels of the 3D render are drawn with characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelStyle {
+    /// Two pixels per character, as half blocks in their colors.
+    HalfBlocks,
+    /// 2x4 pixels per character, as the dots of a braille pattern, for finer edges on small
+    /// terminals. A character shows only one color, so the dots outline the borders in the
+    /// middle of the stickers, and the edges of the cube in the color of its stickers.
+    Braille,
+}
+
+/// What `CubeRender` draws.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderMode {
+    /// The cube in 3D, seen from the orientation of the view.
+    View,
+    /// The unfolded cube, laid out like its `Display`, with the name of every face.
+    Net,
+}
+
+/// A character of the net, see `CubeRender::render_net_lines`.
+#[derive(Debug, Clone, Copy)]
+enum NetCell {
+    /// The name of the face below, or a part of an arrow between the faces.
+    Name(char),
+    /// A sticker of this color, with this brightness.
+    Sticker(Color, f32),
+    /// A letter or a part of an arrow on a sticker of this color and brightness.
+    Letter(char, Color, f32),
+}
+
+/// Preset orientations of the view, see `CubeRender::set_view`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraView {
+    /// Straight at the front face.
+    Front,
+    /// At the up, front and right corner, with the three faces equally visible.
+    Corner,
+    /// Straight at the up face, with the front face at the bottom.
+    Top,
 }
+
+/// A 3D render of a cube, drawn with characters in the terminal (`render_lines`), as SVG
+/// (`render_svg`), or as projected stickers for another renderer (`projected_stickers`).
+#[derive(Clone)]
 pub struct CubeRender {
-    pitch: f32,
-    yaw: f32,
+    mode: RenderMode,
+    /// Rotation from the cube to the view, made of every pitch, yaw and roll so far, so the view
+    /// stays the same when the quads are rebuilt.
+    view_matrix: Array2<f32>,
+    /// Number of columns and rows of the image per unit with `ImageFit::Fixed`. Otherwise, only
+    /// their ratio is used, for the shape of the characters.
     x_scale: f32,
     y_scale: f32,
+    fit: ImageFit,
+    /// Factor of `x_scale` and `y_scale`.
+    zoom: f32,
+    /// Vertical field of view of the perspective camera, or `None` for an orthographic projection.
+    fov: Option<f32>,
     img_w: usize,
     img_h: usize,
+    /// The quads drawn, rotated by the view.
     quads: Vec<Quad>,
+    /// The quads of `cube` before rotating the view, kept so that `update_colors` only has to
+    /// recolor them when the size of the cube stays the same.
+    model_quads: Vec<Quad>,
+    /// Whether `quads` are `model_quads` rotated by the view, and not halfway through a turn.
+    quads_in_view: bool,
+    /// The cube drawn, kept for the net.
+    cube: Cube,
+    /// The turn whose stickers are highlighted.
+    highlight: Option<Turn>,
+    /// Whether each facelet (as in `Cube::facelet_codes`) differs from the solved cube, if the
+    /// unsolved stickers are highlighted.
+    unsolved: Option<Vec<bool>>,
+    /// The only facelet highlighted, see `set_cursor`.
+    cursor: Option<usize>,
+    /// Whether the visible faces are labeled with their names, with arrows of the axes.
+    labels: bool,
+    /// Whether the stickers show their Speffz letters, see `Cube::speffz_letter`.
+    letters: bool,
+    pixel_style: PixelStyle,
+    /// The pieces drawn with arrows on the net, see `set_arrows`.
+    arrows: Vec<(usize, usize)>,
 }
 impl CubeRender {
-    const INIT_PITCH: f32 = 0.0;
-    const INIT_YAW: f32 = 0.0;
+    /// Range of the field of view of the perspective camera. The camera gets nearer to the cube
+    /// as the field of view widens, and past the maximum the near stickers get too distorted.
+    pub const MIN_FOV: f32 = 10.0 * std::f32::consts::PI / 180.0;
+    /// See `MIN_FOV`.
+    pub const MAX_FOV: f32 = 60.0 * std::f32::consts::PI / 180.0;
+
+    /// Creates the render of `cube` on an image of `img_w` columns and `img_h` rows, with
+    /// `x_scale` columns and `y_scale` rows per unit (see `ImageFit::Fixed`).
     pub fn new(cube: &Cube, x_scale: f32, y_scale: f32, img_w: usize, img_h: usize) -> CubeRender {
         let mut new_cr = CubeRender {
-            pitch: CubeRender::INIT_PITCH,
-            yaw: CubeRender::INIT_YAW,
+            mode: RenderMode::View,
+            view_matrix: Array2::eye(3),
             x_scale,
             y_scale,
+            fit: ImageFit::Fixed,
+            zoom: 1.0,
+            fov: None,
             img_w,
             img_h,
             quads: Vec::new(),
+            model_quads: Vec::new(),
+            quads_in_view: false,
+            cube: cube.clone(),
+            highlight: None,
+            unsolved: None,
+            cursor: None,
+            labels: false,
+            letters: false,
+            pixel_style: PixelStyle::HalfBlocks,
+            arrows: Vec::new(),
         };
         new_cr.update_colors(cube);
         new_cr
     }
 
+    /// Draws `cube` instead of the cube drawn so far, keeping the view.
     pub fn update_colors(&mut self, cube: &Cube) {
-        self.quads = Vec::with_capacity(cube.size.pow(3) * 6);
+        if cube.size != self.cube.size {
+            self.arrows.clear();
+        }
+        // the stickers stay where they are, only their colors change
+        if cube.size != self.cube.size || self.model_quads.is_empty() {
+            self.model_quads = CubeRender::cube_quads(cube);
+            self.quads_in_view = false;
+        } else {
+            CubeRender::recolor(&mut self.model_quads, cube);
+        }
+        if self.quads_in_view {
+            CubeRender::recolor(&mut self.quads, cube);
+        } else {
+            self.reset_quads();
+        }
+        self.cube = cube.clone();
+        if self.unsolved.is_some() {
+            self.unsolved = Some(CubeRender::unsolved_flags(cube));
+        }
+    }
+
+    /// Returns whether each facelet of `cube` differs from the nearest solved cube.
+    fn unsolved_flags(cube: &Cube) -> Vec<bool> {
+        let mut flags = vec![false; 6 * cube.size * cube.size];
+        for i in cube.unsolved_facelets() {
+            flags[i] = true;
+        }
+        flags
+    }
+
+    /// Returns what is drawn.
+    pub fn mode(&self) -> RenderMode {
+        self.mode
+    }
+
+    /// Multiplies the size of the cube in the image by `factor`.
+    pub fn zoom(&mut self, factor: f32) {
+        self.zoom *= factor;
+    }
+
+    /// Returns how big the cube is drawn.
+    pub fn fit(&self) -> ImageFit {
+        self.fit
+    }
+
+    /// Changes how big the cube is drawn, before zooming.
+    pub fn set_fit(&mut self, fit: ImageFit) {
+        self.fit = fit;
+    }
+
+    /// Returns the number of columns and rows of the image per unit, which is the size of a
+    /// sticker, with the fit and the zoom.
+    fn image_scale(&self) -> (f32, f32) {
+        let ratio = self.x_scale / self.y_scale;
+        // the distance from the middle of the image that has to fit, horizontally and vertically
+        let (extent_x, extent_y, fill) = match self.fit {
+            ImageFit::Fixed => return (self.zoom * self.x_scale, self.zoom * self.y_scale),
+            ImageFit::Cube => (self.radius(), self.radius(), CUBE_FILL),
+            ImageFit::VisibleFaces => {
+                let camera_distance = self.camera_distance();
+                let (x, y) = self
+                    .quads
+                    .iter()
+                    .filter(|quad| quad.faces_camera(camera_distance))
+                    .flat_map(|quad| quad.vertices.iter())
+                    .fold((0.0, 0.0), |(x, y): (f32, f32), vertex| {
+                        let mut vertex = vertex.clone();
+                        if let Some(camera_distance) = camera_distance {
+                            vertex.apply_perspective(camera_distance);
+                        }
+                        let (vx, vy) = vertex.get_proj();
+                        (x.max(vx.abs()), y.max(vy.abs()))
+                    });
+                (x, y, VISIBLE_FACES_FILL)
+            }
+        };
+        let y_scale = f32::min(
+            fill * self.img_h as f32 / 2.0 / extent_y,
+            fill * self.img_w as f32 / 2.0 / (extent_x * ratio),
+        );
+        (self.zoom * ratio * y_scale, self.zoom * y_scale)
+    }
+
+    /// Returns the field of view of the perspective camera, or `None` if the projection is
+    /// orthographic.
+    pub fn perspective(&self) -> Option<f32> {
+        self.fov
+    }
+
+    /// Uses a perspective camera with the field of view `fov` (in radians, clamped between
+    /// `MIN_FOV` and `MAX_FOV`), or an orthographic projection if `fov` is `None`.
+    ///
+    /// The camera is placed so that the cube fills the field of view, and the middle of the cube
+    /// has the same size as with the orthographic projection: the wider the field of view, the
+    /// nearer the camera and the more the near stickers are enlarged.
+    pub fn set_perspective(&mut self, fov: Option<f32>) {
+        self.fov = fov.map(|fov| fov.clamp(CubeRender::MIN_FOV, CubeRender::MAX_FOV));
+    }
+
+    /// Returns the distance of the perspective camera from the middle of the cube, if there is
+    /// one.
+    fn camera_distance(&self) -> Option<f32> {
+        self.fov.map(|fov| self.radius() / (fov / 2.0).tan())
+    }
+
+    /// Returns the distance of the corners of the cube from its middle.
+    fn radius(&self) -> f32 {
+        self.quads
+            .iter()
+            .flat_map(|quad| quad.vertices.iter())
+            .map(|vertex| vertex.coordinate.dot(&vertex.coordinate).sqrt())
+            .fold(0.0, f32::max)
+    }
+
+    /// Highlights the stickers that `turn` moves, by dimming all the others, or stops
+    /// highlighting if `turn` is `None`.
+    pub fn set_highlight(&mut self, turn: Option<Turn>) {
+        self.highlight = turn;
+    }
+
+    /// Draws arrows on the net from where each piece that `algo` moves is to where it goes (see
+    /// `Turn::piece_moves`), e.g. to show the 3 pieces that a commutator cycles. An empty `algo`
+    /// removes the arrows.
+    pub fn set_arrows(&mut self, algo: &[Turn]) {
+        self.arrows = Turn::piece_moves(algo, self.cube.size);
+    }
+
+    /// Highlights only the facelet at index `facelet` (as in `Cube::facelet_codes`) by dimming
+    /// all the others, like a cursor, or stops if it's `None`. A highlighted turn is shown instead
+    /// while there is one, and the cursor is shown instead of the unsolved stickers.
+    pub fn set_cursor(&mut self, facelet: Option<usize>) {
+        self.cursor = facelet;
+    }
+
+    /// Returns whether the stickers that differ from the solved cube are highlighted.
+    pub fn show_unsolved(&self) -> bool {
+        self.unsolved.is_some()
+    }
+
+    /// Highlights the stickers that differ from the solved cube, in the orientation nearest to
+    /// the cube, by dimming the ones that are already solved. A highlighted turn (see
+    /// `set_highlight`) is shown instead while there is one.
+    pub fn set_show_unsolved(&mut self, show: bool) {
+        self.unsolved = show.then(|| CubeRender::unsolved_flags(&self.cube));
+    }
+
+    /// Returns the letter shown in the middle of the sticker of `color` at `row`, `col` of the
+    /// face at `face_dir`: its Speffz letter if they're shown, or the letter of its color
+    /// without colors.
+    fn sticker_letter(
+        &self,
+        color: Color,
+        face_dir: &FaceDir,
+        row: usize,
+        col: usize,
+    ) -> Option<char> {
+        if self.letters {
+            Cube::speffz_letter(self.cube.size, face_dir, row, col)
+        } else if ColorMode::current() == ColorMode::NoColor {
+            Some(color.letter())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the factor of the brightness of the sticker at `row`, `col` of the face at
+    /// `face_dir`, which dims the stickers that aren't highlighted.
+    fn sticker_brightness(&self, face_dir: &FaceDir, row: usize, col: usize) -> f32 {
+        let size = self.cube.size;
+        let facelet = FaceDir::facelet_index(*face_dir) * size * size + row * size + col;
+        let highlighted = match (&self.highlight, self.cursor, &self.unsolved) {
+            (Some(turn), _, _) => turn.moves_sticker(size, face_dir, row, col),
+            (None, Some(cursor), _) => facelet == cursor,
+            (None, None, Some(unsolved)) => unsolved[facelet],
+            (None, None, None) => true,
+        };
+        if highlighted {
+            1.0
+        } else {
+            DIM_BRIGHTNESS
+        }
+    }
+
+    /// Returns whether the faces and the axes are labeled.
+    pub fn labels(&self) -> bool {
+        self.labels
+    }
+
+    /// Labels the visible faces of the 3D render with their names, and shows in the bottom left
+    /// corner the arrows of the directions that the R, U and F faces are in, so that it's clear
+    /// which face each command turns in the current view.
+    pub fn set_labels(&mut self, labels: bool) {
+        self.labels = labels;
+    }
+
+    /// Returns whether the stickers show their letters.
+    pub fn letters(&self) -> bool {
+        self.letters
+    }
+
+    /// Shows the Speffz letter of every sticker that has one (see `Cube::speffz_letter`), to
+    /// practice the memorization of blindfold solving.
+    pub fn set_letters(&mut self, letters: bool) {
+        self.letters = letters;
+    }
+
+    /// Returns how the pixels of the 3D render are drawn.
+    pub fn pixel_style(&self) -> PixelStyle {
+        self.pixel_style
+    }
+
+    /// Changes how the pixels of the 3D render are drawn.
+    pub fn set_pixel_style(&mut self, pixel_style: PixelStyle) {
+        self.pixel_style = pixel_style;
+    }
+
+    /// Changes the size of the image, in characters. With `ImageFit::Fixed`, the cube keeps its
+    /// size, so it may need to be zoomed to fit.
+    pub fn set_image_size(&mut self, img_w: usize, img_h: usize) {
+        (self.img_w, self.img_h) = (img_w, img_h);
+    }
+
+    /// Changes what is drawn.
+    pub fn set_mode(&mut self, mode: RenderMode) {
+        self.mode = mode;
+    }
+
+    /// Shows `turn` being applied to `cube` over `frames` frames, calling `draw_frame` after
+    /// each of them, instead of snapping to the turned cube.
+    ///
+    /// The turned layer rotates around the axis of the turn, easing in and out. The last frame
+    /// shows `cube` after the turn, with its colors updated, so `update_colors` doesn't need to be
+    /// called afterwards.
+    pub fn animate_turn(
+        &mut self,
+        cube: &Cube,
+        turn: &Turn,
+        frames: usize,
+        mut draw_frame: impl FnMut(&CubeRender),
+    ) {
+        // the net can't show a layer halfway through a turn
+        let frames = match self.mode {
+            RenderMode::View => frames,
+            RenderMode::Net => 0,
+        };
+        self.update_colors(cube);
+        for frame in 1..frames {
+            // smoothstep, so the layer starts and stops slowly
+            let t = frame as f32 / frames as f32;
+            self.quads = self.model_quads.clone();
+            CubeRender::turn_layer(&mut self.quads, cube.size, turn, t * t * (3.0 - 2.0 * t));
+            for square in self.quads.iter_mut() {
+                square.transform(self.view_matrix.view());
+            }
+            self.quads_in_view = false;
+            draw_frame(self);
+        }
+
+        let mut turned_cube = cube.clone();
+        turned_cube.apply_turn(turn);
+        self.update_colors(&turned_cube);
+        draw_frame(self);
+    }
+
+    /// Returns the quads of `cube` (see `cube_quads`) with the layer of `turn` rotated by the
+    /// fraction `progress` of the turn.
+    fn turning_quads(cube: &Cube, turn: &Turn, progress: f32) -> Vec<Quad> {
+        let mut quads = CubeRender::cube_quads(cube);
+        CubeRender::turn_layer(&mut quads, cube.size, turn, progress);
+        quads
+    }
+
+    /// Rotates the quads of the layer of `turn` of a cube of size `size`, before rotating the
+    /// view, by the fraction `progress` of the turn.
+    fn turn_layer(quads: &mut [Quad], size: usize, turn: &Turn, progress: f32) {
+        let face_dir = turn.get_face_dir();
+        let axis = face_dir.get_axis();
+        let sign = if face_dir.is_positive() { 1.0 } else { -1.0 };
+        // a clockwise turn is a clockwise rotation when looking at the turned face, which is
+        // negative around the axis if the face is on its positive side
+        let quarter = -sign * std::f32::consts::FRAC_PI_2;
+        let angle = match turn.get_turn_dir() {
+            TurnDir::Clockwise => quarter,
+            TurnDir::CounterClockwise => -quarter,
+            TurnDir::Half => 2.0 * quarter,
+        };
+        let rotation_matrix = CubeRender::axis_matrix(axis, angle * progress);
+
+        // the turned layer spans one unit along the axis, `layer` units in from the turned face
+        let outer = sign * size as f32 / 2.0;
+        let inner = outer - sign * turn.get_layer() as f32;
+        let layer_range = f32::min(inner, inner + sign)..=f32::max(inner, inner + sign);
+
+        for quad in quads.iter_mut() {
+            if layer_range.contains(&quad.get_center(axis)) {
+                quad.transform(rotation_matrix.view());
+            }
+        }
+    }
+
+    /// Returns the quads of the stickers of `cube`, centered at the origin, before rotating the
+    /// view.
+    fn cube_quads(cube: &Cube) -> Vec<Quad> {
+        let mut quads = Vec::with_capacity(cube.size.pow(2) * 6);
         for face_dir in cube.dir_order.iter() {
             for ((y, x), color) in cube.get_face(face_dir).colors.indexed_iter() {
                 // calculate the first coordinate of the quad
@@ -213,65 +747,502 @@ impl CubeRender {
                 vertices[3].translate(second_axis, second_axis_amt);
 
                 // add new square to the array of quads
-                self.quads.push(Quad::new(vertices, *color));
+                quads.push(Quad::new(vertices, *color, (*face_dir, y, x)));
             }
         }
-        let pitch_matrix = CubeRender::pitch_matrix(self.pitch);
-        let yaw_matrix = CubeRender::yaw_matrix(self.yaw);
-        let rotation_matrix = pitch_matrix.dot(&yaw_matrix);
+        quads
+    }
+
+    /// Sets the color of each quad to the color of its sticker in `cube`.
+    fn recolor(quads: &mut [Quad], cube: &Cube) {
+        for quad in quads.iter_mut() {
+            let (face_dir, row, col) = quad.sticker;
+            quad.color = cube.get_face(&face_dir).colors[[row, col]];
+        }
+    }
+
+    /// Rebuilds `quads` from `model_quads` rotated by the view.
+    fn reset_quads(&mut self) {
+        self.quads = self.model_quads.clone();
         for square in self.quads.iter_mut() {
-            square.transform(rotation_matrix.view());
+            square.transform(self.view_matrix.view());
         }
+        self.quads_in_view = true;
     }
 
+    /// Prints the lines of `render_lines`.
     pub fn render_cube(&self) {
-        // create img structures
-        let mut img_arr = Array::from_elem((self.img_h, self.img_w), None);
-        //stores the depth of the pixel, to avoid squares from behind being drawn on top
-        let mut img_depth = Array::from_elem((self.img_h, self.img_w), f32::INFINITY);
+        for line in self.render_lines() {
+            println!("{line}");
+        }
+    }
 
-        // render each square to `img_arr`
-        for square in self.quads.iter() {
+    /// Renders the cube to one string per row of the image, so it can be drawn anywhere on the
+    /// screen.
+    ///
+    /// Every character shows several pixels, see `PixelStyle`. With half blocks, the upper half
+    /// block is in the color of the top pixel and the background in the color of the bottom one,
+    /// which doubles the vertical resolution.
+    pub fn render_lines(&self) -> Vec<String> {
+        if self.mode == RenderMode::Net {
+            return self.render_net_lines();
+        }
+
+        // create img structures, with `cell_w` by `cell_h` pixels per character
+        let (cell_w, cell_h) = match self.pixel_style {
+            PixelStyle::HalfBlocks => (1, 2),
+            PixelStyle::Braille => (2, 4),
+        };
+        let (img_w, img_h) = (cell_w * self.img_w, cell_h * self.img_h);
+        // the squares facing the camera projected to the image, and the letters with the pixel
+        // of the middle of their squares
+        let mut img_squares = Vec::new();
+        let mut letters = Vec::new();
+
+        let (x_scale, y_scale) = self.image_scale();
+        let camera_distance = self.camera_distance();
+
+        // project each square facing the camera to the image
+        for (i, square) in self.quads.iter().enumerate() {
+            if !square.faces_camera(camera_distance) {
+                continue;
+            }
+            let (face_dir, row, col) = square.sticker;
+            let brightness = square.brightness() * self.sticker_brightness(&face_dir, row, col);
             let mut img_square = square.clone();
-            img_square.to_img_coordinates(self.x_scale, self.y_scale, self.img_w, self.img_h);
-            for (x, y) in img_square.iter_proj_bounding_box() {
-                if x >= self.img_w || y >= self.img_h {
-                    continue;
-                }
-                if img_square.is_point_in_proj(x as f32, y as f32) {
-                    let depth = square.get_depth();
-                    if img_depth[[y, x]] > depth {
-                        img_arr[[y, x]] = Some(square.color);
-                        img_depth[[y, x]] = depth;
+            if let Some(camera_distance) = camera_distance {
+                img_square.apply_perspective(camera_distance);
+            }
+            img_square.project_to_img(
+                cell_w as f32 * x_scale,
+                cell_h as f32 * y_scale,
+                img_w,
+                img_h,
+            );
+            if let Some(letter) = self.sticker_letter(square.color, &face_dir, row, col) {
+                let (x, y) = img_square
+                    .vertices
+                    .iter()
+                    .fold((0.0, 0.0), |(x, y), vertex| {
+                        let (vx, vy) = vertex.get_proj();
+                        (x + vx / 4.0, y + vy / 4.0)
+                    });
+                letters.push((i, letter, x, y));
+            }
+            img_squares.push((i, img_square, square.color, brightness));
+        }
+
+        // the rows of pixels are drawn in parallel, with the index of the square drawn at each
+        // pixel
+        let rows: Vec<Vec<Option<(Pixel, usize)>>> = (0..img_h)
+            .into_par_iter()
+            .map(|y| {
+                let mut row = vec![None; img_w];
+                //stores the depth of the pixel, to avoid squares from behind being drawn on top
+                let mut row_depth = vec![f32::INFINITY; img_w];
+                for (i, img_square, color, brightness) in img_squares.iter() {
+                    let (min_x, min_y, max_x, max_y) = img_square.proj_bounds();
+                    if !(min_y..=max_y).contains(&y) {
+                        continue;
+                    }
+                    for x in min_x..usize::min(max_x + 1, img_w) {
+                        if !img_square.is_point_in_proj(x as f32, y as f32) {
+                            continue;
+                        }
+                        let (depth, uv) = img_square.interpolate_at(x as f32, y as f32);
+                        if row_depth[x] > depth {
+                            let on_border = uv
+                                .iter()
+                                .any(|c| !(BORDER_WIDTH..=1.0 - BORDER_WIDTH).contains(c));
+                            let pixel = if on_border {
+                                Pixel::Border
+                            } else {
+                                Pixel::Sticker(*color, *brightness)
+                            };
+                            row[x] = Some((pixel, *i));
+                            row_depth[x] = depth;
+                        }
                     }
                 }
+                row
+            })
+            .collect();
+        let img_arr = Array::from_shape_fn((img_h, img_w), |(y, x)| rows[y][x].map(|(p, _)| p));
+        let img_square_index =
+            Array::from_shape_fn((img_h, img_w), |(y, x)| rows[y][x].map(|(_, i)| i));
+
+        // the letters are only shown on the squares that aren't covered at their middle, and
+        // the names of the faces are shown over them
+        let mut labels = self.label_cells();
+        for (i, letter, x, y) in letters {
+            let (x, y) = (x.floor(), y.floor());
+            if !(0.0..img_w as f32).contains(&x) || !(0.0..img_h as f32).contains(&y) {
+                continue;
+            }
+            let (x, y) = (x as usize, y as usize);
+            let (row, col) = (y / cell_h, x / cell_w);
+            if img_square_index[[y, x]] == Some(i) && labels[[row, col]].is_none() {
+                labels[[row, col]] = Some(letter);
             }
         }
 
-        for row in img_arr.outer_iter() {
-            for e in row.iter() {
-                match e {
-                    None => print!(" "),
-                    Some(color) => print!("{}", color),
+        let color_mode = ColorMode::current();
+        (0..self.img_h)
+            .into_par_iter()
+            .map(|row| {
+                (0..self.img_w)
+                    .map(|col| {
+                        let pixels = img_arr.slice(s![
+                            row * cell_h..(row + 1) * cell_h,
+                            col * cell_w..(col + 1) * cell_w
+                        ]);
+                        if color_mode == ColorMode::NoColor {
+                            let c = labels[[row, col]].map_or_else(|| ascii_cell(pixels), ascii);
+                            return c.to_string();
+                        }
+                        match (labels[[row, col]], pixels.iter().flatten().next()) {
+                            // the labels are black on the sticker under them, or white on a
+                            // border
+                            (Some(label), Some(pixel)) => {
+                                let foreground = match pixel {
+                                    Pixel::Sticker(..) => 30,
+                                    Pixel::Border => 97,
+                                };
+                                format!(
+                                    "{}\x1b[{foreground}m{label}\x1b[0m",
+                                    pixel.code(color_mode, true)
+                                )
+                            }
+                            (Some(label), None) => label.to_string(),
+                            (None, _) => match self.pixel_style {
+                                PixelStyle::HalfBlocks => {
+                                    half_block_cell(pixels[[0, 0]], pixels[[1, 0]], color_mode)
+                                }
+                                PixelStyle::Braille => braille_cell(pixels, color_mode),
+                            },
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the characters of the labels of the 3D render (see `set_labels`), by row and
+    /// column of the image.
+    fn label_cells(&self) -> Array2<Option<char>> {
+        let mut cells = Array::from_elem((self.img_h, self.img_w), None);
+        if !self.labels {
+            return cells;
+        }
+
+        let (x_scale, y_scale) = self.image_scale();
+        let camera_distance = self.camera_distance();
+        let half_cube = self.cube.size as f32 / 2.0;
+        for face_dir in FaceDir::FACELET_ORDER {
+            let normal = self.view_matrix.dot(&face_normal(face_dir));
+            let center = &normal * half_cube;
+            let to_camera = match camera_distance {
+                Some(camera_distance) => arr1(&[0.0, 0.0, camera_distance]) - &center,
+                None => arr1(&[0.0, 0.0, 1.0]),
+            };
+            // the faces seen nearly edge-on have no room for a label
+            if normal.dot(&to_camera) / to_camera.dot(&to_camera).sqrt() < 0.1 {
+                continue;
+            }
+            let mut vertex = Vertex::new(center[0], center[1], center[2]);
+            if let Some(camera_distance) = camera_distance {
+                vertex.apply_perspective(camera_distance);
+            }
+            vertex.project_to_img(x_scale, y_scale, self.img_w, self.img_h);
+            let (x, y) = vertex.get_proj();
+            let (col, row) = (x.floor(), y.floor());
+            if (0.0..self.img_w as f32).contains(&col) && (0.0..self.img_h as f32).contains(&row) {
+                let name = face_dir.to_string().chars().next().unwrap();
+                cells[[row as usize, col as usize]] = Some(name);
+            }
+        }
+
+        // the arrows of the axes, like "R→ U↑ F⊙"
+        let arrows = [FaceDir::Right, FaceDir::Up, FaceDir::Front]
+            .iter()
+            .map(|face_dir| {
+                let name = face_dir.to_string().chars().next().unwrap();
+                let direction = self.view_matrix.dot(&face_normal(*face_dir));
+                format!(
+                    "{name}{}",
+                    axis_arrow(direction[0], direction[1], direction[2])
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(" ");
+        if let Some(mut row) = cells.outer_iter_mut().last() {
+            for (cell, c) in row.iter_mut().zip(arrows.chars()) {
+                *cell = Some(c);
+            }
+        }
+        cells
+    }
+
+    /// Renders the unfolded cube, with each sticker as a block of spaces in its color and the
+    /// name of each face above it. The blocks are as big as the image allows, twice as wide as
+    /// they're tall so that they look square.
+    fn render_net_lines(&self) -> Vec<String> {
+        let size = self.cube.size;
+        // a column and a row between the faces, and a row for the names of the faces
+        let block_h = usize::max(
+            1,
+            usize::min(
+                self.img_w.saturating_sub(3) / (8 * size),
+                self.img_h.saturating_sub(5) / (3 * size),
+            ),
+        );
+        let (block_w, face_w, face_h) = (2 * block_h, 2 * block_h * size, block_h * size);
+        let (net_w, net_h) = (4 * face_w + 3, 3 * face_h + 5);
+        let (left, top) = (
+            self.img_w.saturating_sub(net_w) / 2,
+            self.img_h.saturating_sub(net_h) / 2,
+        );
+
+        let (img_w, img_h) = (
+            usize::max(self.img_w, left + net_w),
+            usize::max(self.img_h, top + net_h),
+        );
+        let mut img_arr = Array::from_elem((img_h, img_w), None);
+        // the middle of the block of each facelet, where the arrows start and end
+        let mut centers = vec![(0, 0); 6 * size * size];
+        for (i, face_dir) in [
+            FaceDir::Up,
+            FaceDir::Left,
+            FaceDir::Front,
+            FaceDir::Right,
+            FaceDir::Back,
+            FaceDir::Down,
+        ]
+        .iter()
+        .enumerate()
+        {
+            // the faces are at the same position as in the `Display` of the cube
+            let (net_x, net_y) = match i {
+                0 => (1, 0),
+                5 => (1, 2),
+                _ => (i - 1, 1),
+            };
+            let face_x = left + net_x * (face_w + 1);
+            let face_y = top + net_y * (face_h + 2);
+            let name = face_dir.to_string().chars().next().unwrap();
+            img_arr[[face_y, face_x]] = Some(NetCell::Name(name));
+            for ((y, x), color) in self.cube.get_face(face_dir).colors.indexed_iter() {
+                let facelet = FaceDir::facelet_index(*face_dir) * size * size + y * size + x;
+                centers[facelet] = (
+                    face_x + x * block_w + block_w / 2,
+                    face_y + 1 + y * block_h + block_h / 2,
+                );
+                let brightness = self.sticker_brightness(face_dir, y, x);
+                let letter = self.sticker_letter(*color, face_dir, y, x);
+                for dy in 0..block_h {
+                    for dx in 0..block_w {
+                        let (px, py) = (face_x + x * block_w + dx, face_y + 1 + y * block_h + dy);
+                        // the letter is in the middle of the block
+                        img_arr[[py, px]] = Some(match letter {
+                            Some(letter) if (dx, dy) == (block_w / 2, block_h / 2) => {
+                                NetCell::Letter(letter, *color, brightness)
+                            }
+                            _ => NetCell::Sticker(*color, brightness),
+                        });
+                    }
                 }
             }
-            println!();
         }
+
+        for &(from, to) in &self.arrows {
+            let ((x0, y0), (x1, y1)) = (centers[from], centers[to]);
+            let (dx, dy) = (x1 as f32 - x0 as f32, y1 as f32 - y0 as f32);
+            // a character is about twice as tall as it's wide
+            let (line, head) = line_char(dx, -2.0 * dy);
+            let steps = f32::max(dx.abs(), dy.abs()) as usize;
+            for step in 1..=steps {
+                let t = step as f32 / steps as f32;
+                let px = (x0 as f32 + t * dx).round() as usize;
+                let py = (y0 as f32 + t * dy).round() as usize;
+                let c = if step == steps { head } else { line };
+                img_arr[[py, px]] = Some(match img_arr[[py, px]] {
+                    Some(
+                        NetCell::Sticker(color, brightness) | NetCell::Letter(_, color, brightness),
+                    ) => NetCell::Letter(c, color, brightness),
+                    _ => NetCell::Name(c),
+                });
+            }
+        }
+
+        let color_mode = ColorMode::current();
+        img_arr
+            .outer_iter()
+            .map(|row| {
+                row.iter()
+                    .map(|e| match e {
+                        None => " ".to_string(),
+                        Some(NetCell::Name(c) | NetCell::Letter(c, ..))
+                            if color_mode == ColorMode::NoColor =>
+                        {
+                            ascii(*c).to_string()
+                        }
+                        Some(NetCell::Sticker(color, _)) if color_mode == ColorMode::NoColor => {
+                            color.fill_char().to_string()
+                        }
+                        Some(NetCell::Name(name)) => name.to_string(),
+                        Some(NetCell::Letter(letter, color, brightness)) => format!(
+                            "{}\x1b[30m{letter}\x1b[0m",
+                            color_mode.shaded_code(*color, *brightness, true)
+                        ),
+                        Some(NetCell::Sticker(color, brightness)) => {
+                            format!(
+                                "{} \x1b[0m",
+                                color_mode.shaded_code(*color, *brightness, true)
+                            )
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the view of the cube as an SVG image, with `sticker_size` pixels per sticker.
+    ///
+    /// The quads are drawn from the farthest to the nearest, so the nearer stickers cover the ones
+    /// behind them.
+    pub fn render_svg(&self, sticker_size: f32) -> String {
+        let (img_size, stickers) = self.projected_stickers(sticker_size);
+        let polygons: Vec<String> = stickers
+            .iter()
+            .map(|(corners, (r, g, b))| {
+                let points: Vec<String> = corners
+                    .iter()
+                    .map(|(x, y)| format!("{x:.2},{y:.2}"))
+                    .collect();
+                format!(
+                    r##"<polygon points="{}" fill="#{r:02x}{g:02x}{b:02x}"/>"##,
+                    points.join(" ")
+                )
+            })
+            .collect();
+        svg(img_size, img_size, sticker_size, &polygons)
+    }
+
+    /// Returns the size of a square image that fits the view of the cube with `sticker_size`
+    /// pixels per sticker, and the corners of the stickers facing the camera in that image with
+    /// the RGB values of their colors, from the farthest to the nearest, so that they can be
+    /// drawn in that order by any 2D renderer.
+    pub fn projected_stickers(&self, sticker_size: f32) -> (f32, Vec<ProjectedSticker>) {
+        // the image fits the cube in every view, since the corners never get farther than the
+        // radius, or than the radius enlarged by the perspective of the nearest corner
+        let camera_distance = self.camera_distance();
+        let radius = match camera_distance {
+            Some(camera_distance) => {
+                self.radius() * camera_distance / (camera_distance - self.radius())
+            }
+            None => self.radius(),
+        };
+        let img_size = 2.0 * radius * sticker_size + sticker_size;
+
+        let mut quads: Vec<&Quad> = self
+            .quads
+            .iter()
+            .filter(|quad| quad.faces_camera(camera_distance))
+            .collect();
+        quads.sort_by(|a, b| b.get_depth().total_cmp(&a.get_depth()));
+        let palette = Palette::current();
+        let stickers = quads
+            .iter()
+            .map(|quad| {
+                let mut img_quad = (*quad).clone();
+                if let Some(camera_distance) = camera_distance {
+                    img_quad.apply_perspective(camera_distance);
+                }
+                img_quad.project_to_img(sticker_size, sticker_size, 0, 0);
+                let corners = img_quad.vertices.map(|vertex| {
+                    let (x, y) = vertex.get_proj();
+                    (x + img_size / 2.0, y + img_size / 2.0)
+                });
+                (corners, palette.rgb(quad.color))
+            })
+            .collect();
+        (img_size, stickers)
     }
 
+    /// Rotates the view by `dp` radians around the horizontal axis of the screen.
     pub fn rotate_pitch(&mut self, dp: f32) {
-        self.pitch += dp;
-        let pitch_matrix = CubeRender::pitch_matrix(dp);
-        for square in self.quads.iter_mut() {
-            square.transform(pitch_matrix.view());
-        }
+        self.rotate_view(CubeRender::pitch_matrix(dp));
     }
+    /// Rotates the view by `dy` radians around the vertical axis of the screen.
     pub fn rotate_yaw(&mut self, dy: f32) {
-        self.yaw += dy;
+        self.rotate_view(CubeRender::yaw_matrix(dy));
+    }
+    /// Rotates the view around the line of sight, clockwise for a positive `dr`.
+    pub fn rotate_roll(&mut self, dr: f32) {
+        self.rotate_view(CubeRender::roll_matrix(dr));
+    }
+
+    /// Turns the view to one of the preset orientations.
+    pub fn set_view(&mut self, view: CameraView) {
+        self.view_matrix = CubeRender::preset_matrix(view);
+        self.reset_quads();
+    }
+
+    /// Turns the view to one of the preset orientations over `frames` frames, calling
+    /// `draw_frame` after each of them, instead of snapping to it.
+    ///
+    /// The view rotates around a single axis at a steady angle per frame apart from easing in and
+    /// out, which is the shortest way between the two orientations, like a slerp of quaternions.
+    pub fn animate_view(
+        &mut self,
+        view: CameraView,
+        frames: usize,
+        mut draw_frame: impl FnMut(&CubeRender),
+    ) {
+        // the net doesn't show the view
+        let frames = match self.mode {
+            RenderMode::View => frames,
+            RenderMode::Net => 0,
+        };
+        let start = self.view_matrix.clone();
+        let target = CubeRender::preset_matrix(view);
+        let (axis, angle) = rotation_axis_angle(&target.dot(&start.t()));
+        for frame in 1..frames {
+            // smoothstep, like the turns
+            let t = frame as f32 / frames as f32;
+            let rotation = rotation_matrix(&axis, angle * t * t * (3.0 - 2.0 * t));
+            self.view_matrix = rotation.dot(&start);
+            self.reset_quads();
+            draw_frame(self);
+        }
+        self.set_view(view);
+        draw_frame(self);
+    }
+
+    /// Spins the cube by `angle` around its own vertical axis, like on a turntable, so the face
+    /// on top stays on top whatever the view.
+    pub fn spin(&mut self, angle: f32) {
+        self.view_matrix = self.view_matrix.dot(&CubeRender::yaw_matrix(angle));
+        self.reset_quads();
+    }
+
+    /// Returns the rotation of the view of a preset orientation.
+    fn preset_matrix(view: CameraView) -> Array2<f32> {
+        match view {
+            CameraView::Front => Array2::eye(3),
+            // turn the right face halfway to the front, then tilt the up face towards us until
+            // the faces make the same angle with the line of sight
+            CameraView::Corner => CubeRender::pitch_matrix(-f32::atan(f32::sqrt(0.5)))
+                .dot(&CubeRender::yaw_matrix(-std::f32::consts::FRAC_PI_4)),
+            CameraView::Top => CubeRender::pitch_matrix(-std::f32::consts::FRAC_PI_2),
+        }
+    }
 
-        let yaw_matrix = CubeRender::yaw_matrix(dy);
+    /// Applies `rotation_matrix` to the view, after its current rotation.
+    fn rotate_view(&mut self, rotation_matrix: Array2<f32>) {
+        self.view_matrix = rotation_matrix.dot(&self.view_matrix);
         for square in self.quads.iter_mut() {
-            square.transform(yaw_matrix.view());
+            square.transform(rotation_matrix.view());
         }
     }
 
@@ -282,6 +1253,16 @@ impl CubeRender {
             [0.0, -pitch.sin(), pitch.cos()],
         ])
     }
+    /// Returns the matrix of a counter-clockwise rotation by `angle` around `axis`, when looking
+    /// from its positive side.
+    fn axis_matrix(axis: CubeAxis, angle: f32) -> Array2<f32> {
+        let (sin, cos) = angle.sin_cos();
+        match axis {
+            CubeAxis::X => arr2(&[[1.0, 0.0, 0.0], [0.0, cos, -sin], [0.0, sin, cos]]),
+            CubeAxis::Y => arr2(&[[cos, 0.0, sin], [0.0, 1.0, 0.0], [-sin, 0.0, cos]]),
+            CubeAxis::Z => arr2(&[[cos, -sin, 0.0], [sin, cos, 0.0], [0.0, 0.0, 1.0]]),
+        }
+    }
     fn yaw_matrix(yaw: f32) -> Array2<f32> {
         arr2(&[
             [yaw.cos(), 0.0, yaw.sin()],
@@ -289,4 +1270,316 @@ impl CubeRender {
             [-yaw.sin(), 0.0, yaw.cos()],
         ])
     }
+    fn roll_matrix(roll: f32) -> Array2<f32> {
+        arr2(&[
+            [roll.cos(), roll.sin(), 0.0],
+            [-roll.sin(), roll.cos(), 0.0],
+            [0.0, 0.0, 1.0],
+        ])
+    }
+}
+
+/// The corners of a sticker in an image and the RGB value of its color, see
+/// `CubeRender::projected_stickers`.
+pub type ProjectedSticker = ([(f32, f32); 4], (u8, u8, u8));
+
+/// The corners of a sticker and the RGB value of its color, see `sticker_corners`.
+pub type StickerCorners = ([[f32; 3]; 4], (u8, u8, u8));
+
+/// Returns the corners of the stickers of `cube` with the RGB values of their colors in the
+/// current palette, centered at the origin with one unit per sticker, so that other renderers
+/// draw the same cube as `CubeRender`. If `turn` is given, its layer is rotated by the fraction
+/// of the turn that comes with it.
+///
+/// The corners go around each sticker, with x to the right, y up and z toward the front.
+pub fn sticker_corners(cube: &Cube, turn: Option<(&Turn, f32)>) -> Vec<StickerCorners> {
+    let palette = Palette::current();
+    let quads = match turn {
+        Some((turn, progress)) => CubeRender::turning_quads(cube, turn, progress),
+        None => CubeRender::cube_quads(cube),
+    };
+    quads
+        .iter()
+        .map(|quad| {
+            let corners = quad.vertices.clone().map(|vertex| {
+                let c = vertex.coordinate;
+                [c[0], c[1], c[2]]
+            });
+            (corners, palette.rgb(quad.color))
+        })
+        .collect()
+}
+
+/// Returns the character showing the pixels `top` and `bottom` as half blocks.
+fn half_block_cell(top: Option<Pixel>, bottom: Option<Pixel>, color_mode: ColorMode) -> String {
+    match (top, bottom) {
+        (None, None) => " ".to_string(),
+        (Some(top), None) => format!("{}▀\x1b[0m", top.code(color_mode, false)),
+        (None, Some(bottom)) => format!("{}▄\x1b[0m", bottom.code(color_mode, false)),
+        (Some(top), Some(bottom)) => format!(
+            "{}{}▀\x1b[0m",
+            top.code(color_mode, false),
+            bottom.code(color_mode, true)
+        ),
+    }
+}
+
+/// Returns the character showing a cell of `pixels` with `ColorMode::NoColor`: the fill
+/// character of the color of most of its stickers (see `Color::fill_char`), or a space for
+/// the borders and the background.
+fn ascii_cell(pixels: ArrayView2<Option<Pixel>>) -> char {
+    let chars: Vec<char> = pixels
+        .iter()
+        .flatten()
+        .map(|pixel| match pixel {
+            Pixel::Sticker(color, _) => color.fill_char(),
+            Pixel::Border => ' ',
+        })
+        .collect();
+    chars
+        .iter()
+        .copied()
+        .max_by_key(|c| chars.iter().filter(|other| *other == c).count())
+        .unwrap_or(' ')
+}
+
+/// Returns the closest ASCII character to `c`, for the lines and the arrows of the labels and
+/// the net (see `line_char`) with `ColorMode::NoColor`.
+fn ascii(c: char) -> char {
+    match c {
+        '─' => '-',
+        '│' => '|',
+        '╱' => '/',
+        '╲' => '\\',
+        '→' => '>',
+        '←' => '<',
+        '↑' => '^',
+        '↓' => 'v',
+        '↗' | '↖' | '↙' | '↘' => '*',
+        '⊙' => 'o',
+        '⊗' => 'x',
+        c => c,
+    }
+}
+
+/// Returns the character showing a cell of 2x4 `pixels` as a braille pattern (see
+/// `PixelStyle::Braille`).
+///
+/// Inside the cube, the background is the color of most of the stickers of the cell, and the
+/// dots are its borders. On the edges of the cube, the dots are the pixels of the cube, in the
+/// color of most of its stickers.
+fn braille_cell(pixels: ArrayView2<Option<Pixel>>, color_mode: ColorMode) -> String {
+    // the bit of the dot of each pixel, by row and column
+    const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+    let stickers: Vec<Pixel> = pixels
+        .iter()
+        .flatten()
+        .filter(|pixel| matches!(pixel, Pixel::Sticker(..)))
+        .copied()
+        .collect();
+    let same_color = |a: &Pixel, b: &Pixel| match (a, b) {
+        (Pixel::Sticker(a, _), Pixel::Sticker(b, _)) => a == b,
+        _ => false,
+    };
+    let fill = stickers
+        .iter()
+        .max_by_key(|pixel| stickers.iter().filter(|p| same_color(p, pixel)).count());
+    let dots = |is_dot: &dyn Fn(&Option<Pixel>) -> bool| {
+        let bits = pixels
+            .indexed_iter()
+            .filter(|(_, pixel)| is_dot(pixel))
+            .fold(0, |bits, ((y, x), _)| bits | DOTS[y][x]);
+        char::from_u32(0x2800 + bits).unwrap()
+    };
+
+    if pixels.iter().all(Option::is_none) {
+        return " ".to_string();
+    }
+    match (pixels.iter().all(Option::is_some), fill) {
+        (true, Some(fill)) => format!(
+            "{}{}{}\x1b[0m",
+            fill.code(color_mode, true),
+            Pixel::Border.code(color_mode, false),
+            dots(&|pixel| matches!(pixel, Some(Pixel::Border)))
+        ),
+        (true, None) => format!("{} \x1b[0m", Pixel::Border.code(color_mode, true)),
+        (false, fill) => format!(
+            "{}{}\x1b[0m",
+            fill.unwrap_or(&Pixel::Border).code(color_mode, false),
+            dots(&|pixel| pixel.is_some())
+        ),
+    }
+}
+
+/// Returns the unit vector pointing out of the face at `face_dir`.
+fn face_normal(face_dir: FaceDir) -> Array1<f32> {
+    let mut normal = Array1::zeros(3);
+    let i = match face_dir.get_axis() {
+        CubeAxis::X => 0,
+        CubeAxis::Y => 1,
+        CubeAxis::Z => 2,
+    };
+    normal[i] = if face_dir.is_positive() { 1.0 } else { -1.0 };
+    normal
+}
+
+/// Returns the arrow pointing in the direction `x`, `y`, `z` of the view, as seen on the
+/// screen. Directions nearly toward the viewer or away from them are shown as the tip or the
+/// tail of an arrow.
+fn axis_arrow(x: f32, y: f32, z: f32) -> char {
+    if x.hypot(y) < 0.4 {
+        return if z > 0.0 { '⊙' } else { '⊗' };
+    }
+    line_char(x, y).1
+}
+
+/// Returns the character of a line going in the direction `x`, `y` on the screen (with `y`
+/// going up), and the arrow pointing in that direction, to the nearest eighth of a turn.
+fn line_char(x: f32, y: f32) -> (char, char) {
+    // the lines and the arrows counter-clockwise from the right, every eighth of a turn
+    const LINES: [char; 4] = ['─', '╱', '│', '╲'];
+    const ARROWS: [char; 8] = ['→', '↗', '↑', '↖', '←', '↙', '↓', '↘'];
+    let eighth = (y.atan2(x) / std::f32::consts::FRAC_PI_4).round() as i32;
+    (
+        LINES[eighth.rem_euclid(4) as usize],
+        ARROWS[eighth.rem_euclid(8) as usize],
+    )
+}
+
+/// Returns the unit axis and the angle (between 0 and pi, counter-clockwise when the axis points
+/// toward the viewer) of the rotation `matrix`. The axis is arbitrary if the angle is 0.
+fn rotation_axis_angle(matrix: &Array2<f32>) -> (Array1<f32>, f32) {
+    let m = matrix;
+    let cos = ((m[[0, 0]] + m[[1, 1]] + m[[2, 2]] - 1.0) / 2.0).clamp(-1.0, 1.0);
+    let angle = cos.acos();
+    let axis = arr1(&[
+        m[[2, 1]] - m[[1, 2]],
+        m[[0, 2]] - m[[2, 0]],
+        m[[1, 0]] - m[[0, 1]],
+    ]);
+    let norm = axis.dot(&axis).sqrt();
+    if norm > 1e-4 {
+        return (axis / norm, angle);
+    }
+    if angle < std::f32::consts::FRAC_PI_2 {
+        return (arr1(&[0.0, 1.0, 0.0]), 0.0);
+    }
+    // a half turn is symmetric, so the axis is the longest column of `matrix` + I, which is
+    // twice the axis times itself
+    let half = m + &Array2::<f32>::eye(3);
+    let axis = half
+        .columns()
+        .into_iter()
+        .map(|column| column.to_owned())
+        .max_by(|a, b| a.dot(a).total_cmp(&b.dot(b)))
+        .unwrap();
+    let norm = axis.dot(&axis).sqrt();
+    (axis / norm, angle)
+}
+
+/// Returns the matrix of a counter-clockwise rotation by `angle` around the unit vector `axis`,
+/// with Rodrigues' formula.
+fn rotation_matrix(axis: &Array1<f32>, angle: f32) -> Array2<f32> {
+    let (x, y, z) = (axis[0], axis[1], axis[2]);
+    let cross_matrix = arr2(&[[0.0, -z, y], [z, 0.0, -x], [-y, x, 0.0]]);
+    let (sin, cos) = angle.sin_cos();
+    Array2::<f32>::eye(3) + &cross_matrix * sin + cross_matrix.dot(&cross_matrix) * (1.0 - cos)
+}
+
+/// Returns the cross product of two 3D vectors.
+fn cross(a: &Array1<f32>, b: &Array1<f32>) -> Array1<f32> {
+    arr1(&[
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ])
+}
+
+/// Renders `left` and `right` next to each other in one frame (e.g. a cube before and after an
+/// algorithm), by putting their lines (see `CubeRender::render_lines`) side by side.
+pub fn render_side_by_side(left: &CubeRender, right: &CubeRender) -> Vec<String> {
+    let (left_lines, right_lines) = (left.render_lines(), right.render_lines());
+    // the lines are padded by their width on the screen, without their escape codes
+    let left_w = left_lines.iter().map(|line| visible_width(line)).max();
+    let left_w = left_w.unwrap_or(0) + SIDE_BY_SIDE_GAP;
+    (0..usize::max(left_lines.len(), right_lines.len()))
+        .map(|i| {
+            let left_line = left_lines.get(i).map_or("", String::as_str);
+            let right_line = right_lines.get(i).map_or("", String::as_str);
+            let padding = " ".repeat(left_w - visible_width(left_line));
+            format!("{left_line}{padding}{right_line}")
+        })
+        .collect()
+}
+
+/// Returns the number of characters of `line` shown on the screen, skipping its escape codes.
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape_code = false;
+    for c in line.chars() {
+        match c {
+            '\x1b' => in_escape_code = true,
+            // the escape codes of the renders all end with "m"
+            'm' if in_escape_code => in_escape_code = false,
+            _ if in_escape_code => (),
+            _ => width += 1,
+        }
+    }
+    width
+}
+
+/// Returns the unfolded cube as an SVG image, laid out like its `Display`, with `sticker_size`
+/// pixels per sticker.
+pub fn render_net_svg(cube: &Cube, sticker_size: f32) -> String {
+    let size = cube.size as f32;
+    // position of each face in the net, in faces
+    let face_positions = [
+        (FaceDir::Up, 1.0, 0.0),
+        (FaceDir::Left, 0.0, 1.0),
+        (FaceDir::Front, 1.0, 1.0),
+        (FaceDir::Right, 2.0, 1.0),
+        (FaceDir::Back, 3.0, 1.0),
+        (FaceDir::Down, 1.0, 2.0),
+    ];
+    // leave a margin of half a sticker around the net
+    let margin = sticker_size / 2.0;
+    let palette = Palette::current();
+    let mut squares = Vec::new();
+    for (face_dir, face_x, face_y) in face_positions {
+        for ((y, x), color) in cube.get_face(&face_dir).colors.indexed_iter() {
+            squares.push(format!(
+                r#"<rect x="{:.2}" y="{:.2}" width="{sticker_size:.2}" height="{sticker_size:.2}" fill="{}"/>"#,
+                margin + (face_x * size + x as f32) * sticker_size,
+                margin + (face_y * size + y as f32) * sticker_size,
+                palette.hex(*color)
+            ));
+        }
+    }
+    let (width, height) = (4.0 * size * sticker_size, 3.0 * size * sticker_size);
+    svg(
+        width + 2.0 * margin,
+        height + 2.0 * margin,
+        sticker_size,
+        &squares,
+    )
+}
+
+/// Returns an SVG image of `width` by `height` pixels with the `shapes`, outlined in black like
+/// the stickers of a cube.
+fn svg(width: f32, height: f32, sticker_size: f32, shapes: &[String]) -> String {
+    let mut s = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.0}" height="{height:.0}" viewBox="0 0 {width:.2} {height:.2}">"#
+    );
+    s.push('\n');
+    s.push_str(&format!(
+        r#"<g stroke="black" stroke-width="{:.2}" stroke-linejoin="round">"#,
+        sticker_size / 15.0
+    ));
+    s.push('\n');
+    for shape in shapes {
+        s.push_str(shape);
+        s.push('\n');
+    }
+    s.push_str("</g>\n</svg>\n");
+    s
 }
@@ -0,0 +1,151 @@
+//! Functions to use the cube and the solvers from C, C++ or game engines, with the `ffi` feature.
+//!
+//! The declarations are in `include/rubiks.h`, which the build writes with cbindgen. The library
+//! to link is built with
+//!
+//! ```sh
+//! cargo rustc --release --lib --features ffi --crate-type cdylib
+//! ```
+//!
+//! or `--crate-type staticlib`. The cubes and the strings returned are owned by the caller, who
+//! frees them with `rubiks_cube_free` and `rubiks_string_free`. A panic never unwinds into C: the
+//! function returns NULL or false instead, like when it fails.
+
+use std::{
+    ffi::{c_char, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+    time::Duration,
+};
+
+use crate::{
+    cube::{Cube, Turn},
+    search::{self, Heuristic, MoveSet, SearchConfig, Solver},
+};
+
+/// A cube handed to C, created by `rubiks_cube_new`.
+pub struct RubiksCube(Cube);
+
+/// Returns `s` as a string that C can read and free with `rubiks_string_free`.
+fn into_c_string(s: String) -> *mut c_char {
+    // the strings made here never have a NUL byte
+    CString::new(s).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Runs the body of a function of this module, returning `on_panic` if it panics, since unwinding
+/// into C is undefined behavior. The cube of a function that panicked may be left half turned.
+fn catch_panic<T>(on_panic: T, body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or(on_panic)
+}
+
+/// Creates a solved cube of size `size`, or returns NULL if `size` is less than 2.
+#[no_mangle]
+pub extern "C" fn rubiks_cube_new(size: usize) -> *mut RubiksCube {
+    catch_panic(ptr::null_mut(), || {
+        match Cube::builder().size(size).build() {
+            Ok(cube) => Box::into_raw(Box::new(RubiksCube(cube))),
+            Err(_) => ptr::null_mut(),
+        }
+    })
+}
+
+/// Frees a cube created by `rubiks_cube_new`. Does nothing if `cube` is NULL.
+///
+/// # Safety
+///
+/// `cube` must be NULL or a cube created by `rubiks_cube_new` that wasn't freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn rubiks_cube_free(cube: *mut RubiksCube) {
+    catch_panic((), || {
+        if !cube.is_null() {
+            drop(Box::from_raw(cube));
+        }
+    })
+}
+
+/// Applies the turns of `algo`, like `R U R' U'`, to `cube`. Returns false and leaves the cube as
+/// it is if the turns are invalid or turn layers that the cube doesn't have.
+///
+/// # Safety
+///
+/// `cube` must be a cube created by `rubiks_cube_new`, and `algo` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rubiks_cube_apply(cube: *mut RubiksCube, algo: *const c_char) -> bool {
+    catch_panic(false, || {
+        let cube = &mut (*cube).0;
+        let Ok(algo) = CStr::from_ptr(algo).to_str() else {
+            return false;
+        };
+        match Turn::parse_algorithm(algo.split_whitespace().collect()) {
+            Some(algo) if algo.iter().all(|turn| turn.get_layer() <= cube.size()) => {
+                cube.apply_algorithm(algo);
+                true
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Returns whether `cube` is solved.
+///
+/// # Safety
+///
+/// `cube` must be a cube created by `rubiks_cube_new`.
+#[no_mangle]
+pub unsafe extern "C" fn rubiks_cube_is_solved(cube: *const RubiksCube) -> bool {
+    catch_panic(false, || (*cube).0.is_solved())
+}
+
+/// Returns the letters of the colors of the facelets of `cube`, see `Cube::facelet_string`. The
+/// string must be freed with `rubiks_string_free`.
+///
+/// # Safety
+///
+/// `cube` must be a cube created by `rubiks_cube_new`.
+#[no_mangle]
+pub unsafe extern "C" fn rubiks_cube_facelets(cube: *const RubiksCube) -> *mut c_char {
+    catch_panic(ptr::null_mut(), || {
+        into_c_string((*cube).0.facelet_string())
+    })
+}
+
+/// Solves `cube` with the fastest solver for its size (see `search::solve`), giving up after
+/// `time_limit_ms` milliseconds unless it's 0. Returns the turns of the solution, like
+/// `R U R' U'`, or NULL if there's none. The string must be freed with `rubiks_string_free`.
+///
+/// The cube isn't turned, the solution can be applied with `rubiks_cube_apply`.
+///
+/// # Safety
+///
+/// `cube` must be a cube created by `rubiks_cube_new`.
+#[no_mangle]
+pub unsafe extern "C" fn rubiks_solve(cube: *const RubiksCube, time_limit_ms: u64) -> *mut c_char {
+    catch_panic(ptr::null_mut(), || {
+        let cube = &(*cube).0;
+        let config = SearchConfig {
+            move_set: MoveSet::default_for(cube.size()),
+            time_limit: (time_limit_ms > 0).then(|| Duration::from_millis(time_limit_ms)),
+            ..SearchConfig::default()
+        };
+        match search::solve(cube, &config, Solver::Auto, Heuristic::default())
+            .and_then(|result| result.solution)
+        {
+            Some(solution) => into_c_string(Turn::algo_string(&solution)),
+            None => ptr::null_mut(),
+        }
+    })
+}
+
+/// Frees a string returned by the functions of this module. Does nothing if `s` is NULL.
+///
+/// # Safety
+///
+/// `s` must be NULL or a string returned by this module that wasn't freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn rubiks_string_free(s: *mut c_char) {
+    catch_panic((), || {
+        if !s.is_null() {
+            drop(CString::from_raw(s));
+        }
+    })
+}
@@ -0,0 +1,243 @@
+use std::collections::VecDeque;
+
+use macroquad::{
+    camera::{set_camera, set_default_camera, Camera3D},
+    color::{self, Color as MqColor},
+    input::{
+        get_char_pressed, is_key_pressed, is_mouse_button_down, mouse_position, mouse_wheel,
+        KeyCode, MouseButton,
+    },
+    math::{vec2, vec3, Vec3, Vec4},
+    models::{draw_mesh, Mesh, Vertex},
+    text::draw_text,
+    time::get_frame_time,
+    window::{clear_background, next_frame, Conf},
+};
+
+use rubiks::{
+    cube::rendering::sticker_corners,
+    cube::{Cube, Turn},
+    search::{solve, MoveSet, SearchConfig},
+};
+
+use crate::{app::face_turn, config::Config};
+
+/// Time that a turn takes, in seconds.
+const TURN_DURATION: f32 = 0.25;
+/// Gap between the stickers, as a fraction of a sticker, like the borders of the terminal app.
+const STICKER_GAP: f32 = 0.1;
+/// Direction the light comes from, relative to the camera (x right, y up, z toward the
+/// camera), and the brightness of the faces that it doesn't reach.
+const LIGHT_DIRECTION: Vec3 = vec3(-0.4, 0.6, 1.0);
+const AMBIENT_LIGHT: f32 = 0.45;
+/// Rotation of the view for each pixel the mouse is dragged by, and zoom for each step of the
+/// wheel.
+const DRAG_SPEED: f32 = 0.01;
+const ZOOM_SPEED: f32 = 1.1;
+
+/// Lines of help shown under the status bar.
+const HELP_LINES: [&str; 3] = [
+    "u/d/r/l/f/b to turn a face clockwise, Shift+u/d/r/l/f/b to turn it counter-clockwise",
+    "Drag to rotate the view, scroll to zoom",
+    "M to scramble, X to reset, S to solve, Q to quit",
+];
+
+/// Opens a window showing a cube of size `size` in 3D, with the same commands as the terminal
+/// app (see `main_app_loop`) and the scramble length and solver of `config`.
+pub fn main_gui_loop(size: usize, config: &Config) {
+    let conf = Conf {
+        window_title: "Rubik's cube".to_string(),
+        window_width: 800,
+        window_height: 800,
+        sample_count: 4,
+        ..Conf::default()
+    };
+    macroquad::Window::from_config(conf, gui_loop(size, config.clone()));
+}
+
+async fn gui_loop(size: usize, config: Config) {
+    let mut cube = Cube::new(size);
+    // number of turns applied since the cube was reset or scrambled
+    let mut move_count = 0;
+    // turns waiting to be shown, the first one being shown, and how far it is
+    let mut turns: VecDeque<Turn> = VecDeque::new();
+    let mut turn_progress = 0.0;
+    let mut message = String::new();
+
+    // the camera looks at the middle of the cube, from the up, front and right corner
+    let (mut yaw, mut pitch) = (std::f32::consts::FRAC_PI_4, 0.6);
+    let mut distance = 4.0 * cube.size() as f32;
+    // where the mouse was on the last frame if it's being dragged
+    let mut drag_position = None;
+
+    loop {
+        // the keys are read as the characters they type, so Shift turns counter-clockwise like
+        // in the terminal
+        while let Some(c) = get_char_pressed() {
+            match c {
+                'q' | 'Q' => return,
+                c if face_turn(c).is_some() => {
+                    turns.push_back(face_turn(c).unwrap());
+                    move_count += 1;
+                }
+                'x' | 'X' => {
+                    cube = Cube::new(cube.size());
+                    turns.clear();
+                    move_count = 0;
+                }
+                'm' | 'M' => {
+                    // finish the turns being shown first, so the scramble starts from them
+                    cube.apply_algorithm(turns.drain(..).collect());
+                    let algo = cube.scramble(config.scramble_length);
+                    message = format!("Scramble sequence: {}", Turn::algo_string(&algo));
+                    move_count = 0;
+                }
+                's' | 'S' if turns.is_empty() => {
+                    let search_config = SearchConfig {
+                        move_set: MoveSet::default_for(cube.size()),
+                        fallback: true,
+                        ..SearchConfig::default()
+                    };
+                    let Some(result) =
+                        solve(&cube, &search_config, config.solver, config.heuristic)
+                    else {
+                        message = "No solution found".to_string();
+                        continue;
+                    };
+                    message = result.to_string().lines().next().unwrap_or("").to_string();
+                    if let Some(solution) = result.solution {
+                        move_count += solution.len();
+                        turns.extend(solution);
+                    }
+                }
+                _ => (),
+            }
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            return;
+        }
+
+        // dragging the mouse rotates the view, and scrolling zooms
+        if is_mouse_button_down(MouseButton::Left) {
+            let (x, y) = mouse_position();
+            if let Some((last_x, last_y)) = drag_position {
+                yaw -= (x - last_x) * DRAG_SPEED;
+                pitch += (y - last_y) * DRAG_SPEED;
+                // past straight above or below, the view would turn upside down
+                pitch = f32::clamp(pitch, -1.5, 1.5);
+            }
+            drag_position = Some((x, y));
+        } else {
+            drag_position = None;
+        }
+        let (_, wheel) = mouse_wheel();
+        if wheel > 0.0 {
+            distance /= ZOOM_SPEED;
+        } else if wheel < 0.0 {
+            distance *= ZOOM_SPEED;
+        }
+
+        // advance the turn being shown, and apply it once it's done
+        if let Some(turn) = turns.front() {
+            turn_progress += get_frame_time() / TURN_DURATION;
+            if turn_progress >= 1.0 {
+                cube.apply_turn(turn);
+                turns.pop_front();
+                turn_progress = 0.0;
+            }
+        }
+
+        clear_background(color::DARKGRAY);
+        let camera = Camera3D {
+            position: distance
+                * vec3(
+                    pitch.cos() * yaw.sin(),
+                    pitch.sin(),
+                    pitch.cos() * yaw.cos(),
+                ),
+            target: Vec3::ZERO,
+            up: Vec3::Y,
+            ..Camera3D::default()
+        };
+        set_camera(&camera);
+        // smoothstep, so the layer starts and stops slowly like in the terminal
+        let t = turn_progress;
+        let turn = turns.front().map(|turn| (turn, t * t * (3.0 - 2.0 * t)));
+        draw_stickers(&cube, turn, &camera);
+
+        set_default_camera();
+        let solved = if cube.is_solved() && turns.is_empty() {
+            "Solved"
+        } else {
+            "Not solved"
+        };
+        let status = format!(
+            "{size}x{size} | Moves: {move_count} | {solved}",
+            size = cube.size()
+        );
+        for (i, line) in [status.as_str()]
+            .into_iter()
+            .chain(HELP_LINES)
+            .chain(message.lines())
+            .enumerate()
+        {
+            draw_text(line, 10.0, 24.0 + 20.0 * i as f32, 20.0, color::WHITE);
+        }
+
+        next_frame().await;
+    }
+}
+
+/// Draws the stickers of `cube` (see `sticker_corners`) as black squares with a smaller square
+/// of their color on top, shaded by the light coming from the camera.
+fn draw_stickers(cube: &Cube, turn: Option<(&Turn, f32)>, camera: &Camera3D) {
+    // the light turns with the camera, so the faces facing the camera are always lit
+    let forward = (camera.target - camera.position).normalize();
+    let right = forward.cross(camera.up).normalize();
+    let up = right.cross(forward);
+    let light_direction = LIGHT_DIRECTION.normalize();
+    let light_direction =
+        right * light_direction.x + up * light_direction.y - forward * light_direction.z;
+
+    for (corners, (r, g, b)) in sticker_corners(cube, turn) {
+        let corners = corners.map(Vec3::from);
+        let center = corners.iter().sum::<Vec3>() / 4.0;
+        let mut normal = (corners[1] - corners[0])
+            .cross(corners[3] - corners[0])
+            .normalize();
+        // the stickers face away from the middle of the cube, even on a turning layer
+        if normal.dot(center) < 0.0 {
+            normal = -normal;
+        }
+        let brightness =
+            AMBIENT_LIGHT + (1.0 - AMBIENT_LIGHT) * normal.dot(light_direction).max(0.0);
+
+        let shade = |c: u8| c as f32 / 255.0 * brightness;
+        let sticker_color = MqColor::new(shade(r), shade(g), shade(b), 1.0);
+        // the colored square is slightly in front of the black one, so it's drawn over it
+        let inset: Vec<Vec3> = corners
+            .iter()
+            .map(|&corner| center + (corner - center) * (1.0 - 2.0 * STICKER_GAP) + normal * 1e-3)
+            .collect();
+        draw_quad(&corners, color::BLACK);
+        draw_quad(&inset, sticker_color);
+    }
+}
+
+/// Draws the quad with the corners `corners`, going around it, in `color`.
+fn draw_quad(corners: &[Vec3], color: MqColor) {
+    let vertices = corners
+        .iter()
+        .map(|&position| Vertex {
+            position,
+            uv: vec2(0.0, 0.0),
+            color: color.into(),
+            normal: Vec4::ZERO,
+        })
+        .collect();
+    draw_mesh(&Mesh {
+        vertices,
+        indices: vec![0, 1, 2, 0, 2, 3],
+        texture: None,
+    });
+}
@@ -0,0 +1,493 @@
+use std::{
+    io::{self, BufRead, Read},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+use rubiks::{
+    cube::{Cube, Turn},
+    search::{
+        report::{SolveReport, Timings},
+        solve, Heuristic, MoveSet, SearchConfig, SearchStatus, Solver,
+    },
+};
+
+use crate::{batch::read_algorithm_file, completions, config::Config, scramble::ScrambleOptions};
+
+/// Exit codes of `main_solve`.
+const EXIT_SOLVED: i32 = 0;
+const EXIT_UNSOLVABLE: i32 = 1;
+const EXIT_TIMED_OUT: i32 = 2;
+pub const EXIT_INVALID: i32 = 3;
+
+const USAGE: &str = "Usage: rubiks solve [--size N] [--timeout SECONDS] [--solver NAME]
+                   [--heuristic NAME] [--facelets] [--url] [--json] [--verify] [SCRAMBLE]
+
+Solves the cube scrambled by SCRAMBLE, like \"R U R' U'\", or by the turns read from stdin if
+there's none, and prints the solution.
+
+  --size N             size of the cube, 2 by default or the size of the config
+  --timeout SECONDS    gives up after this long
+  --solver NAME        auto (the fastest for the size, by default), idastar, two-phase or lbl
+  --heuristic NAME     heuristic of IDA*: single-l0 (by default), all-l0, coordinate or
+                       exact-table
+  --facelets           also prints the colors of the facelets of the scrambled cube
+  --url                also prints the link of alg.cubing.net that replays the solution
+  --json               prints the scramble, the solver, the heuristic, the solution, its link
+                       and the statistics of the search as a JSON object instead
+  --verify             checks that the solution solves the cube before printing it, which
+                       only debug builds do by default
+  --config PATH        reads the default settings from PATH instead of
+                       ~/.config/rubiks-solver/config.toml
+  -q, --quiet          only logs the errors to stderr, not the warnings
+  -v, -vv, -vvv        also logs what the solvers do to stderr, with more details each time
+
+Exits with 0 if the cube is solved, 1 if no solution was found, 2 if the search timed out, and 3
+if the arguments or the scramble are invalid.";
+
+const SCRAMBLE_USAGE: &str =
+    "Usage: rubiks scramble [--size N] [--length N] [--seed S] [--official] [--daily]
+                      [--facelets] [--json]
+
+Prints a scramble of the cube in standard notation.
+
+  --size N             size of the cube, 2 by default or the size of the config
+  --length N           number of random turns, 20 by default or scramble_length of the config
+  --seed S             gives the same scramble for the same seed
+  --official           scrambles a 2x2 or a 3x3 to a random state, like in competitions
+  --daily              gives the scramble of today (in UTC), the same for everyone, seeded by
+                       the date like 20240131 and with 25 turns by default
+  --facelets           also prints the colors of the facelets of the scrambled cube
+  --json               prints the scramble, its options and the time it took as a JSON object
+                       instead
+  --config PATH        reads the default settings from PATH instead of
+                       ~/.config/rubiks-solver/config.toml
+  -q, --quiet          only logs the errors to stderr, not the warnings
+  -v, -vv, -vvv        also logs what the solvers do to stderr, with more details each time
+
+Exits with 0 if the cube is scrambled, and 3 if the arguments are invalid.";
+
+const APPLY_USAGE: &str =
+    "Usage: rubiks apply --file FILE [--size N] [--reset] [--render] [--solve]
+                   [--solver NAME] [--heuristic NAME] [--timeout SECONDS] [--facelets]
+
+Applies the algorithms of FILE, one per line, in order to a solved cube, and prints whether the
+cube is solved after each one. Empty lines and lines starting with // are skipped.
+
+  --file FILE          file of the algorithms
+  --size N             size of the cube, 2 by default or the size of the config
+  --reset              applies each algorithm to a solved cube, e.g. to check an algorithm sheet
+  --render             prints the net of the cube after each algorithm
+  --solve              prints a solution of the cube after each algorithm
+  --solver NAME        solver of --solve, see `rubiks solve --help`
+  --heuristic NAME     heuristic of IDA* for --solve
+  --timeout SECONDS    gives up each solve after this long
+  --facelets           prints the colors of the facelets after each algorithm
+  --config PATH        reads the default settings from PATH instead of
+                       ~/.config/rubiks-solver/config.toml
+  -q, --quiet          only logs the errors to stderr, not the warnings
+  -v, -vv, -vvv        also logs what the solvers do to stderr, with more details each time
+
+Exits with 0 if every algorithm was applied, and 3 if the arguments or the file are invalid.";
+
+const PIPE_USAGE: &str = "Usage: rubiks pipe [ALGORITHM]
+
+Reads cubes from stdin, one per line, as the colors of their facelets followed by turns, like
+\"YYYYBBBBOOOOWWWWGGGGRRRR R U\", applies the turns and then ALGORITHM if it's given, and prints
+the colors of the facelets of each cube to stdout. The facelets are the letters of their colors in
+the order of --facelets, so that the output can be read again, e.g. by another rubiks pipe. The
+size of each cube is found from the number of its facelets.
+
+Exits with 0 if every line was turned, and 3 if a line or ALGORITHM is invalid.";
+
+const COMPLETIONS_USAGE: &str = "Usage: rubiks completions SHELL
+
+Prints the script that completes the subcommands, the flags, the solvers and the heuristics of
+rubiks in SHELL: bash, zsh or fish. For example, in bash:
+
+  source <(rubiks completions bash)
+
+Exits with 0 if the script is printed, and 3 if SHELL isn't supported.";
+
+/// What `rubiks solve --json` prints: the report of the solve, with what the flags add.
+#[derive(Serialize)]
+struct SolveOutput {
+    #[serde(flatten)]
+    report: SolveReport,
+    /// The link of alg.cubing.net that replays the solution.
+    url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    facelets: Option<String>,
+}
+
+/// What `rubiks scramble --json` prints.
+#[derive(Serialize)]
+struct ScrambleOutput {
+    size: usize,
+    scramble: String,
+    length: usize,
+    seed: Option<u64>,
+    official: bool,
+    /// The date of the daily scramble, like `2024-01-31`.
+    daily: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    facelets: Option<String>,
+    timings: Timings,
+}
+
+/// Prints `output` as JSON on a single line.
+fn print_json(output: &impl Serialize) {
+    println!(
+        "{}",
+        serde_json::to_string(output).expect("Error when writing JSON")
+    );
+}
+
+/// Solves a scramble without the interface of the app, for scripts: reads the scramble from
+/// `args` or from stdin, prints the solution to stdout, and returns the exit code.
+pub fn main_solve(args: &[String], config: &Config) -> i32 {
+    let invalid = |msg: &str| {
+        eprintln!("{msg}\n\n{USAGE}");
+        EXIT_INVALID
+    };
+
+    let (mut size, mut timeout, mut print_facelets) = (config.size, None, false);
+    let (mut solver, mut heuristic) = (config.solver, config.heuristic);
+    let (mut print_url, mut json, mut verify) = (false, false, cfg!(debug_assertions));
+    let mut scramble = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--size" => match args.next().and_then(|size| size.parse().ok()) {
+                Some(n) if n >= 2 => size = n,
+                _ => return invalid("Invalid size, it must be at least 2"),
+            },
+            "--timeout" => match args.next().and_then(|secs| secs.parse().ok()) {
+                Some(secs) if secs >= 0.0 => timeout = Some(Duration::from_secs_f64(secs)),
+                _ => return invalid("Invalid timeout"),
+            },
+            "--solver" => match args.next().and_then(|name| Solver::from_name(name)) {
+                Some(chosen) => solver = chosen,
+                None => return invalid("Invalid solver"),
+            },
+            "--heuristic" => match args.next().and_then(|name| Heuristic::from_name(name)) {
+                Some(chosen) => heuristic = chosen,
+                None => return invalid("Invalid heuristic"),
+            },
+            "--facelets" => print_facelets = true,
+            "--url" => print_url = true,
+            "--json" => json = true,
+            "--verify" => verify = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return EXIT_SOLVED;
+            }
+            _ => scramble.push(arg.clone()),
+        }
+    }
+    let scramble = if scramble.is_empty() {
+        let mut input = String::new();
+        if let Err(e) = io::stdin().read_to_string(&mut input) {
+            return invalid(&format!("Can't read the scramble: {e}"));
+        }
+        input
+    } else {
+        scramble.join(" ")
+    };
+
+    if !solver.supports(size) {
+        return invalid(&format!(
+            "The {} solver can't solve this size",
+            solver.name()
+        ));
+    }
+
+    let algo = match Turn::parse_algorithm(scramble.split_whitespace().collect()) {
+        Some(algo) if algo.iter().all(|turn| turn.get_layer() <= size) => algo,
+        Some(_) => return invalid("The cube doesn't have the layers of the scramble"),
+        None => return invalid("Invalid scramble"),
+    };
+    let mut cube = Cube::new(size);
+    cube.apply_algorithm(algo.clone());
+
+    let search_config = SearchConfig {
+        move_set: MoveSet::default_for(size),
+        time_limit: timeout,
+        verify,
+        fallback: true,
+        ..SearchConfig::default()
+    };
+    let start_time = Instant::now();
+    let result = solve(&cube, &search_config, solver, heuristic);
+    let solution = result
+        .as_ref()
+        .and_then(|result| result.solution.as_deref());
+    let url = solution.map(|solution| Turn::alg_cubing_url(size, &algo, solution));
+    if json {
+        print_json(&SolveOutput {
+            report: SolveReport::new(
+                size,
+                &algo,
+                solver.name(),
+                heuristic.name(),
+                result.as_ref(),
+                start_time.elapsed(),
+            ),
+            url: url.clone(),
+            facelets: print_facelets.then(|| cube.facelet_string()),
+        });
+    }
+    // the two-phase algorithm and the layer by layer method return nothing if they fail
+    let Some(result) = result else {
+        if json {
+            return EXIT_UNSOLVABLE;
+        }
+        if print_facelets {
+            println!("{}", cube.facelet_string());
+        }
+        return EXIT_UNSOLVABLE;
+    };
+    if !json {
+        if let Some(solution) = &result.solution {
+            println!("{}", Turn::algo_string(solution));
+        }
+        if let (true, Some(url)) = (print_url, url) {
+            println!("{url}");
+        }
+        if print_facelets {
+            println!("{}", cube.facelet_string());
+        }
+    }
+    match result.status {
+        SearchStatus::Solved => EXIT_SOLVED,
+        SearchStatus::TimedOut => EXIT_TIMED_OUT,
+        _ => EXIT_UNSOLVABLE,
+    }
+}
+
+/// Scrambles a cube without the interface of the app, for scripts: prints the scramble to stdout
+/// and returns the exit code.
+pub fn main_scramble(args: &[String], config: &Config) -> i32 {
+    let invalid = |msg: &str| {
+        eprintln!("{msg}\n\n{SCRAMBLE_USAGE}");
+        EXIT_INVALID
+    };
+
+    let (mut size, mut print_facelets, mut json) = (config.size, false, false);
+    let mut scramble_args = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--size" => match args.next().and_then(|size| size.parse().ok()) {
+                Some(n) if n >= 2 => size = n,
+                _ => return invalid("Invalid size, it must be at least 2"),
+            },
+            "--facelets" => print_facelets = true,
+            "--json" => json = true,
+            "-h" | "--help" => {
+                println!("{SCRAMBLE_USAGE}");
+                return EXIT_SOLVED;
+            }
+            _ => scramble_args.push(arg.as_str()),
+        }
+    }
+    let options = match ScrambleOptions::parse(&scramble_args) {
+        Ok(options) => options,
+        Err(msg) => return invalid(&msg),
+    };
+    let mut cube = Cube::new(size);
+    let start_time = Instant::now();
+    let algo = match options.scramble(&mut cube, config.scramble_length) {
+        Ok(algo) => algo,
+        Err(msg) => return invalid(&msg),
+    };
+    if json {
+        print_json(&ScrambleOutput {
+            size,
+            scramble: Turn::algo_string(&algo),
+            length: algo.len(),
+            seed: options.seed,
+            official: options.official,
+            daily: options.daily.map(|date| date.to_string()),
+            facelets: print_facelets.then(|| cube.facelet_string()),
+            timings: Timings::since(start_time),
+        });
+        return EXIT_SOLVED;
+    }
+    println!("{}", Turn::algo_string(&algo));
+    if print_facelets {
+        println!("{}", cube.facelet_string());
+    }
+    EXIT_SOLVED
+}
+
+/// Applies the algorithms of a file without the interface of the app, for scripts: prints the
+/// state of the cube after each algorithm to stdout, and returns the exit code.
+pub fn main_apply(args: &[String], config: &Config) -> i32 {
+    let invalid = |msg: &str| {
+        eprintln!("{msg}\n\n{APPLY_USAGE}");
+        EXIT_INVALID
+    };
+
+    let (mut size, mut timeout, mut path) = (config.size, None, None);
+    let (mut reset, mut render, mut print_solution, mut print_facelets) =
+        (false, false, false, false);
+    let (mut solver, mut heuristic) = (config.solver, config.heuristic);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--file" => match args.next() {
+                Some(file) => path = Some(file),
+                None => return invalid("Missing file"),
+            },
+            "--size" => match args.next().and_then(|size| size.parse().ok()) {
+                Some(n) if n >= 2 => size = n,
+                _ => return invalid("Invalid size, it must be at least 2"),
+            },
+            "--timeout" => match args.next().and_then(|secs| secs.parse().ok()) {
+                Some(secs) if secs >= 0.0 => timeout = Some(Duration::from_secs_f64(secs)),
+                _ => return invalid("Invalid timeout"),
+            },
+            "--solver" => match args.next().and_then(|name| Solver::from_name(name)) {
+                Some(chosen) => solver = chosen,
+                None => return invalid("Invalid solver"),
+            },
+            "--heuristic" => match args.next().and_then(|name| Heuristic::from_name(name)) {
+                Some(chosen) => heuristic = chosen,
+                None => return invalid("Invalid heuristic"),
+            },
+            "--reset" => reset = true,
+            "--render" => render = true,
+            "--solve" => print_solution = true,
+            "--facelets" => print_facelets = true,
+            "-h" | "--help" => {
+                println!("{APPLY_USAGE}");
+                return EXIT_SOLVED;
+            }
+            _ => return invalid(&format!("Invalid argument `{arg}`")),
+        }
+    }
+    let Some(path) = path else {
+        return invalid("Missing file");
+    };
+    if print_solution && !solver.supports(size) {
+        return invalid(&format!(
+            "The {} solver can't solve this size",
+            solver.name()
+        ));
+    }
+    let algorithms = match read_algorithm_file(Path::new(path), size) {
+        Ok(algorithms) => algorithms,
+        Err(e) => return invalid(&format!("Can't read {path}: {e}")),
+    };
+
+    let search_config = SearchConfig {
+        move_set: MoveSet::default_for(size),
+        time_limit: timeout,
+        fallback: true,
+        ..SearchConfig::default()
+    };
+    let mut cube = Cube::new(size);
+    for (line, algo) in algorithms {
+        if reset {
+            cube = Cube::new(size);
+        }
+        cube.apply_algorithm(algo.clone());
+        let solved = if cube.is_solved() {
+            "Solved"
+        } else {
+            "Not solved"
+        };
+        println!("Line {line}: {} ({solved})", Turn::algo_string(&algo));
+        if render {
+            println!("{cube}");
+        }
+        if print_facelets {
+            println!("{}", cube.facelet_string());
+        }
+        if print_solution {
+            match solve(&cube, &search_config, solver, heuristic).and_then(|result| result.solution)
+            {
+                Some(solution) => println!("Solution: {}", Turn::algo_string(&solution)),
+                None => println!("No solution found"),
+            }
+        }
+    }
+    EXIT_SOLVED
+}
+
+/// Turns cubes without the interface of the app, for shell pipelines: reads each cube from a line
+/// of stdin as its facelets followed by turns, applies them and the algorithm of `args`, prints
+/// the facelets of the cube to stdout, and returns the exit code.
+pub fn main_pipe(args: &[String]) -> i32 {
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{PIPE_USAGE}");
+        return EXIT_SOLVED;
+    }
+    let invalid = |msg: &str| {
+        eprintln!("{msg}\n\n{PIPE_USAGE}");
+        EXIT_INVALID
+    };
+    let then = args.join(" ");
+    let Some(then) = Turn::parse_algorithm(then.split_whitespace().collect()) else {
+        return invalid("Invalid algorithm");
+    };
+
+    for (i, line) in io::stdin().lock().lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => return invalid(&format!("Can't read the cubes: {e}")),
+        };
+        let mut words = line.split_whitespace();
+        let Some(facelets) = words.next() else {
+            continue;
+        };
+        let Some(mut cube) = Cube::from_facelets(facelets) else {
+            return invalid(&format!("Invalid facelets on line {}", i + 1));
+        };
+        let size = cube.size();
+        let algo = match Turn::parse_algorithm(words.collect()) {
+            Some(algo) => algo,
+            None => return invalid(&format!("Invalid turns on line {}", i + 1)),
+        };
+        if algo.iter().chain(&then).any(|turn| turn.get_layer() > size) {
+            return invalid(&format!(
+                "The cube on line {} doesn't have the layers of the turns",
+                i + 1
+            ));
+        }
+        cube.apply_algorithm(algo);
+        cube.apply_algorithm(then.clone());
+        println!("{}", cube.facelet_string());
+    }
+    EXIT_SOLVED
+}
+
+/// Prints the completion script of the shell named in `args`, and returns the exit code.
+pub fn main_completions(args: &[String]) -> i32 {
+    match args {
+        [arg] if arg == "-h" || arg == "--help" => {
+            println!("{COMPLETIONS_USAGE}");
+            EXIT_SOLVED
+        }
+        [shell] => match completions::script(shell) {
+            Some(script) => {
+                print!("{script}");
+                EXIT_SOLVED
+            }
+            None => {
+                eprintln!(
+                    "Unsupported shell, the shells are {}\n\n{COMPLETIONS_USAGE}",
+                    completions::SHELLS.join(", ")
+                );
+                EXIT_INVALID
+            }
+        },
+        _ => {
+            eprintln!("Missing shell\n\n{COMPLETIONS_USAGE}");
+            EXIT_INVALID
+        }
+    }
+}
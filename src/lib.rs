@@ -0,0 +1,55 @@
+//! Rubik's cubes of any size, and solvers for them.
+//!
+//! - `cube` has the cube itself, turned with `Turn`s and read or written as facelets, and
+//!   `cube::rendering` draws it in a terminal, as SVG or as the polygons of its stickers.
+//! - `search` solves it, with IDA* and its heuristics for any size, and with faster solvers for
+//!   the 2x2 and the 3x3.
+//!
+//! ```
+//! use rubiks::{
+//!     cube::{builder::Scheme, Cube},
+//!     search::{self, Heuristic, SearchConfig, Solver},
+//! };
+//!
+//! let cube = Cube::builder()
+//!     .size(2)
+//!     .scheme(Scheme::WcaWhiteTop)
+//!     .scrambled(10, 7)
+//!     .build()
+//!     .unwrap();
+//! let result = search::solve(&cube, &SearchConfig::default(), Solver::Auto, Heuristic::default());
+//! assert!(result.and_then(|result| result.solution).is_some());
+//! ```
+//!
+//! The cubes, the solvers and their results are `Send` and `Sync`, so a host can solve cubes on
+//! several threads at once, like `search::task` and the parallel IDA* do.
+//!
+//! With the `ffi` feature, `ffi` lets C create, turn and solve cubes, and with the `wasm` feature,
+//! `wasm` lets JavaScript do it in the browser. The `rubiks` binary is the app built on them, see
+//! its `--help`.
+
+#![warn(missing_docs)]
+
+pub mod cube;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod search;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+// fails to build if a type that threads share stops being `Send` or `Sync`, e.g. by holding an
+// `Rc` or a `RefCell`
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<cube::Cube>();
+    assert_send_sync::<cube::builder::CubeBuilder>();
+    assert_send_sync::<cube::rendering::CubeRender>();
+    assert_send_sync::<search::SearchConfig>();
+    assert_send_sync::<search::SearchResult>();
+    assert_send_sync::<search::Heuristic>();
+    assert_send_sync::<search::Solver>();
+    assert_send_sync::<search::pdb::PatternDatabase>();
+    #[cfg(not(target_arch = "wasm32"))]
+    assert_send_sync::<search::task::SolveHandle>();
+    assert_send_sync::<search::report::SolveReport>();
+};
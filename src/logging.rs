@@ -0,0 +1,62 @@
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Logger that writes the messages of the `log` crate to stderr, like `warning: ...`, so that
+/// they don't mix with the output of the commands on stdout.
+struct StderrLogger;
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let level = match record.level() {
+                log::Level::Warn => "warning".to_string(),
+                level => level.as_str().to_lowercase(),
+            };
+            eprintln!("{level}: {}", record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Removes the flags of the log level from `args` and returns the level: warnings and errors by
+/// default, only errors with `-q` or `--quiet`, and more details with each `-v` (`-vv` and
+/// `-vvv` for the debug and the trace messages), or `--verbose` for `-v`.
+pub fn level_from_args(args: &mut Vec<String>) -> LevelFilter {
+    let mut verbosity = 0;
+    let mut quiet = false;
+    args.retain(|arg| match arg.as_str() {
+        "-q" | "--quiet" => {
+            quiet = true;
+            false
+        }
+        "--verbose" => {
+            verbosity += 1;
+            false
+        }
+        arg if arg.len() > 1
+            && arg
+                .strip_prefix('-')
+                .is_some_and(|v| v.chars().all(|c| c == 'v')) =>
+        {
+            verbosity += arg.len() - 1;
+            false
+        }
+        _ => true,
+    });
+    match (quiet, verbosity) {
+        (true, _) => LevelFilter::Error,
+        (false, 0) => LevelFilter::Warn,
+        (false, 1) => LevelFilter::Info,
+        (false, 2) => LevelFilter::Debug,
+        (false, _) => LevelFilter::Trace,
+    }
+}
+
+/// Logs the messages up to `level` to stderr from now on.
+pub fn init(level: LevelFilter) {
+    log::set_logger(&StderrLogger).expect("Error when setting the logger");
+    log::set_max_level(level);
+}
@@ -1,22 +1,128 @@
+#[cfg(not(target_arch = "wasm32"))]
 mod app;
-mod cube;
-mod search;
+#[cfg(not(target_arch = "wasm32"))]
+mod batch;
+#[cfg(not(target_arch = "wasm32"))]
+mod completions;
+#[cfg(not(target_arch = "wasm32"))]
+mod config;
+#[cfg(feature = "gui")]
+mod gui;
+#[cfg(not(target_arch = "wasm32"))]
+mod headless;
+#[cfg(not(target_arch = "wasm32"))]
+mod logging;
+#[cfg(not(target_arch = "wasm32"))]
+mod practice;
+#[cfg(not(target_arch = "wasm32"))]
+mod profile;
+#[cfg(not(target_arch = "wasm32"))]
+mod scramble;
+#[cfg(not(target_arch = "wasm32"))]
+mod session;
 mod stats;
 
-use app::main_app_loop;
+use std::num::NonZeroUsize;
+
+use rubiks::search::{self, Heuristic};
 use stats::{heuristic_stats::check_heuristic, idastar_stats::check_idastar};
 
+/// Number of heuristic values cached by the cached experiments.
+const HEURISTIC_CACHE_SIZE: usize = 1 << 20;
+
 /// main function, called when we starts.
+#[cfg(not(target_arch = "wasm32"))]
 fn main() {
     // check for feature stats flag
     if !cfg!(feature = "stats") {
-        // run the rubiks cube app
-        main_app_loop();
+        let mut args: Vec<String> = std::env::args().collect();
+        // -q and -v set how much is logged to stderr, see `logging::level_from_args`
+        logging::init(logging::level_from_args(&mut args));
+        // the completions don't depend on the config, so a broken config doesn't break them
+        if args.get(1).is_some_and(|arg| arg == "completions") {
+            std::process::exit(headless::main_completions(&args[2..]));
+        }
+        // the settings are read from `--config PATH`, or from the config of the user if there's
+        // one, see `Config::load`
+        let config_path = match args.iter().position(|arg| arg == "--config") {
+            Some(i) if i + 1 < args.len() => {
+                let path = args.remove(i + 1);
+                args.remove(i);
+                Some(path)
+            }
+            Some(_) => {
+                eprintln!("The config must be the path of a file, like `--config config.toml`");
+                std::process::exit(headless::EXIT_INVALID);
+            }
+            None => None,
+        };
+        let config = match config::Config::load(config_path.as_deref().map(std::path::Path::new)) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Can't read the config: {e}");
+                std::process::exit(headless::EXIT_INVALID);
+            }
+        };
+        config.palette.set_current();
+        // solve, scramble, apply algorithms or turn cubes read from stdin without the interface
+        // if it's asked for, e.g. by a script
+        if args.get(1).is_some_and(|arg| arg == "solve") {
+            std::process::exit(headless::main_solve(&args[2..], &config));
+        }
+        if args.get(1).is_some_and(|arg| arg == "scramble") {
+            std::process::exit(headless::main_scramble(&args[2..], &config));
+        }
+        if args.get(1).is_some_and(|arg| arg == "apply") {
+            std::process::exit(headless::main_apply(&args[2..], &config));
+        }
+        if args.get(1).is_some_and(|arg| arg == "pipe") {
+            std::process::exit(headless::main_pipe(&args[2..]));
+        }
+        // draw without colors, e.g. on terminals that don't have them
+        if args.iter().any(|arg| arg == "--no-color") {
+            rubiks::cube::palette::ColorMode::NoColor.set_current();
+        }
+        // the size of the cube is the one of the config unless `--size N` is given
+        let size = match args.iter().position(|arg| arg == "--size") {
+            Some(i) => match args.get(i + 1).and_then(|size| size.parse().ok()) {
+                Some(size) if size >= 2 => size,
+                _ => {
+                    eprintln!("The size must be a number of at least 2, like `--size 3`");
+                    std::process::exit(headless::EXIT_INVALID);
+                }
+            },
+            None => config.size,
+        };
+        // run the rubiks cube app, in a window if it's asked for and built with the gui feature
+        #[cfg(feature = "gui")]
+        if args.iter().any(|arg| arg == "--gui") {
+            gui::main_gui_loop(size, &config);
+            return;
+        }
+        app::main_app_loop(size, &config);
     } else {
         // run experiments
-        check_idastar(&search::single_l0, "Single L0");
-        check_idastar(&search::all_l0, "All L0");
+        // the statistics name the heuristics like `--heuristic` does
+        check_idastar(&search::single_l0, None, Heuristic::SingleL0.name());
+        check_idastar(&search::all_l0, None, Heuristic::AllL0.name());
+        check_idastar(
+            &search::all_l0,
+            NonZeroUsize::new(HEURISTIC_CACHE_SIZE),
+            Heuristic::AllL0.name(),
+        );
+        check_idastar(
+            &search::walking_distance,
+            None,
+            Heuristic::Coordinate.name(),
+        );
+        check_idastar(&search::no_heuristic, None, "none");
         check_heuristic(&search::single_l0, "Single L0");
         check_heuristic(&search::all_l0, "All L0");
+        check_heuristic(&search::walking_distance, "Walking Distance");
     }
 }
+
+/// In the browser, the page calls the functions of the `rubiks::wasm` module of the library
+/// instead.
+#[cfg(target_arch = "wasm32")]
+fn main() {}
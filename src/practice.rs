@@ -0,0 +1,130 @@
+use std::{
+    fmt::Display,
+    io,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use csv::{Reader, Writer};
+
+/// A scramble solved by hand, see `SessionLog::solve_time`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Solve {
+    /// When the solve finished, in seconds since the Unix epoch.
+    pub finished_at: u64,
+    pub time: Duration,
+}
+
+/// Every solve timed by the app, oldest first. It's kept in a file so that the averages carry
+/// over from one session to the next.
+#[derive(Debug, Default)]
+pub struct SolveHistory {
+    solves: Vec<Solve>,
+}
+impl SolveHistory {
+    /// Reads the solves saved at `path` by `save`, or starts without any if there's no file
+    /// there.
+    pub fn load(path: &Path) -> io::Result<SolveHistory> {
+        if !path.exists() {
+            return Ok(SolveHistory::default());
+        }
+        let mut csv_reader = Reader::from_path(path)?;
+        let mut solves = Vec::new();
+        for record in csv_reader.records() {
+            let record = record?;
+            let finished_at = record.get(0).and_then(|field| field.parse().ok());
+            let time = record
+                .get(1)
+                .and_then(|field| field.parse().ok())
+                .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok());
+            let (Some(finished_at), Some(time)) = (finished_at, time) else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "invalid solve: {}",
+                        record.iter().collect::<Vec<_>>().join(",")
+                    ),
+                ));
+            };
+            solves.push(Solve { finished_at, time });
+        }
+        Ok(SolveHistory { solves })
+    }
+
+    /// Writes the solves to a CSV file at `path`, with when each one finished and its time in
+    /// seconds.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut csv_writer = Writer::from_path(path)?;
+        csv_writer.write_record(["finished_at", "seconds"])?;
+        for solve in &self.solves {
+            csv_writer.write_record([
+                solve.finished_at.to_string(),
+                format!("{:.3}", solve.time.as_secs_f64()),
+            ])?;
+        }
+        csv_writer.flush()
+    }
+
+    /// Adds a solve that just finished, and returns whether it's the fastest one so far.
+    pub fn add(&mut self, time: Duration) -> bool {
+        let personal_best = self.best().is_none_or(|best| time < best);
+        let finished_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_secs());
+        self.solves.push(Solve { finished_at, time });
+        personal_best
+    }
+
+    #[allow(dead_code)]
+    pub fn solves(&self) -> &[Solve] {
+        &self.solves
+    }
+
+    /// Returns the time of the fastest solve.
+    pub fn best(&self) -> Option<Duration> {
+        self.solves.iter().map(|solve| solve.time).min()
+    }
+
+    /// Returns the average of the last `n` solves (see `average`), if there are that many. `n`
+    /// must be at least 3.
+    pub fn current_average(&self, n: usize) -> Option<Duration> {
+        let start = self.solves.len().checked_sub(n)?;
+        Some(average(&self.solves[start..]))
+    }
+
+    /// Returns the fastest average of `n` solves in a row. `n` must be at least 3.
+    pub fn best_average(&self, n: usize) -> Option<Duration> {
+        self.solves.windows(n).map(average).min()
+    }
+}
+impl Display for SolveHistory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Solves: {}\tBest: {}\tao5: {} (best {})\tao12: {} (best {})",
+            self.solves.len(),
+            format_time(self.best()),
+            format_time(self.current_average(5)),
+            format_time(self.best_average(5)),
+            format_time(self.current_average(12)),
+            format_time(self.best_average(12)),
+        )
+    }
+}
+
+/// Returns the mean time of `solves` without the fastest and the slowest one, like the averages
+/// of competitions.
+fn average(solves: &[Solve]) -> Duration {
+    let mut times: Vec<Duration> = solves.iter().map(|solve| solve.time).collect();
+    times.sort();
+    let counted = &times[1..times.len() - 1];
+    counted.iter().sum::<Duration>() / counted.len() as u32
+}
+
+/// Returns `time` in seconds with 2 decimals, or "-" if there's no time.
+pub fn format_time(time: Option<Duration>) -> String {
+    match time {
+        Some(time) => format!("{:.2}", time.as_secs_f64()),
+        None => "-".to_string(),
+    }
+}
@@ -0,0 +1,108 @@
+use std::{collections::BTreeMap, fmt::Display, fs, io, path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use rubiks::cube::Turn;
+
+use crate::practice::format_time;
+
+/// Number of algorithms shown by the `Display` of a profile, the most used first.
+const TOP_ALGORITHMS: usize = 5;
+
+/// Lifetime statistics of the user of the app, kept in a JSON file so that they add up over the
+/// sessions: the solves by hand, their turns, the best time of each size and the algorithms
+/// typed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    /// Number of scrambles solved by hand.
+    pub solves: usize,
+    /// Number of turns of the solves by hand, from the scramble to solved.
+    pub total_moves: usize,
+    /// Fastest solve of each size, in seconds.
+    pub best_times: BTreeMap<usize, f64>,
+    /// Number of times each algorithm was typed, by its turns like `R U R' U'`.
+    pub algorithms: BTreeMap<String, usize>,
+}
+impl Profile {
+    /// Reads the profile saved at `path` by `save`, or starts a new one if there's no file
+    /// there.
+    pub fn load(path: &Path) -> io::Result<Profile> {
+        if !path.exists() {
+            return Ok(Profile::default());
+        }
+        serde_json::from_str(&fs::read_to_string(path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Writes the profile to a JSON file at `path`.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json + "\n")
+    }
+
+    /// Counts a scramble of a cube of size `size` solved by hand in `time` with `moves` turns.
+    pub fn add_solve(&mut self, size: usize, time: Duration, moves: usize) {
+        self.solves += 1;
+        self.total_moves += moves;
+        let best = self.best_times.entry(size).or_insert(f64::INFINITY);
+        *best = best.min(time.as_secs_f64());
+    }
+
+    /// Counts an algorithm typed by the user. Single turns aren't algorithms, so they aren't
+    /// counted.
+    pub fn add_algorithm(&mut self, algo: &[Turn]) {
+        if algo.len() > 1 {
+            *self.algorithms.entry(Turn::algo_string(algo)).or_default() += 1;
+        }
+    }
+
+    /// Returns the algorithms typed the most, with how many times, the most used first.
+    pub fn most_used_algorithms(&self, n: usize) -> Vec<(&str, usize)> {
+        let mut algorithms: Vec<(&str, usize)> = self
+            .algorithms
+            .iter()
+            .map(|(algo, &count)| (algo.as_str(), count))
+            .collect();
+        // the algorithms used as often keep their alphabetical order
+        algorithms.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        algorithms.truncate(n);
+        algorithms
+    }
+}
+impl Display for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Solves: {}\tTotal moves: {}",
+            self.solves, self.total_moves
+        )?;
+        if self.solves > 0 {
+            write!(
+                f,
+                "\tMoves per solve: {:.1}",
+                self.total_moves as f64 / self.solves as f64
+            )?;
+        }
+        let best_times: Vec<String> = self
+            .best_times
+            .iter()
+            .map(|(size, &seconds)| {
+                let time = Duration::try_from_secs_f64(seconds).ok();
+                format!("{size}x{size} {}", format_time(time))
+            })
+            .collect();
+        if !best_times.is_empty() {
+            write!(f, "\nBest times: {}", best_times.join(", "))?;
+        }
+        let algorithms: Vec<String> = self
+            .most_used_algorithms(TOP_ALGORITHMS)
+            .iter()
+            .map(|(algo, count)| format!("{algo} ({count}x)"))
+            .collect();
+        if !algorithms.is_empty() {
+            write!(f, "\nMost used algorithms: {}", algorithms.join(", "))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use std::{
+    fmt::Display,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use rubiks::{
+    cube::{cubie::CubieCube, Cube, Turn},
+    search::{coordinate, two_phase, MoveSet, SearchConfig},
+};
+
+/// Fewest turns of an official scramble. Random states that are solved in fewer turns are drawn
+/// again, like in competitions.
+const MIN_OFFICIAL_LENGTH: usize = 2;
+/// Number of random turns of the daily scramble when it isn't given, which doesn't depend on the
+/// config so that everyone gets the same scramble.
+const DAILY_LENGTH: usize = 25;
+
+/// A day of the calendar, in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+impl Date {
+    /// Returns the date of today in UTC, so that it's the same day everywhere.
+    pub fn today() -> Date {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_secs());
+        Date::from_days((secs / 86400) as i64)
+    }
+
+    /// Returns the date `days` days after 1970-01-01 (Howard Hinnant's `civil_from_days`).
+    fn from_days(days: i64) -> Date {
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let day_of_era = z.rem_euclid(146097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        // months start in March, so that the leap day is at the end of the year
+        let month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * month + 2) / 5 + 1) as u32;
+        let month = if month < 10 { month + 3 } else { month - 9 } as u32;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Date { year, month, day }
+    }
+
+    /// Returns the seed of the daily scramble of the date, which reads like the date, e.g.
+    /// 20240131, so that the scramble can be made again with `--seed`.
+    pub fn seed(&self) -> u64 {
+        self.year as u64 * 10000 + u64::from(self.month) * 100 + u64::from(self.day)
+    }
+}
+impl Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// How to scramble the cube, from the arguments of a scramble, see `ScrambleOptions::parse`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScrambleOptions {
+    /// Number of random turns, or the default length of the config if it isn't given.
+    pub length: Option<usize>,
+    /// Seed of the random numbers, so that a seed always gives the same scramble.
+    pub seed: Option<u64>,
+    /// Whether to scramble to a random state instead, see `random_state_scramble`.
+    pub official: bool,
+    /// The date of the daily scramble, which everyone gets on the same day: the seed is the date
+    /// and the default length is `DAILY_LENGTH`.
+    pub daily: Option<Date>,
+}
+impl ScrambleOptions {
+    /// Parses the arguments of a scramble, like `25 --seed 7` or `--official`: the number of
+    /// turns (also given with `--length N`), `--seed S`, `--official` and `--daily`. An official
+    /// scramble doesn't take a number of turns, and the daily scramble doesn't take a seed.
+    pub fn parse(args: &[&str]) -> Result<ScrambleOptions, String> {
+        let mut options = ScrambleOptions::default();
+        let mut args = args.iter();
+        while let Some(&arg) = args.next() {
+            match arg {
+                "--seed" => match args.next().and_then(|seed| seed.parse().ok()) {
+                    Some(seed) => options.seed = Some(seed),
+                    None => return Err("Invalid seed".to_string()),
+                },
+                "--official" => options.official = true,
+                "--daily" => options.daily = Some(Date::today()),
+                "--length" => match args.next().and_then(|length| length.parse().ok()) {
+                    Some(length) => options.length = Some(length),
+                    None => return Err("Invalid length".to_string()),
+                },
+                length => match length.parse() {
+                    Ok(length) => options.length = Some(length),
+                    Err(_) => return Err(format!("Invalid argument `{length}`")),
+                },
+            }
+        }
+        if options.official && options.length.is_some() {
+            return Err("An official scramble doesn't take a number of turns".to_string());
+        }
+        if let Some(date) = options.daily {
+            if options.seed.is_some() {
+                return Err("The daily scramble doesn't take a seed".to_string());
+            }
+            options.seed = Some(date.seed());
+        }
+        Ok(options)
+    }
+
+    /// Scrambles `cube` and returns the turns used, with `default_length` random turns if the
+    /// options don't give a number (or `DAILY_LENGTH` for the daily scramble). Returns the
+    /// message to show instead if an official scramble is asked for a size other than 2 and 3.
+    ///
+    /// The random numbers come from `ChaCha8Rng`, whose output for a seed is stable across
+    /// versions, unlike `StdRng`, so that a seed gives the same scramble in every build.
+    pub fn scramble(&self, cube: &mut Cube, default_length: usize) -> Result<Vec<Turn>, String> {
+        let mut rng = match self.seed {
+            Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+            None => ChaCha8Rng::from_entropy(),
+        };
+        if !self.official {
+            let default_length = match self.daily {
+                Some(_) => DAILY_LENGTH,
+                None => default_length,
+            };
+            let length = self.length.unwrap_or(default_length);
+            return Ok(cube.scramble_with_rng(length, &mut rng));
+        }
+        let algo = random_state_scramble(cube.size(), &mut rng)
+            .ok_or_else(|| "Official scrambles are only for the 2x2 and the 3x3".to_string())?;
+        cube.apply_algorithm(algo.clone());
+        Ok(algo)
+    }
+}
+
+/// Returns the turns that take a solved 2x2 or 3x3 to a random state, where every state is as
+/// likely as the others, like the scrambles of competitions. Random turns can't do that in a
+/// reasonable number of turns.
+///
+/// The turns reverse a solution of the random state: the two-phase algorithm for the 3x3, and
+/// an optimal search turning the right, up and front faces for the 2x2. Returns `None` for other
+/// sizes.
+pub fn random_state_scramble(size: usize, rng: &mut impl Rng) -> Option<Vec<Turn>> {
+    let config = SearchConfig {
+        move_set: MoveSet::HalfTurnRUF,
+        ..SearchConfig::default()
+    };
+    loop {
+        let cube = match size {
+            2 | 3 => CubieCube::random(rng).to_cube(size),
+            _ => return None,
+        };
+        let result = match size {
+            2 => coordinate::solve_2x2(&cube, &config),
+            _ => two_phase::solve_two_phase(&cube, two_phase::DEFAULT_MAX_LENGTH, config.verify),
+        };
+        let solution = result
+            .and_then(|result| result.solution)
+            .expect("Error when solving a random state");
+        if solution.len() >= MIN_OFFICIAL_LENGTH {
+            return Some(Turn::inverse_algorithm(&solution));
+        }
+    }
+}
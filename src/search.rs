@@ -1,207 +1,1550 @@
+//! Solvers of the cube: `solve` with the `Solver` and the `Heuristic` chosen by name, IDA* and
+//! its heuristics for any size, and the solvers of the 2x2 and the 3x3 in the submodules. A solve
+//! is written for other programs as a `report::SolveReport`.
+
+pub mod anytime;
+pub mod checkpoint;
+pub mod coordinate;
+pub mod lbl;
+pub mod pdb;
+mod persist;
+pub mod report;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod task;
+pub mod two_phase;
+
 use std::{
     cell::RefCell,
+    collections::{HashMap, HashSet, VecDeque},
     fmt::Display,
-    io::{stdout, Write},
-    rc::Rc,
-    time::{Duration, Instant},
+    io::{self, stdout, Write},
+    mem,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    slice,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+    time::Duration,
 };
 
-use crate::cube::{Cube, FaceDir, Turn, TurnDir};
+use lru::LruCache;
+use rayon::prelude::*;
+use web_time::Instant;
 
-#[derive(Clone)]
-struct Node {
-    state: Cube,
-    prev_action: Option<Turn>,
-    parent: Option<Rc<RefCell<Node>>>,
-    path_cost: usize,
-    evaluation: Option<usize>, // acts as a cache to avoid recalculating heuristic
+use crate::cube::{cubie::CubieCube, Cube, FaceDir, Turn, TurnDir};
+use checkpoint::SearchCheckpoint;
+use pdb::{Pattern, PatternDatabase};
+use task::SearchProgress;
+
+/// Path of the exact depth table of the 2x2, see `depth_table_2x2`.
+pub const DEPTH_TABLE_2X2_PATH: &str = "depth_table_2x2.bin";
+
+/// The turns that the solvers use as actions.
+///
+/// Only the two directions of right, up, and front are used, since the other turns can be applied
+/// by these 6 turns (on a 2x2, where they leave the down left back corner in place).
+const SOLVER_TURNS: [Turn; 6] = [
+    Turn::new(FaceDir::Right, TurnDir::Clockwise),
+    Turn::new(FaceDir::Right, TurnDir::CounterClockwise),
+    Turn::new(FaceDir::Up, TurnDir::Clockwise),
+    Turn::new(FaceDir::Up, TurnDir::CounterClockwise),
+    Turn::new(FaceDir::Front, TurnDir::Clockwise),
+    Turn::new(FaceDir::Front, TurnDir::CounterClockwise),
+];
+
+const QUARTER_TURN_DIRS: [TurnDir; 2] = [TurnDir::Clockwise, TurnDir::CounterClockwise];
+const ALL_TURN_DIRS: [TurnDir; 3] = [TurnDir::Clockwise, TurnDir::CounterClockwise, TurnDir::Half];
+const RUF_FACES: [FaceDir; 3] = [FaceDir::Right, FaceDir::Up, FaceDir::Front];
+const RU_FACES: [FaceDir; 2] = [FaceDir::Right, FaceDir::Up];
+
+/// Returns every turn of `faces` in every direction of `turn_dirs`, grouped by face.
+const fn face_turns<const N: usize>(faces: &[FaceDir], turn_dirs: &[TurnDir]) -> [Turn; N] {
+    let mut turns = [Turn::new(FaceDir::Up, TurnDir::Clockwise); N];
+    let mut i = 0;
+    while i < N {
+        turns[i] = Turn::new(faces[i / turn_dirs.len()], turn_dirs[i % turn_dirs.len()]);
+        i += 1;
+    }
+    turns
 }
-impl Node {
-    fn new_root(init_cube: Cube) -> Node {
-        Node {
-            state: init_cube,
-            prev_action: None,
-            parent: None,
-            path_cost: 0,
-            evaluation: None,
+
+const HALF_TURN_RUF_TURNS: [Turn; 9] = face_turns(&RUF_FACES, &ALL_TURN_DIRS);
+const QUARTER_TURNS: [Turn; 12] = face_turns(&FaceDir::FACELET_ORDER, &QUARTER_TURN_DIRS);
+const HALF_TURNS: [Turn; 18] = face_turns(&FaceDir::FACELET_ORDER, &ALL_TURN_DIRS);
+const RU_TURNS: [Turn; 6] = face_turns(&RU_FACES, &ALL_TURN_DIRS);
+
+/// The set of turns that a search may use as actions.
+///
+/// Note that with the 2x2, move sets that turn every face don't keep any corner in place, so the
+/// cube can end up solved in any orientation. `single_l0` only knows one solved orientation, so
+/// use `all_l0` or `walking_distance` with them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoveSet {
+    /// Quarter turns of right, up, and front, see `SOLVER_TURNS`.
+    QuarterTurnRUF,
+    /// Quarter and half turns of right, up, and front. Enough for the 2x2 in the half turn metric.
+    HalfTurnRUF,
+    /// Quarter turns of every face.
+    QuarterTurn,
+    /// Quarter and half turns of every face, the usual move set of the 3x3.
+    HalfTurn,
+    /// Quarter and half turns of right and up, which only reach the states of the <R, U> subgroup.
+    RU,
+    /// Quarter and half turns of the given faces and slices, which only reach the states of the
+    /// subgroup that they generate, e.g. <M, U>. See `MoveSet::generated_by`.
+    Generated(Vec<Turn>),
+}
+impl MoveSet {
+    /// Returns true if rotating the whole cube maps the turns of the set to turns of the set, up
+    /// to a whole cube rotation, so that symmetric states take as many turns to solve (see
+    /// `Cube::symmetric_hash`). On a 2x2, turning a face is the same as turning the opposite face
+    /// and rotating the cube, so one face per axis is enough.
+    pub fn is_rotation_symmetric(&self, size: usize) -> bool {
+        match self {
+            MoveSet::QuarterTurn | MoveSet::HalfTurn => true,
+            MoveSet::QuarterTurnRUF | MoveSet::HalfTurnRUF => size == 2,
+            MoveSet::RU | MoveSet::Generated(_) => false,
+        }
+    }
+
+    /// Returns the move set of the quarter and half turns of the layers of `generators`, whatever
+    /// their directions, e.g. `R U` for 2-generator solutions like the ones of many last layer
+    /// algorithms, or `M U` for the edges of the last six edges method.
+    pub fn generated_by(generators: &[Turn]) -> MoveSet {
+        let mut turns: Vec<Turn> = Vec::with_capacity(ALL_TURN_DIRS.len() * generators.len());
+        for generator in generators {
+            let is_new = !turns.iter().any(|turn| {
+                turn.get_face_dir() == generator.get_face_dir()
+                    && turn.get_layer() == generator.get_layer()
+            });
+            if is_new {
+                turns.extend(ALL_TURN_DIRS.map(|turn_dir| {
+                    Turn::new_slice(generator.get_face_dir(), turn_dir, generator.get_layer())
+                }));
+            }
         }
+        MoveSet::Generated(turns)
     }
 
-    fn is_goal(&self) -> bool {
-        self.state.is_solved()
+    /// Returns the turns of this move set.
+    pub fn turns(&self) -> &[Turn] {
+        match self {
+            MoveSet::QuarterTurnRUF => &SOLVER_TURNS,
+            MoveSet::HalfTurnRUF => &HALF_TURN_RUF_TURNS,
+            MoveSet::QuarterTurn => &QUARTER_TURNS,
+            MoveSet::HalfTurn => &HALF_TURNS,
+            MoveSet::RU => &RU_TURNS,
+            MoveSet::Generated(turns) => turns,
+        }
+    }
+
+    /// Returns the move set that solves a cube of size `size` in the fewest turns: the right, up,
+    /// and front quarter turns for the 2x2, and every face turn otherwise.
+    pub fn default_for(size: usize) -> MoveSet {
+        match size {
+            2 => MoveSet::QuarterTurnRUF,
+            _ => MoveSet::HalfTurn,
+        }
     }
+}
 
-    fn get_path(&self) -> Vec<Turn> {
-        if let Some(parent_node) = &self.parent {
-            let mut path = parent_node.borrow().get_path();
-            if let Some(action) = &self.prev_action {
-                path.push(action.clone());
+/// The state that a search looks for.
+///
+/// Other than `Solved`, these are the stages of the layer by layer method, so that a search can
+/// produce the solution of a single stage. The faces are given by their direction, e.g.
+/// `FaceDir::Down` is the white face of a new cube.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Goal {
+    /// The whole cube is solved.
+    Solved,
+    /// The face is a single color.
+    Face(FaceDir),
+    /// The layer under the face is solved.
+    FirstLayer(FaceDir),
+    /// Every layer but the one opposite to the face is solved, and the opposite face is a single
+    /// color. This is the state after orienting the last layer (OLL), which only leaves
+    /// permuting the last layer (PLL).
+    LastLayerOriented(FaceDir),
+    /// The cube has the same stickers as a target cube (see `Goal::state`). This lets a search
+    /// look for any state, like a pattern.
+    State {
+        /// The Zobrist hash of the stickers of the target, which rules out most states before
+        /// their stickers are packed and compared.
+        hash: u64,
+        /// The stickers of the target, packed with `Cube::packed_facelets`.
+        facelets: Arc<[u64]>,
+    },
+}
+impl Goal {
+    /// Returns true if `cube` is in a state that this goal looks for.
+    pub fn is_reached(&self, cube: &Cube) -> bool {
+        match self {
+            Goal::Solved => cube.is_solved(),
+            Goal::Face(face_dir) => cube.is_face_solved(face_dir),
+            Goal::FirstLayer(face_dir) => cube.are_layers_solved(face_dir, 1),
+            Goal::LastLayerOriented(face_dir) => {
+                cube.are_layers_solved(face_dir, cube.size() - 1)
+                    && cube.is_face_solved(&face_dir.get_opposite())
+            }
+            Goal::State { hash, facelets } => {
+                cube.zobrist_hash() == *hash && cube.packed_facelets() == **facelets
             }
-            return path;
         }
-        Vec::new()
     }
 
-    fn get_evaluation(&mut self, heuristic_function: &dyn Fn(&Cube) -> f32) -> usize {
-        if let Some(f) = self.evaluation {
-            return f;
+    /// Returns the goal of reaching exactly the stickers of `target`.
+    pub fn state(target: &Cube) -> Goal {
+        Goal::State {
+            hash: target.zobrist_hash(),
+            facelets: target.packed_facelets().into(),
         }
-        let new_h = heuristic_function(&self.state).ceil() as usize;
-        match &self.parent {
-            None => new_h,
-            Some(ptr) => {
-                let mut parent = ptr.borrow_mut();
-                let new_f = self.path_cost + new_h;
+    }
+}
+
+/// Well known patterns of the 3x3, with an algorithm that makes them from the solved cube.
+pub const PATTERNS_3X3: [(&str, &str); 5] = [
+    ("Checkerboard", "R2 L2 U2 D2 F2 B2"),
+    ("Plus minus", "U2 R2 L2 U2 R2 L2"),
+    ("Tetris", "L R F B U' D' L' R'"),
+    ("Six spots", "U D' R L' F B' U D'"),
+    ("Cube in a cube", "F L F U' R U F2 L2 U' L' B D' B' L2 U"),
+];
+
+/// Searches for the shortest algorithm that makes `target` from a solved cube of the same size,
+/// with `parallel_idastar`. `target` can be any state, usually a pattern like the ones of
+/// `PATTERNS_3X3`.
+///
+/// The goal of `config` is replaced with `Goal::state(target)`. Since the target isn't solved, the
+/// heuristics of this module don't apply, so the search uses the Hamming distance to the target
+/// divided by the stickers a turn can move, which is admissible for any target.
+pub fn find_pattern_algorithm(target: &Cube, config: &SearchConfig) -> SearchResult {
+    let size = target.size();
+    // the target is fixed, so pack it once rather than at every node
+    let packed_target = target.packed_facelets();
+    let heuristic = |cube: &Cube| {
+        Cube::packed_hamming_distance(&cube.packed_facelets(), &packed_target) as f32
+            / max_stickers_moved_per_turn(size)
+    };
+    let config = SearchConfig {
+        goal: Goal::state(target),
+        ..config.clone()
+    };
+    parallel_idastar(Cube::new(size), &heuristic, &config)
+}
 
-                let parent_f = parent.get_evaluation(heuristic_function);
+/// Well known algorithms that a search can use as macros (see `SearchConfig::macros`): the sexy
+/// move, Sune, and the T permutation.
+pub const MACRO_LIBRARY: [&str; 3] = [
+    "R U R' U'",
+    "R U R' U R U2 R'",
+    "R U R' U' R' F R2 U' R' U' R U R' F'",
+];
 
-                // ensure that f is monotone (Korf pg. 104)
-                let f = usize::max(new_f, parent_f);
-                self.evaluation = Some(f);
-                f
+/// Returns the algorithms of `MACRO_LIBRARY` as macros of a search.
+pub fn library_macros() -> Vec<Vec<Turn>> {
+    MACRO_LIBRARY
+        .iter()
+        .map(|algo| {
+            Turn::parse_algorithm(algo.split_whitespace().collect())
+                .expect("Error when parsing algorithm")
+        })
+        .collect()
+}
+
+/// By default, IDA* gives up when the limit exceeds this many turns.
+const DEFAULT_GIVE_UP_LIMIT: usize = 28;
+
+/// Number of nodes expanded between two checks of the deadline and the node budget of a search.
+const STOP_CHECK_INTERVAL: usize = 1024;
+
+/// Time between two prints of the progress line of a search, see `ProgressLine`.
+const PROGRESS_PRINT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Parameters of `idastar` and `parallel_idastar`.
+#[derive(Clone)]
+pub struct SearchConfig {
+    /// The turns to expand each node with.
+    pub move_set: MoveSet,
+    /// Algorithms that are applied as a single move in addition to the turns of `move_set`, like
+    /// the ones of `MACRO_LIBRARY`. A macro counts as one move for the limit, so solutions that
+    /// use them are found in much shallower trees, but they aren't optimal: the search stops at
+    /// the fewest moves, which can be more turns than another solution, so the results are marked
+    /// `SearchResult::possibly_suboptimal`.
+    pub macros: Vec<Vec<Turn>>,
+    /// The state to search for.
+    pub goal: Goal,
+    /// The search gives up when no solution has at most this many moves. The limits of a
+    /// weighted search are weighted too, see `SearchConfig::max_limit`.
+    pub give_up_limit: usize,
+    /// The search is interrupted once it has visited this many nodes. Threads of a parallel
+    /// search only share their counts every `STOP_CHECK_INTERVAL` nodes, so it can be slightly
+    /// exceeded.
+    pub node_budget: Option<usize>,
+    /// The search is interrupted once its queues and caches take about this many bytes. IDA*
+    /// only uses memory for its heuristic caches, which are shrunk to fit instead.
+    pub memory_budget: Option<usize>,
+    /// The search is interrupted once it has run for this long.
+    pub time_limit: Option<Duration>,
+    /// The search is interrupted once this is set, e.g. from a Ctrl+C handler.
+    pub cancel_token: Option<Arc<AtomicBool>>,
+    /// If given, heuristic values are cached (up to that many states) and reused across
+    /// iterations instead of being recomputed.
+    pub heuristic_cache_size: Option<NonZeroUsize>,
+    /// Prints a line with the limit of the iteration, the nodes visited, the nodes visited per
+    /// second, the elapsed time and the time left at the limit, which is rewritten as the search
+    /// goes.
+    pub print_progress: bool,
+    /// If given, the limit, the iterations and the nodes visited are stored there at every
+    /// iteration, so that another thread can follow the search.
+    pub progress: Option<Arc<SearchProgress>>,
+    /// Weight of the heuristic in the evaluation `f = g + weight * h`. Weights above 1 find
+    /// solutions faster on hard scrambles, but the solutions can be longer than optimal, by at
+    /// most this factor.
+    pub weight: f32,
+    /// Keeps searching at the bound of the first solution until this many solutions are found,
+    /// or the bound is exhausted. With an admissible heuristic and a weight of 1, these are
+    /// distinct optimal solutions.
+    pub max_solutions: usize,
+    /// Checks the solutions before returning them with `SearchResult::verify`. On by default in
+    /// debug builds.
+    pub verify: bool,
+    /// If given, the progress of the search is written there at every iteration (and after every
+    /// subtree of a parallel search), and a checkpoint of the same search is resumed from its
+    /// limit instead of starting over. The checkpoint is removed once the search ends, but kept
+    /// if it's interrupted. The node counts of the result continue from the checkpoint, but the
+    /// node budget only applies to the nodes visited since resuming.
+    pub checkpoint_path: Option<PathBuf>,
+    /// When IDA* gives up on solving a 3x3, solves it with the two-phase algorithm instead, or
+    /// the layer by layer method if that fails, so that the result still has a (possibly
+    /// suboptimal) solution. Off by default. Only searches with the `MoveSet::HalfTurn` move set
+    /// and no macros fall back, since the solutions of the fallback turn every face, and
+    /// interrupted searches don't fall back.
+    pub fallback: bool,
+}
+impl Default for SearchConfig {
+    /// Searches for the solved state with the right, up, and front quarter turns, without any
+    /// budget.
+    fn default() -> Self {
+        SearchConfig {
+            move_set: MoveSet::QuarterTurnRUF,
+            macros: Vec::new(),
+            goal: Goal::Solved,
+            give_up_limit: DEFAULT_GIVE_UP_LIMIT,
+            node_budget: None,
+            memory_budget: None,
+            time_limit: None,
+            cancel_token: None,
+            heuristic_cache_size: None,
+            print_progress: false,
+            progress: None,
+            weight: 1.0,
+            max_solutions: 1,
+            verify: cfg!(debug_assertions),
+            checkpoint_path: None,
+            fallback: false,
+        }
+    }
+}
+impl SearchConfig {
+    /// Returns true if the solutions of this search can be longer than optimal, because of the
+    /// weight or the macros.
+    pub fn is_possibly_suboptimal(&self) -> bool {
+        self.weight > 1.0 || !self.macros.is_empty()
+    }
+
+    /// Returns the largest limit of an iteration that can still find a solution within
+    /// `give_up_limit` moves. The limits are weighted like the evaluations, so a path of
+    /// `give_up_limit` moves can be evaluated up to `weight` times that with an admissible
+    /// heuristic.
+    pub fn max_limit(&self) -> usize {
+        (self.weight.max(1.0) * self.give_up_limit as f32).ceil() as usize
+    }
+
+    /// Returns the result of the fallback of `config` if `gave_up` is the result of a search of
+    /// `init_cube` that gave up and `config` asks for it, otherwise returns `gave_up` as is. The
+    /// statistics of the search are kept, with the nodes and the time of the fallback added.
+    fn fall_back(&self, init_cube: &Cube, gave_up: SearchResult) -> SearchResult {
+        let can_fall_back = self.fallback
+            && self.goal == Goal::Solved
+            && self.move_set == MoveSet::HalfTurn
+            && self.macros.is_empty();
+        if !can_fall_back || gave_up.status != SearchStatus::GaveUp {
+            return gave_up;
+        }
+        log::info!(
+            "IDA* gave up at limit {}, falling back to the two-phase algorithm",
+            gave_up.bound
+        );
+        let start_time = Instant::now();
+        let (solution, node_visited) =
+            // the result is verified below
+            match two_phase::solve_two_phase(init_cube, two_phase::DEFAULT_MAX_LENGTH, false) {
+                Some(result) => (result.solution, result.node_visited),
+                None => match lbl::solve_layer_by_layer(init_cube) {
+                    Some(solution) => (Some(solution.turns()), 0),
+                    None => return gave_up,
+                },
+            };
+        let mut result = SearchResult {
+            solution_len: solution.as_ref().map(Vec::len),
+            solutions: solution.iter().cloned().collect(),
+            solution,
+            node_visited: gave_up.node_visited + node_visited,
+            wall_time: gave_up.wall_time + start_time.elapsed(),
+            status: SearchStatus::Solved,
+            possibly_suboptimal: true,
+            ..gave_up
+        };
+        if self.verify {
+            result.verify(init_cube, &self.goal);
+        }
+        result
+    }
+
+    /// Returns the moves that a search expands each node with: the turns of the move set, one at a
+    /// time, then the macros.
+    fn actions(&self) -> Vec<&[Turn]> {
+        self.move_set
+            .turns()
+            .iter()
+            .map(slice::from_ref)
+            .chain(self.macros.iter().map(Vec::as_slice))
+            .collect()
+    }
+}
+
+/// How a search ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchStatus {
+    /// A solution was found.
+    Solved,
+    /// The search ended without a solution, for IDA* when the limit exceeded the give up limit.
+    GaveUp,
+    /// The time limit of the search ran out.
+    TimedOut,
+    /// The search was cancelled through its cancellation token.
+    Cancelled,
+    /// The search visited as many nodes or used as much memory as its budgets allow.
+    BudgetExhausted,
+}
+impl SearchStatus {
+    /// Returns the name of the status, like `timed-out`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SearchStatus::Solved => "solved",
+            SearchStatus::GaveUp => "gave-up",
+            SearchStatus::TimedOut => "timed-out",
+            SearchStatus::Cancelled => "cancelled",
+            SearchStatus::BudgetExhausted => "budget-exhausted",
+        }
+    }
+}
+
+/// The line that a search prints with `SearchConfig::print_progress`, shared by the threads of a
+/// parallel search. The time left at a limit is estimated from the growth of the nodes visited by
+/// the previous iterations, so it's only shown from the third iteration on.
+struct ProgressLine {
+    start_time: Instant,
+    /// Nodes visited before resuming from a checkpoint, which don't count for the rate.
+    resumed_node_visited: usize,
+    state: Mutex<ProgressState>,
+}
+struct ProgressState {
+    limit: usize,
+    /// Nodes visited before the iteration.
+    iteration_start: usize,
+    /// Nodes that the iteration is expected to visit.
+    expected_nodes: Option<usize>,
+    last_print: Instant,
+}
+impl ProgressLine {
+    fn new(start_time: Instant, resumed_node_visited: usize) -> ProgressLine {
+        ProgressLine {
+            start_time,
+            resumed_node_visited,
+            state: Mutex::new(ProgressState {
+                limit: 0,
+                iteration_start: resumed_node_visited,
+                expected_nodes: None,
+                last_print: start_time,
+            }),
+        }
+    }
+
+    /// Starts the iteration at `limit` after `node_visited` nodes, where the previous iterations
+    /// visited `iteration_node_visited`, and prints the line.
+    fn start_iteration(&self, limit: usize, node_visited: usize, iteration_node_visited: &[usize]) {
+        let mut state = self.state.lock().expect("Error when locking progress");
+        state.limit = limit;
+        state.iteration_start = node_visited;
+        // assume the nodes keep growing by the same factor as between the last two iterations
+        state.expected_nodes = match iteration_node_visited {
+            [.., before, last] if *before > 0 => {
+                Some((*last as f64 * *last as f64 / *before as f64) as usize)
+            }
+            _ => None,
+        };
+        self.print(&mut state, node_visited);
+    }
+
+    /// Prints the line again after `node_visited` nodes if it wasn't printed recently. Skips it
+    /// if another thread is printing it.
+    fn update(&self, node_visited: usize) {
+        if let Ok(mut state) = self.state.try_lock() {
+            if state.last_print.elapsed() >= PROGRESS_PRINT_INTERVAL {
+                self.print(&mut state, node_visited);
+            }
+        }
+    }
+
+    fn print(&self, state: &mut ProgressState, node_visited: usize) {
+        let elapsed = self.start_time.elapsed();
+        let rate = match elapsed.as_secs_f64() {
+            0.0 => 0.0,
+            secs => (node_visited - self.resumed_node_visited) as f64 / secs,
+        };
+        let iteration_nodes = node_visited - state.iteration_start;
+        let time_left = match state.expected_nodes {
+            Some(expected) if rate > 0.0 && iteration_nodes < expected => {
+                format_secs((expected - iteration_nodes) as f64 / rate)
             }
+            _ => "?".to_string(),
+        };
+        let line = format!(
+            "Limit {}: {node_visited} nodes, {} nodes/s, {} elapsed, {time_left} left at this limit",
+            state.limit,
+            format_count(rate),
+            format_secs(elapsed.as_secs_f64()),
+        );
+        print!("\r{line:<90}");
+        stdout().flush().expect("Error when printing text");
+        state.last_print = Instant::now();
+    }
+}
+
+/// Formats `count` with a `k`, `M` or `G` suffix, like `1.5M`.
+fn format_count(count: f64) -> String {
+    match count {
+        c if c >= 1e9 => format!("{:.1}G", c / 1e9),
+        c if c >= 1e6 => format!("{:.1}M", c / 1e6),
+        c if c >= 1e3 => format!("{:.1}k", c / 1e3),
+        c => format!("{c:.0}"),
+    }
+}
+
+/// Formats a number of seconds like `42.0s` or `3m05s`.
+fn format_secs(secs: f64) -> String {
+    if secs < 60.0 {
+        return format!("{secs:.1}s");
+    }
+    let secs = secs as u64;
+    match secs / 3600 {
+        0 => format!("{}m{:02}s", secs / 60, secs % 60),
+        hours => format!("{hours}h{:02}m", secs / 60 % 60),
+    }
+}
+
+/// The limits of `SearchConfig` that interrupt a search: its time limit, its cancellation token
+/// and its node budget.
+#[derive(Clone, Copy)]
+struct StopLimits<'a> {
+    /// The search is interrupted once this instant is passed.
+    deadline: Option<Instant>,
+    /// The search is interrupted once this is set.
+    cancel_token: Option<&'a AtomicBool>,
+    /// The search is interrupted once it visited this many nodes.
+    node_budget: Option<usize>,
+}
+impl<'a> StopLimits<'a> {
+    /// Returns the limits of `config`, with its time limit counted from `start_time`.
+    fn new(config: &'a SearchConfig, start_time: Instant) -> StopLimits<'a> {
+        StopLimits {
+            deadline: config.time_limit.map(|time_limit| start_time + time_limit),
+            cancel_token: config.cancel_token.as_deref(),
+            node_budget: config.node_budget,
+        }
+    }
+
+    /// Returns why a search should stop, if it should. `node_count` returns the nodes the budget
+    /// applies to. Reading the clock takes longer than expanding a node, so the deadline is only
+    /// checked if `check_clock` is set.
+    fn interruption(
+        &self,
+        check_clock: bool,
+        node_count: impl FnOnce() -> usize,
+    ) -> Option<SearchStatus> {
+        if self
+            .node_budget
+            .is_some_and(|budget| node_count() >= budget)
+        {
+            Some(SearchStatus::BudgetExhausted)
+        } else if check_clock
+            && self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            Some(SearchStatus::TimedOut)
+        } else if self
+            .cancel_token
+            .is_some_and(|cancel_token| cancel_token.load(Ordering::Relaxed))
+        {
+            Some(SearchStatus::Cancelled)
+        } else {
+            None
         }
     }
+}
 
-    /// Returns the children of this node. Also consumes `self`.
-    fn generate_children(parent_ptr: Rc<RefCell<Node>>) -> Vec<Node> {
-        let mut res = Vec::with_capacity(5);
-        let parent = parent_ptr.borrow();
-        // only iterate through the two directions of right, up, and front, since the other turns
-        // can be applied by these 6 turns.
-        for face_dir in [FaceDir::Right, FaceDir::Up, FaceDir::Front] {
-            for turn_dir in [TurnDir::Clockwise, TurnDir::CounterClockwise] {
-                let turn = Turn::new(face_dir, turn_dir);
-                // skip the reverse turn of the previous action, effectively cutting the branching
-                // factor down to 5
-                if let Some(t) = &parent.prev_action {
-                    if t.is_reversed(&turn) {
-                        continue;
-                    }
+/// One depth-first search of IDA*.
+///
+/// Instead of cloning the cube for every node, the search applies a move to a single cube before
+/// going deeper and undoes it when coming back, keeping the current path in a fixed size vec.
+/// A move is either a single turn or a whole macro (see `SearchConfig::macros`).
+struct DepthFirstSearch<'a> {
+    cube: Cube,
+    /// `path[..depth]` is the list of turns applied to the initial cube.
+    path: Vec<Turn>,
+    depth: usize,
+    /// `action_path[..num_actions]` is the list of moves applied to the initial cube, as indices
+    /// of `actions`. The limit applies to the number of moves.
+    action_path: Vec<usize>,
+    num_actions: usize,
+    evaluate: &'a mut dyn FnMut(&Cube) -> f32,
+    /// Weight of the heuristic returned by `evaluate`.
+    weight: f32,
+    /// Most moves of a path, see `SearchConfig::give_up_limit`.
+    give_up_limit: usize,
+    node_visited: usize,
+    /// Depth of the deepest node visited.
+    max_depth: usize,
+    /// The moves to expand each node with, see `SearchConfig::actions`.
+    actions: Vec<&'a [Turn]>,
+    goal: &'a Goal,
+    /// Moves (indices of `actions`) that are forced at the top of the tree, so that only the
+    /// subtree below them is searched.
+    prefix: &'a [usize],
+    /// Set by another search to tell this one to give up.
+    stop: Option<&'a AtomicBool>,
+    limits: StopLimits<'a>,
+    /// Nodes visited by every search of the same parallel iteration, which the node budget
+    /// applies to instead of `node_visited`.
+    shared_node_count: Option<&'a AtomicUsize>,
+    /// Why the search was interrupted, if it was.
+    interrupted: Option<SearchStatus>,
+    /// The solutions found so far.
+    solutions: Vec<Vec<Turn>>,
+    /// The search ends once this many solutions are found.
+    max_solutions: usize,
+    /// Solutions found by every search of the same parallel iteration, which `max_solutions`
+    /// applies to instead of the length of `solutions`.
+    shared_solution_count: Option<&'a AtomicUsize>,
+    /// The line to print the progress on, if it's printed.
+    progress_line: Option<&'a ProgressLine>,
+    /// Nodes visited before this search, added to the nodes it visits on the progress line.
+    progress_offset: usize,
+}
+impl<'a> DepthFirstSearch<'a> {
+    /// Creates the search of `config`, with its time limit counted from `start_time`.
+    fn new(
+        cube: Cube,
+        config: &'a SearchConfig,
+        start_time: Instant,
+        evaluate: &'a mut dyn FnMut(&Cube) -> f32,
+    ) -> DepthFirstSearch<'a> {
+        let actions = config.actions();
+        let longest_action = actions.iter().map(|action| action.len()).max().unwrap_or(1);
+        DepthFirstSearch {
+            cube,
+            path: vec![SOLVER_TURNS[0]; (config.give_up_limit + 1) * longest_action],
+            depth: 0,
+            action_path: vec![0; config.give_up_limit + 1],
+            num_actions: 0,
+            evaluate,
+            weight: config.weight,
+            give_up_limit: config.give_up_limit,
+            node_visited: 0,
+            max_depth: 0,
+            actions,
+            goal: &config.goal,
+            prefix: &[],
+            stop: None,
+            limits: StopLimits::new(config, start_time),
+            shared_node_count: None,
+            interrupted: None,
+            solutions: Vec::new(),
+            max_solutions: config.max_solutions,
+            shared_solution_count: None,
+            progress_line: None,
+            progress_offset: 0,
+        }
+    }
+
+    /// Checks if the search should stop, recording why in `interrupted`.
+    fn is_interrupted(&mut self) -> bool {
+        if self.interrupted.is_some() || self.stop.is_some_and(|stop| stop.load(Ordering::Relaxed))
+        {
+            return true;
+        }
+        // the clock, the shared node count and the progress line are only checked once in a while
+        let check_clock =
+            self.node_visited > 0 && self.node_visited.is_multiple_of(STOP_CHECK_INTERVAL);
+        if check_clock {
+            // share the nodes visited since the last check with the other threads
+            if let Some(count) = self.shared_node_count {
+                count.fetch_add(STOP_CHECK_INTERVAL, Ordering::Relaxed);
+            }
+            if let Some(progress_line) = self.progress_line {
+                let node_count = match self.shared_node_count {
+                    Some(count) => count.load(Ordering::Relaxed),
+                    None => self.node_visited,
+                };
+                progress_line.update(self.progress_offset + node_count);
+            }
+        }
+        self.interrupted = self
+            .limits
+            .interruption(check_clock, || match self.shared_node_count {
+                Some(count) => {
+                    count.load(Ordering::Relaxed) + self.node_visited % STOP_CHECK_INTERVAL
                 }
+                None => self.node_visited,
+            });
+        self.interrupted.is_some()
+    }
 
-                let mut new_cube = parent.state.clone();
-                new_cube.turn_layer(&turn, 1);
+    /// Searches the subtree of the current cube for a solution within `limit`.
+    ///
+    /// Solutions are added to `solutions`, and once there are `max_solutions` of them, returns
+    /// `None`. Otherwise returns the smallest evaluation that exceeded `limit`, which is the next
+    /// limit to try. `parent_f` is the evaluation of the parent node. If the search is stopped or
+    /// interrupted, returns `usize::MAX`.
+    fn search(&mut self, parent_f: usize, limit: usize) -> Option<usize> {
+        if self.is_interrupted() {
+            return Some(usize::MAX);
+        }
+        self.node_visited += 1;
+        self.max_depth = usize::max(self.max_depth, self.depth);
+        let new_f = self.num_actions + (self.weight * (self.evaluate)(&self.cube)).ceil() as usize;
+        // ensure that f is monotone (Korf pg. 104)
+        let f = usize::max(new_f, parent_f);
 
-                res.push(Node {
-                    state: new_cube,
-                    prev_action: Some(turn),
-                    parent: Some(Rc::clone(&parent_ptr)),
-                    path_cost: parent.path_cost + 1,
-                    evaluation: None,
-                });
+        // check for if node exceeds the threshold, if yes we skip it
+        if f > limit {
+            return Some(f);
+        }
+        // if we reached the goal, keep the solution and look for the next one if we need more
+        if self.goal.is_reached(&self.cube) {
+            self.solutions.push(self.path[..self.depth].to_vec());
+            let solution_count = match self.shared_solution_count {
+                Some(count) => count.fetch_add(1, Ordering::Relaxed) + 1,
+                None => self.solutions.len(),
+            };
+            if solution_count >= self.max_solutions {
+                return None;
             }
+            return Some(usize::MAX);
+        }
+        // a weighted limit can exceed the give up limit, but the paths can't
+        if self.num_actions >= self.give_up_limit {
+            return Some(usize::MAX);
         }
-        res
+
+        let forced_action = self.prefix.get(self.num_actions).copied();
+        let mut min_f = usize::MAX;
+        for i in 0..self.actions.len() {
+            if forced_action.is_some_and(|forced_action| forced_action != i) {
+                continue;
+            }
+            // skip turns that lead to a state that a shorter or canonical sequence reaches, like
+            // the reverse of the previous turn
+            let action_path = &self.action_path[..self.num_actions];
+            if is_redundant_action(&self.actions, action_path, &self.path[..self.depth], i) {
+                continue;
+            }
+
+            let action = self.actions[i];
+            for turn in action.iter() {
+                self.cube.apply_turn(turn);
+                self.path[self.depth] = *turn;
+                self.depth += 1;
+            }
+            self.action_path[self.num_actions] = i;
+            self.num_actions += 1;
+
+            min_f = usize::min(min_f, self.search(f, limit)?);
+
+            self.num_actions -= 1;
+            for turn in action.iter().rev() {
+                self.depth -= 1;
+                self.cube.apply_turn(&turn.get_reversed());
+            }
+        }
+        Some(min_f)
     }
 }
 
-#[allow(dead_code)]
+/// Checks if applying the move `action` of `actions` after the moves of `action_path`, whose
+/// turns are `path`, is redundant.
+///
+/// A single turn is checked with `Turn::is_redundant_after` against the single turns right before
+/// it only, since a turn that cancels the end of a macro doesn't make a shorter sequence of moves.
+/// Macros are never redundant.
+fn is_redundant_action(
+    actions: &[&[Turn]],
+    action_path: &[usize],
+    path: &[Turn],
+    action: usize,
+) -> bool {
+    let [turn] = actions[action] else {
+        return false;
+    };
+    // `Turn::is_redundant_after` looks at the last two turns at most
+    let num_single_turns = action_path
+        .iter()
+        .rev()
+        .take(2)
+        .take_while(|&&i| actions[i].len() == 1)
+        .count();
+    turn.is_redundant_after(&path[path.len() - num_single_turns..])
+}
+
+/// Returns the maximum number of stickers that a single turn can move on a cube of size `size`.
+///
+/// A turn moves the `4 * size` stickers of the band and every sticker of the turned face except
+/// its center (which only exists on odd sized cubes). Since a turn can fix at most this many
+/// stickers, dividing a Hamming distance by it gives an admissible heuristic.
+fn max_stickers_moved_per_turn(size: usize) -> f32 {
+    let face_stickers = size * size - size % 2;
+    (4 * size + face_stickers) as f32
+}
+
+/// Heuristic that always returns 0, which turns IDA* into iterative deepening. It's admissible for
+/// every goal.
+pub fn no_heuristic(_cube: &Cube) -> f32 {
+    0.0
+}
+
+/// Heuristic that counts the stickers that differ from the solved cube, divided by the most
+/// stickers a turn can move, so that it's admissible.
 pub fn single_l0(cube: &Cube) -> f32 {
-    cube.hamming_distance(&Cube::new(2)) as f32 / 12.0
+    // cache the packed solved cube, since creating it allocates its faces
+    thread_local! {
+        static SOLVED_CUBE_CACHE: RefCell<HashMap<usize, Vec<u64>>> = RefCell::new(HashMap::new());
+    }
+    let size = cube.size();
+    let dist = SOLVED_CUBE_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let solved_cube = cache
+            .entry(size)
+            .or_insert_with(|| Cube::new(size).packed_facelets());
+        Cube::packed_hamming_distance(&cube.packed_facelets(), solved_cube)
+    });
+    dist as f32 / max_stickers_moved_per_turn(size)
 }
 
-#[allow(dead_code)]
+/// Like `single_l0`, but compared with the solved cube in each of its orientations, keeping the
+/// smallest distance, so that a cube solved in another orientation is at 0.
 pub fn all_l0(cube: &Cube) -> f32 {
     // have a cache to avoid creating the vec many time
     // cut heuristic evaluating time by half
-    static mut ALL_POSSIBLE_SOLVED_CUBES_CACHE: Option<Vec<Cube>> = None;
-    let apsc_iter = unsafe {
-        if let Some(cache) = &ALL_POSSIBLE_SOLVED_CUBES_CACHE {
-            cache.iter()
-        } else {
-            ALL_POSSIBLE_SOLVED_CUBES_CACHE = Some(Cube::all_possible_solved_cubes(2));
-            ALL_POSSIBLE_SOLVED_CUBES_CACHE.as_ref().unwrap().iter()
+    thread_local! {
+        static ALL_POSSIBLE_SOLVED_CUBES_CACHE: RefCell<HashMap<usize, Vec<Vec<u64>>>> =
+            RefCell::new(HashMap::new());
+    }
+    let size = cube.size();
+    // pack the cube once and compare it with every packed solved cube
+    let packed_cube = cube.packed_facelets();
+    let min_dist = ALL_POSSIBLE_SOLVED_CUBES_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let apsc = cache.entry(size).or_insert_with(|| {
+            Cube::all_possible_solved_cubes(size)
+                .iter()
+                .map(Cube::packed_facelets)
+                .collect()
+        });
+
+        let mut min_dist = usize::MAX;
+        for goal_state in apsc.iter() {
+            min_dist = usize::min(
+                min_dist,
+                Cube::packed_hamming_distance(&packed_cube, goal_state),
+            );
         }
-    };
+        min_dist
+    });
+    min_dist as f32 / max_stickers_moved_per_turn(size)
+}
+
+/// Lower bound from the coordinate distance tables (see `coordinate::distance`), which is much
+/// more informed than the sticker heuristics, especially on a 3x3. Falls back to `single_l0` for
+/// sizes it doesn't support.
+pub fn walking_distance(cube: &Cube) -> f32 {
+    match coordinate::distance(cube) {
+        Some(distance) => distance as f32,
+        None => single_l0(cube),
+    }
+}
+
+/// Heuristic of the IDA* that solves the cube, chosen at runtime by its name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Heuristic {
+    /// `single_l0`.
+    #[default]
+    SingleL0,
+    /// `all_l0`.
+    AllL0,
+    /// `walking_distance`, which looks up the corner coordinates (and the edge orientation on a
+    /// 3x3) in the coordinate distance tables.
+    Coordinate,
+    /// The exact distance of a 2x2 in its depth table, see `distance_2x2`. It's `single_l0` on
+    /// other sizes or while the table isn't built.
+    ExactTable,
+}
+impl Heuristic {
+    /// Every heuristic, e.g. to list their names.
+    pub const ALL: [Heuristic; 4] = [
+        Heuristic::SingleL0,
+        Heuristic::AllL0,
+        Heuristic::Coordinate,
+        Heuristic::ExactTable,
+    ];
+
+    /// Returns the name that the heuristic is chosen by.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Heuristic::SingleL0 => "single-l0",
+            Heuristic::AllL0 => "all-l0",
+            Heuristic::Coordinate => "coordinate",
+            Heuristic::ExactTable => "exact-table",
+        }
+    }
+
+    /// Returns the heuristic named `name`, see `name`.
+    pub fn from_name(name: &str) -> Option<Heuristic> {
+        Heuristic::ALL
+            .into_iter()
+            .find(|heuristic| heuristic.name() == name)
+    }
+
+    /// Returns the value of the heuristic for `cube`.
+    pub fn evaluate(&self, cube: &Cube) -> f32 {
+        match self {
+            Heuristic::SingleL0 => single_l0(cube),
+            Heuristic::AllL0 => all_l0(cube),
+            Heuristic::Coordinate => walking_distance(cube),
+            Heuristic::ExactTable => {
+                match depth_table_2x2().and_then(|depth_table| distance_2x2(cube, depth_table)) {
+                    Some(distance) => distance as f32,
+                    None => single_l0(cube),
+                }
+            }
+        }
+    }
+}
+
+/// Algorithm that solves the cube, chosen at runtime by its name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Solver {
+    /// The fastest solver for the size of the cube: the depth table or the corner coordinates of
+    /// the 2x2, the two-phase algorithm of the 3x3, and IDA* otherwise.
+    #[default]
+    Auto,
+    /// `parallel_idastar` with the chosen `Heuristic`.
+    IdaStar,
+    /// `two_phase::solve_two_phase`, only for the 3x3.
+    TwoPhase,
+    /// `lbl::solve_layer_by_layer`, only for the 3x3.
+    LayerByLayer,
+}
+impl Solver {
+    /// Every solver, e.g. to list their names.
+    pub const ALL: [Solver; 4] = [
+        Solver::Auto,
+        Solver::IdaStar,
+        Solver::TwoPhase,
+        Solver::LayerByLayer,
+    ];
+
+    /// Returns the name that the solver is chosen by.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Solver::Auto => "auto",
+            Solver::IdaStar => "idastar",
+            Solver::TwoPhase => "two-phase",
+            Solver::LayerByLayer => "lbl",
+        }
+    }
+
+    /// Returns the solver named `name`, see `name`.
+    pub fn from_name(name: &str) -> Option<Solver> {
+        Solver::ALL.into_iter().find(|solver| solver.name() == name)
+    }
+
+    /// Returns whether the solver can solve cubes of size `size`.
+    pub fn supports(&self, size: usize) -> bool {
+        match self {
+            Solver::Auto | Solver::IdaStar => true,
+            Solver::TwoPhase | Solver::LayerByLayer => size == 3,
+        }
+    }
+}
+
+/// Solves `cube` with `solver`. `Solver::Auto` looks up the solution in the exact depth table of
+/// the 2x2 or searches its corner coordinates, uses the two-phase algorithm on the 3x3 if we can,
+/// and otherwise runs IDA* with `heuristic`, like `Solver::IdaStar`. Every solver checks its
+/// solution if `config` asks for it, see `SearchConfig::verify`.
+///
+/// Returns `None` if the solver doesn't solve cubes of this size, or if the two-phase algorithm
+/// or the layer by layer method can't solve the cube.
+pub fn solve(
+    cube: &Cube,
+    config: &SearchConfig,
+    solver: Solver,
+    heuristic: Heuristic,
+) -> Option<SearchResult> {
+    let idastar = || parallel_idastar(cube.clone(), &|cube| heuristic.evaluate(cube), config);
+    match solver {
+        Solver::Auto => {
+            let fast_result = match (cube.size(), depth_table_2x2()) {
+                (2, Some(depth_table)) => solve_optimal_2x2(cube, depth_table, config.verify),
+                (2, None) => coordinate::solve_2x2(cube, config),
+                (3, _) => {
+                    two_phase::solve_two_phase(cube, two_phase::DEFAULT_MAX_LENGTH, config.verify)
+                }
+                _ => None,
+            };
+            Some(fast_result.unwrap_or_else(idastar))
+        }
+        Solver::IdaStar => Some(idastar()),
+        Solver::TwoPhase => {
+            two_phase::solve_two_phase(cube, two_phase::DEFAULT_MAX_LENGTH, config.verify)
+        }
+        Solver::LayerByLayer => {
+            let start_time = Instant::now();
+            let solution = lbl::solve_layer_by_layer(cube)?;
+            let mut result = SearchResult::from_solution(solution.turns(), start_time.elapsed());
+            if config.verify {
+                result.verify(cube, &Goal::Solved);
+            }
+            Some(result)
+        }
+    }
+}
 
-    let mut min_dist = usize::MAX;
-    for goal_state in apsc_iter {
-        min_dist = usize::min(min_dist, cube.hamming_distance(&goal_state));
+/// Bounded cache of heuristic values, keyed by the Zobrist hash of the state.
+///
+/// IDA* expands the same states again in every iteration, so caching pays off for expensive
+/// heuristics like `all_l0`. The least recently used values are evicted when the cache is full.
+struct HeuristicCache {
+    values: LruCache<u64, f32>,
+}
+impl HeuristicCache {
+    /// Approximate number of bytes taken by each cached value, including the links and the hash
+    /// table entry of the LRU cache.
+    const ENTRY_SIZE: usize = size_of::<(u64, f32)>() + 4 * size_of::<usize>();
+
+    /// Returns the capacity of each of `num_caches` caches of a search, shrunk to fit the memory
+    /// budget of `config`. Returns `None` if the search doesn't cache heuristic values.
+    fn capacity(config: &SearchConfig, num_caches: usize) -> Option<NonZeroUsize> {
+        let capacity = config.heuristic_cache_size?;
+        match config.memory_budget {
+            None => Some(capacity),
+            Some(memory_budget) => NonZeroUsize::new(usize::min(
+                capacity.get(),
+                memory_budget / num_caches / HeuristicCache::ENTRY_SIZE,
+            )),
+        }
+    }
+
+    fn new(capacity: NonZeroUsize) -> HeuristicCache {
+        HeuristicCache {
+            values: LruCache::new(capacity),
+        }
+    }
+
+    /// Returns the cached heuristic value of `cube`, computing and caching it if it's missing.
+    fn get_or_evaluate(&mut self, cube: &Cube, heuristic_function: &dyn Fn(&Cube) -> f32) -> f32 {
+        *self
+            .values
+            .get_or_insert(cube.zobrist_hash(), || heuristic_function(cube))
     }
-    min_dist as f32 / 12.0
 }
 
+/// What a search found, with the statistics of how it searched.
+#[derive(Clone)]
 pub struct SearchResult {
+    /// The shortest solution found, or `None` if the search didn't find one.
     pub solution: Option<Vec<Turn>>,
+    /// Number of turns of `solution`.
     pub solution_len: Option<usize>,
+    /// Number of nodes visited by the search.
     pub node_visited: usize,
+    /// Time the search took.
     pub wall_time: Duration,
+    /// Why the search ended.
+    pub status: SearchStatus,
+    /// Weight of the heuristic of the search, see `SearchConfig::weight`.
+    pub weight: f32,
+    /// The solution may be longer than optimal, e.g. because the search was weighted or used
+    /// macros.
+    pub possibly_suboptimal: bool,
+    /// Every solution found, from the shortest, the first being `solution`. There is more than
+    /// one when `SearchConfig::max_solutions` asks for more.
+    pub solutions: Vec<Vec<Turn>>,
+    /// The solutions were applied to the initial cube and checked to reach the goal, see
+    /// `SearchResult::verify`.
+    pub verified: bool,
+    /// The bound of the last iteration of IDA*, or the depth reached by a breadth-first search.
+    /// When the search is solved without a weight, it's the length of the solution.
+    pub bound: usize,
+    /// Number of iterations of IDA*, or of depths expanded by a breadth-first search.
+    pub iterations: usize,
+    /// Nodes visited by each iteration.
+    pub iteration_node_visited: Vec<usize>,
+    /// Depth of the deepest node visited.
+    pub max_depth: usize,
+    /// Largest number of nodes kept at once: the path of a depth-first search, or the queue of a
+    /// breadth-first search.
+    pub peak_stack_size: usize,
+}
+impl SearchResult {
+    /// Returns the result of a solver that builds `solution` in `wall_time` without searching
+    /// nodes, like the layer by layer method. The solution isn't optimal.
+    pub fn from_solution(solution: Vec<Turn>, wall_time: Duration) -> SearchResult {
+        SearchResult {
+            solution_len: Some(solution.len()),
+            bound: solution.len(),
+            iterations: 0,
+            iteration_node_visited: Vec::new(),
+            max_depth: solution.len(),
+            peak_stack_size: 0,
+            solution: Some(solution.clone()),
+            solutions: vec![solution],
+            node_visited: 0,
+            wall_time,
+            status: SearchStatus::Solved,
+            weight: 1.0,
+            possibly_suboptimal: true,
+            verified: false,
+        }
+    }
+
+    /// Applies every solution to a copy of `init_cube` and panics if one of them doesn't reach
+    /// `goal`, which catches bugs in generating the turns right away. Marks the result as verified
+    /// if it has solutions.
+    pub fn verify(&mut self, init_cube: &Cube, goal: &Goal) {
+        for solution in self.solutions.iter() {
+            let mut cube = init_cube.clone();
+            cube.apply_algorithm(solution.clone());
+            assert!(
+                goal.is_reached(&cube),
+                "Error: solution {} doesn't reach {goal:?}",
+                Turn::algo_string(solution)
+            );
+        }
+        self.verified = !self.solutions.is_empty();
+    }
+
+    /// Returns the number of nodes visited per second of wall time.
+    pub fn nodes_per_second(&self) -> f64 {
+        if self.wall_time.is_zero() {
+            return 0.0;
+        }
+        self.node_visited as f64 / self.wall_time.as_secs_f64()
+    }
 }
 impl Display for SearchResult {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
             "Solution: {}\tWall Time: {} ns\tNode Visited: {}",
-            match &self.solution {
-                None => "Can't find solution".to_string(),
-                Some(algo) => Turn::algo_string(algo),
+            match (&self.solution, self.status) {
+                (Some(algo), _) => Turn::algo_string(algo),
+                (None, SearchStatus::TimedOut) => "Timed out".to_string(),
+                (None, SearchStatus::Cancelled) => "Cancelled".to_string(),
+                (None, SearchStatus::BudgetExhausted) => {
+                    format!("Budget exhausted at bound {}", self.bound)
+                }
+                (None, _) => "Can't find solution".to_string(),
             },
             self.wall_time.as_nanos(),
             self.node_visited
-        )
+        )?;
+        if self.weight != 1.0 {
+            write!(f, "\tWeight: {}", self.weight)?;
+        }
+        if self.solution.is_some() && self.possibly_suboptimal {
+            write!(f, "\t(possibly suboptimal)")?;
+        }
+        if self.verified {
+            write!(f, "\t(verified)")?;
+        }
+        write!(
+            f,
+            "\nIterations: {}\tFinal Bound: {}\tMax Depth: {}\tNodes/s: {:.0}\tPeak Stack: {}\tIteration Nodes: {:?}",
+            self.iterations,
+            self.bound,
+            self.max_depth,
+            self.nodes_per_second(),
+            self.peak_stack_size,
+            self.iteration_node_visited
+        )?;
+        // list the other solutions on their own lines
+        for (i, algo) in self.solutions.iter().enumerate().skip(1) {
+            write!(f, "\nSolution {}: {}", i + 1, Turn::algo_string(algo))?;
+        }
+        Ok(())
     }
 }
 
 /// Based on Korf's
+///
+/// The search expands every node with the turns of the move set of `config`, until it reaches
+/// its goal. The heuristic has to be admissible for the goal. The heuristics in this module
+/// estimate the turns to solve the whole cube, which can overestimate the turns to reach other
+/// goals, so use `no_heuristic` for them.
+///
+/// The search can be interrupted by the time limit, the cancellation token or the node budget of
+/// `config`, in which case the result has no solution but still has the statistics of the search
+/// so far.
 pub fn idastar(
     init_cube: Cube,
     heuristic_function: &dyn Fn(&Cube) -> f32,
-    print_progress: bool,
+    config: &SearchConfig,
 ) -> SearchResult {
-    let mut root = Node::new_root(init_cube);
-    const GIVE_UP_LIMIT: usize = 28;
-    let mut limit = root.get_evaluation(heuristic_function);
+    let print_progress = config.print_progress;
+    let mut cache = HeuristicCache::capacity(config, 1).map(HeuristicCache::new);
+    let mut evaluate = |cube: &Cube| match &mut cache {
+        None => heuristic_function(cube),
+        Some(cache) => cache.get_or_evaluate(cube, heuristic_function),
+    };
 
-    let mut node_visited = 0;
     let start_time = Instant::now();
+    let fingerprint = SearchCheckpoint::fingerprint(&init_cube, config);
+    let mut limit = (config.weight * evaluate(&init_cube)).ceil() as usize;
+    let start_cube = init_cube.clone();
 
+    let mut bound = limit;
+    let mut iteration_node_visited = Vec::new();
+    let mut max_depth = 0;
+    // nodes visited before resuming, which the node budget doesn't apply to
+    let mut resumed_node_visited = 0;
+    if let Some(checkpoint) = SearchCheckpoint::resume(config, fingerprint) {
+        limit = checkpoint.bound;
+        resumed_node_visited = checkpoint.node_visited;
+        max_depth = checkpoint.max_depth;
+        iteration_node_visited = checkpoint.iteration_node_visited;
+    }
+    let progress_line = print_progress.then(|| ProgressLine::new(start_time, resumed_node_visited));
+    let mut dfs = DepthFirstSearch::new(init_cube, config, start_time, &mut evaluate);
+    dfs.max_depth = max_depth;
+    dfs.progress_line = progress_line.as_ref();
+    dfs.progress_offset = resumed_node_visited;
     loop {
-        let mut node_stack = vec![root.clone()];
-        if print_progress {
-            print!("\rSearching with limit = {limit:<10.2}");
+        if limit > config.max_limit() {
+            break;
+        }
+        bound = limit;
+        SearchCheckpoint {
+            bound,
+            node_visited: resumed_node_visited + dfs.node_visited,
+            iteration_node_visited: iteration_node_visited.clone(),
+            max_depth: dfs.max_depth,
+            finished_subtrees: Vec::new(),
+        }
+        .save(config, fingerprint);
+        if let Some(progress) = &config.progress {
+            let node_visited = resumed_node_visited + dfs.node_visited;
+            progress.update(limit, iteration_node_visited.len(), node_visited);
+        }
+        if let Some(progress_line) = &progress_line {
+            let node_visited = resumed_node_visited + dfs.node_visited;
+            progress_line.start_iteration(limit, node_visited, &iteration_node_visited);
         }
-        stdout().flush().expect("Error when printing text");
-
-        let mut min_f = usize::MAX;
-
-        while !node_stack.is_empty() {
-            let mut node = node_stack.pop().unwrap();
-            node_visited += 1;
-            let f = node.get_evaluation(heuristic_function);
-
-            // println!(
-            //     "{} {}",
-            //     Turn::algo_string(&node.get_path()),
-            //     node.get_evaluation(heuristic_function)
-            // );
-            // check for if node exceeds the threshold, if yes we skip it
-            if f > limit {
-                min_f = usize::min(min_f, f);
-                continue;
-            }
 
-            // if we found the solution, returns the list of actions
-            if node.is_goal() {
+        let node_visited_before = dfs.node_visited;
+        let outcome = dfs.search(0, limit);
+        iteration_node_visited.push(dfs.node_visited - node_visited_before);
+        log::debug!(
+            "IDA* searched limit {limit} in {} nodes",
+            dfs.node_visited - node_visited_before
+        );
+        match outcome {
+            // increase the limit
+            Some(min_f) if dfs.solutions.is_empty() => limit = min_f,
+            // if we found solutions, returns them
+            _ => {
                 if print_progress {
                     println!();
                 }
-                let path = node.get_path();
-                return SearchResult {
-                    solution_len: Some(path.len()),
-                    solution: Some(path),
-                    node_visited,
+                let mut solutions = mem::take(&mut dfs.solutions);
+                solutions.sort_by_key(Vec::len);
+                let max_depth = dfs.max_depth;
+                let mut result = SearchResult {
+                    solution_len: Some(solutions[0].len()),
+                    solution: Some(solutions[0].clone()),
+                    node_visited: resumed_node_visited + dfs.node_visited,
                     wall_time: start_time.elapsed(),
+                    status: SearchStatus::Solved,
+                    weight: config.weight,
+                    possibly_suboptimal: config.is_possibly_suboptimal(),
+                    verified: false,
+                    bound,
+                    solutions,
+                    iterations: iteration_node_visited.len(),
+                    iteration_node_visited,
+                    max_depth,
+                    peak_stack_size: max_depth + 1,
                 };
+                if config.verify {
+                    result.verify(&start_cube, &config.goal);
+                }
+                SearchCheckpoint::remove(config);
+                return result;
             }
+        }
+        if dfs.interrupted.is_some() {
+            break;
+        }
+    }
+    // can't find solution
+    if print_progress {
+        println!();
+    }
+    if dfs.interrupted.is_none() {
+        SearchCheckpoint::remove(config);
+    }
+    let gave_up = SearchResult {
+        solution: None,
+        solution_len: None,
+        node_visited: resumed_node_visited + dfs.node_visited,
+        wall_time: start_time.elapsed(),
+        status: dfs.interrupted.unwrap_or(SearchStatus::GaveUp),
+        iterations: iteration_node_visited.len(),
+        iteration_node_visited,
+        max_depth: dfs.max_depth,
+        peak_stack_size: dfs.max_depth + 1,
+        weight: config.weight,
+        possibly_suboptimal: config.is_possibly_suboptimal(),
+        verified: false,
+        bound,
+        solutions: Vec::new(),
+    };
+    config.fall_back(&start_cube, gave_up)
+}
+
+/// Number of turns at the top of the tree that are expanded before splitting the search between
+/// threads. With the right, up, and front quarter turns, depth 2 gives 27 subtrees, which keeps
+/// the threads busy even when some subtrees are pruned early.
+const SPLIT_DEPTH: usize = 2;
+
+/// Returns every sequence of `SPLIT_DEPTH` moves that `DepthFirstSearch` would expand with
+/// `actions`, as indices of `actions`.
+fn split_prefixes(actions: &[&[Turn]]) -> Vec<Vec<usize>> {
+    let mut prefixes = vec![Vec::new()];
+    for _ in 0..SPLIT_DEPTH {
+        let mut longer_prefixes = Vec::new();
+        for prefix in prefixes {
+            let path: Vec<Turn> = prefix
+                .iter()
+                .flat_map(|&i: &usize| actions[i].iter().copied())
+                .collect();
+            for action in 0..actions.len() {
+                if is_redundant_action(actions, &prefix, &path, action) {
+                    continue;
+                }
+                let mut longer_prefix = prefix.clone();
+                longer_prefix.push(action);
+                longer_prefixes.push(longer_prefix);
+            }
+        }
+        prefixes = longer_prefixes;
+    }
+    prefixes
+}
+
+/// Parallel version of `idastar`.
+///
+/// Every iteration, the tree is split at depth `SPLIT_DEPTH` and the subtrees are searched with
+/// the same limit by the rayon thread pool. Once the threads found as many solutions as asked
+/// for, the others stop, and the shortest solutions found are returned. Since every thread
+/// finishes the previous limit before the next one starts, the solutions are as short as the ones
+/// `idastar` finds.
+///
+/// Each subtree keeps its own heuristic cache across iterations. With a checkpoint path, the
+/// subtrees that were searched at the limit of the checkpoint are skipped when resuming.
+pub fn parallel_idastar(
+    init_cube: Cube,
+    heuristic_function: &(dyn Fn(&Cube) -> f32 + Sync),
+    config: &SearchConfig,
+) -> SearchResult {
+    let print_progress = config.print_progress;
+    let start_time = Instant::now();
+    let fingerprint = SearchCheckpoint::fingerprint(&init_cube, config);
+    let prefixes = split_prefixes(&config.actions());
+    let capacity = HeuristicCache::capacity(config, prefixes.len());
+    let caches: Vec<Mutex<Option<HeuristicCache>>> = prefixes
+        .iter()
+        .map(|_| Mutex::new(capacity.map(HeuristicCache::new)))
+        .collect();
+
+    let mut limit = (config.weight * heuristic_function(&init_cube)).ceil() as usize;
+    let mut node_visited = 0;
+    let mut interrupted = None;
+    let mut bound = limit;
+    let mut iteration_node_visited = Vec::new();
+    let mut max_depth = 0;
+    let mut finished_subtrees = Vec::new();
+    // nodes visited before resuming, which the node budget doesn't apply to
+    let mut resumed_node_visited = 0;
+    if let Some(checkpoint) = SearchCheckpoint::resume(config, fingerprint) {
+        limit = checkpoint.bound;
+        resumed_node_visited = checkpoint.node_visited;
+        iteration_node_visited = checkpoint.iteration_node_visited;
+        max_depth = checkpoint.max_depth;
+        finished_subtrees = checkpoint.finished_subtrees;
+    }
+    let progress_line = print_progress.then(|| ProgressLine::new(start_time, resumed_node_visited));
+    loop {
+        if limit > config.max_limit() {
+            break;
+        }
+        bound = limit;
+        let checkpoint = SearchCheckpoint {
+            bound,
+            node_visited: resumed_node_visited + node_visited,
+            iteration_node_visited: iteration_node_visited.clone(),
+            max_depth,
+            finished_subtrees: mem::take(&mut finished_subtrees),
+        };
+        checkpoint.save(config, fingerprint);
+        let checkpoint = Mutex::new(checkpoint);
+        if let Some(progress) = &config.progress {
+            let node_visited = resumed_node_visited + node_visited;
+            progress.update(limit, iteration_node_visited.len(), node_visited);
+        }
+        if let Some(progress_line) = &progress_line {
+            let node_visited = resumed_node_visited + node_visited;
+            progress_line.start_iteration(limit, node_visited, &iteration_node_visited);
+        }
+
+        let found = AtomicBool::new(false);
+        let shared_node_count = AtomicUsize::new(node_visited);
+        let shared_solution_count = AtomicUsize::new(0);
+        let results: Vec<_> = prefixes
+            .par_iter()
+            .enumerate()
+            .zip(caches.par_iter())
+            .map(|((i, prefix), cache)| {
+                let finished_min_f = checkpoint
+                    .lock()
+                    .expect("Error when locking checkpoint")
+                    .finished_subtrees
+                    .iter()
+                    .find_map(|&(subtree, min_f)| (subtree == i).then_some(min_f));
+                if let Some(min_f) = finished_min_f {
+                    // already searched at this limit before the checkpoint
+                    return (Some(min_f), Vec::new(), 0, 0, None);
+                }
+                let mut cache = cache.lock().expect("Error when locking heuristic cache");
+                let mut evaluate = |cube: &Cube| match &mut *cache {
+                    None => heuristic_function(cube),
+                    Some(cache) => cache.get_or_evaluate(cube, heuristic_function),
+                };
+                let mut dfs =
+                    DepthFirstSearch::new(init_cube.clone(), config, start_time, &mut evaluate);
+                dfs.prefix = prefix;
+                dfs.stop = Some(&found);
+                dfs.shared_node_count = Some(&shared_node_count);
+                dfs.shared_solution_count = Some(&shared_solution_count);
+                dfs.progress_line = progress_line.as_ref();
+                dfs.progress_offset = resumed_node_visited;
+
+                let min_f = dfs.search(0, limit);
+                if min_f.is_none() {
+                    // the threads found enough solutions
+                    found.store(true, Ordering::Relaxed);
+                }
+                // count the nodes since the last check, so that small subtrees count too
+                shared_node_count
+                    .fetch_add(dfs.node_visited % STOP_CHECK_INTERVAL, Ordering::Relaxed);
+                let is_finished = dfs.interrupted.is_none()
+                    && dfs.solutions.is_empty()
+                    && !found.load(Ordering::Relaxed);
+                if let (true, Some(min_f)) = (is_finished, min_f) {
+                    let mut checkpoint = checkpoint.lock().expect("Error when locking checkpoint");
+                    checkpoint.finished_subtrees.push((i, min_f));
+                    checkpoint.node_visited += dfs.node_visited;
+                    checkpoint.max_depth = usize::max(checkpoint.max_depth, dfs.max_depth);
+                    checkpoint.save(config, fingerprint);
+                }
+                (
+                    min_f,
+                    dfs.solutions,
+                    dfs.node_visited,
+                    dfs.max_depth,
+                    dfs.interrupted,
+                )
+            })
+            .collect();
+        let iteration_nodes = results.iter().map(|(_, _, n, _, _)| n).sum::<usize>();
+        iteration_node_visited.push(iteration_nodes);
+        log::debug!(
+            "IDA* searched limit {limit} in {iteration_nodes} nodes over {} subtrees",
+            prefixes.len()
+        );
+        node_visited += iteration_nodes;
+        max_depth = results
+            .iter()
+            .map(|(_, _, _, depth, _)| *depth)
+            .fold(max_depth, usize::max);
 
-            // add children to node_stack
-            let node_ptr = Rc::new(RefCell::new(node));
-            node_stack.extend(Node::generate_children(node_ptr).into_iter());
+        // if we found solutions, returns the shortest ones
+        let mut solutions: Vec<Vec<Turn>> = results
+            .iter()
+            .flat_map(|(_, solutions, _, _, _)| solutions.iter().cloned())
+            .collect();
+        if !solutions.is_empty() {
+            if print_progress {
+                println!();
+            }
+            solutions.sort_by_key(Vec::len);
+            solutions.truncate(config.max_solutions);
+            let mut result = SearchResult {
+                solution_len: Some(solutions[0].len()),
+                solution: Some(solutions[0].clone()),
+                node_visited: resumed_node_visited + node_visited,
+                wall_time: start_time.elapsed(),
+                status: SearchStatus::Solved,
+                weight: config.weight,
+                possibly_suboptimal: config.is_possibly_suboptimal(),
+                verified: false,
+                bound,
+                solutions,
+                iterations: iteration_node_visited.len(),
+                iteration_node_visited,
+                max_depth,
+                peak_stack_size: max_depth + 1,
+            };
+            if config.verify {
+                result.verify(&init_cube, &config.goal);
+            }
+            SearchCheckpoint::remove(config);
+            return result;
         }
+        interrupted = results
+            .iter()
+            .find_map(|(_, _, _, _, interrupted)| *interrupted);
+
         // increase the limit
-        limit = min_f;
-        if limit > GIVE_UP_LIMIT {
+        limit = results
+            .iter()
+            .filter_map(|(min_f, _, _, _, _)| *min_f)
+            .min()
+            .unwrap_or(usize::MAX);
+        if interrupted.is_some() {
             break;
         }
     }
@@ -209,10 +1552,306 @@ pub fn idastar(
     if print_progress {
         println!();
     }
-    SearchResult {
+    if interrupted.is_none() {
+        SearchCheckpoint::remove(config);
+    }
+    let gave_up = SearchResult {
         solution: None,
         solution_len: None,
+        node_visited: resumed_node_visited + node_visited,
+        wall_time: start_time.elapsed(),
+        status: interrupted.unwrap_or(SearchStatus::GaveUp),
+        iterations: iteration_node_visited.len(),
+        iteration_node_visited,
+        max_depth,
+        peak_stack_size: max_depth + 1,
+        weight: config.weight,
+        possibly_suboptimal: config.is_possibly_suboptimal(),
+        verified: false,
+        bound,
+        solutions: Vec::new(),
+    };
+    config.fall_back(&init_cube, gave_up)
+}
+
+/// Iterative deepening depth-first search, which is `idastar` without a heuristic. It's a
+/// baseline for how much the heuristics speed up the search.
+pub fn iddfs(init_cube: Cube, config: &SearchConfig) -> SearchResult {
+    idastar(init_cube, &no_heuristic, config)
+}
+
+/// Breadth-first search, which finds a shortest solution without a heuristic by expanding the
+/// states in order of their distance, never expanding a state twice.
+///
+/// Since it keeps every state it reached in memory, it's only practical for short scrambles,
+/// where it's a simple oracle to check the other searches against. States are told apart by their
+/// Zobrist hash. When looking for the solved state with a move set that allows it, states
+/// symmetric to one already reached are skipped too, since they are just as far from solved (see
+/// `Cube::symmetric_hash`). The search gives up past the give up limit of `config`, and stops on
+/// its time limit, cancellation token and budgets like `idastar`. The heuristic cache, weight,
+/// macros, number of solutions and checkpoint path of `config` are ignored.
+pub fn bfs(init_cube: Cube, config: &SearchConfig) -> SearchResult {
+    let start_time = Instant::now();
+    let deadline = config.time_limit.map(|time_limit| start_time + time_limit);
+
+    let use_symmetry =
+        config.goal == Goal::Solved && config.move_set.is_rotation_symmetric(init_cube.size());
+    let state_hash = |cube: &Cube| {
+        if use_symmetry {
+            cube.symmetric_hash()
+        } else {
+            cube.zobrist_hash()
+        }
+    };
+
+    // the parent and the turn from the parent of every state reached, to rebuild the solution
+    let mut parents: Vec<Option<(usize, Turn)>> = vec![None];
+    let mut visited = HashSet::from([state_hash(&init_cube)]);
+    let mut queue = VecDeque::from([(init_cube.clone(), 0, 0)]);
+    let mut node_visited: usize = 0;
+    let mut status = SearchStatus::GaveUp;
+    let mut goal_index = None;
+    let mut bound = 0;
+    let mut iteration_node_visited = Vec::new();
+    let mut peak_stack_size = queue.len();
+
+    // approximate number of bytes taken by each state reached and each state in the queue
+    let state_size = size_of::<u64>() + size_of::<Option<(usize, Turn)>>();
+    let queued_size = init_cube.memory_size() + 2 * size_of::<usize>();
+
+    while let Some((mut cube, index, depth)) = queue.pop_front() {
+        bound = depth;
+        if node_visited.is_multiple_of(STOP_CHECK_INTERVAL) {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                status = SearchStatus::TimedOut;
+                break;
+            }
+            if config
+                .cancel_token
+                .as_ref()
+                .is_some_and(|cancel_token| cancel_token.load(Ordering::Relaxed))
+            {
+                status = SearchStatus::Cancelled;
+                break;
+            }
+        }
+        let memory = visited.len() * state_size + queue.len() * queued_size;
+        if config
+            .node_budget
+            .is_some_and(|budget| node_visited >= budget)
+            || config
+                .memory_budget
+                .is_some_and(|memory_budget| memory >= memory_budget)
+        {
+            status = SearchStatus::BudgetExhausted;
+            break;
+        }
+        node_visited += 1;
+        if depth == iteration_node_visited.len() {
+            iteration_node_visited.push(0);
+        }
+        iteration_node_visited[depth] += 1;
+
+        if config.goal.is_reached(&cube) {
+            status = SearchStatus::Solved;
+            goal_index = Some(index);
+            break;
+        }
+        if depth == config.give_up_limit {
+            continue;
+        }
+        for turn in config.move_set.turns() {
+            cube.apply_turn(turn);
+            if visited.insert(state_hash(&cube)) {
+                queue.push_back((cube.clone(), parents.len(), depth + 1));
+                parents.push(Some((index, *turn)));
+            }
+            cube.apply_turn(&turn.get_reversed());
+        }
+        peak_stack_size = usize::max(peak_stack_size, queue.len());
+    }
+
+    // follow the parents back to the initial cube
+    let solution = goal_index.map(|mut index| {
+        let mut path = Vec::new();
+        while let Some((parent, turn)) = parents[index] {
+            path.push(turn);
+            index = parent;
+        }
+        path.reverse();
+        path
+    });
+    let mut result = SearchResult {
+        solution_len: solution.as_ref().map(Vec::len),
+        solutions: solution.iter().cloned().collect(),
+        solution,
         node_visited,
         wall_time: start_time.elapsed(),
+        status,
+        weight: 1.0,
+        possibly_suboptimal: false,
+        verified: false,
+        bound,
+        iterations: iteration_node_visited.len(),
+        iteration_node_visited,
+        max_depth: bound,
+        peak_stack_size,
+    };
+    if config.verify {
+        result.verify(&init_cube, &config.goal);
+    }
+    result
+}
+
+/// The exact depth table of the 2x2, once it's loaded or built.
+static DEPTH_TABLE_2X2: OnceLock<PatternDatabase> = OnceLock::new();
+
+/// Returns the table of the exact number of turns needed to solve every 2x2 state, loading it
+/// from `DEPTH_TABLE_2X2_PATH` the first time. Returns `None` if the table hasn't been built yet.
+pub fn depth_table_2x2() -> Option<&'static PatternDatabase> {
+    if let Some(table) = DEPTH_TABLE_2X2.get() {
+        return Some(table);
+    }
+    let path = Path::new(DEPTH_TABLE_2X2_PATH);
+    let table = PatternDatabase::load(Pattern::full(2), &SOLVER_TURNS, path).ok()?;
+    Some(DEPTH_TABLE_2X2.get_or_init(|| table))
+}
+
+/// Builds the exact depth table of the 2x2 and saves it to `DEPTH_TABLE_2X2_PATH`, resuming a
+/// previously interrupted build.
+pub fn build_depth_table_2x2() -> io::Result<&'static PatternDatabase> {
+    let path = Path::new(DEPTH_TABLE_2X2_PATH);
+    let table = PatternDatabase::build(Pattern::full(2), &SOLVER_TURNS, Some(path), true)?;
+    Ok(DEPTH_TABLE_2X2.get_or_init(|| table))
+}
+
+/// Returns the exact number of quarter turns needed to solve a 2x2, looked up in its depth table.
+/// Returns `None` if `cube` isn't a 2x2 or its state isn't in the table.
+pub fn distance_2x2(cube: &Cube, depth_table: &PatternDatabase) -> Option<usize> {
+    // recolor the cube like `solve_optimal_2x2`
+    let state = CubieCube::from_cube(cube)?.to_cube(2);
+    depth_table.lookup(&state)
+}
+
+/// Finds an optimal solution of a 2x2 using its exact depth table.
+///
+/// From the current state, one of the turns always leads to a state that is one turn closer to
+/// solved, so we greedily follow those turns without any search. The solution is checked if
+/// `verify` is true, see `SearchConfig::verify`. Returns `None` if `cube` isn't a 2x2 or its state
+/// isn't in the table.
+pub fn solve_optimal_2x2(
+    cube: &Cube,
+    depth_table: &PatternDatabase,
+    verify: bool,
+) -> Option<SearchResult> {
+    let start_time = Instant::now();
+    // recolor the cube so that the corner the solver turns never move is in place, since the
+    // table is only built for those states.
+    let mut state = CubieCube::from_cube(cube)?.to_cube(2);
+    let mut depth = depth_table.lookup(&state)?;
+    let mut node_visited = 1;
+
+    let mut path = Vec::with_capacity(depth);
+    while depth > 0 {
+        let (turn, next_state) = SOLVER_TURNS
+            .into_iter()
+            .map(|turn| {
+                let mut next_state = state.clone();
+                next_state.apply_turn(&turn);
+                (turn, next_state)
+            })
+            .find(|(_, next_state)| {
+                node_visited += 1;
+                depth_table.lookup(next_state) == Some(depth - 1)
+            })?;
+        path.push(turn);
+        state = next_state;
+        depth -= 1;
+    }
+
+    let mut result = SearchResult {
+        solution_len: Some(path.len()),
+        bound: path.len(),
+        iterations: 1,
+        iteration_node_visited: vec![node_visited],
+        max_depth: path.len(),
+        // only the current state is kept
+        peak_stack_size: 1,
+        solution: Some(path.clone()),
+        solutions: vec![path],
+        node_visited,
+        wall_time: start_time.elapsed(),
+        status: SearchStatus::Solved,
+        weight: 1.0,
+        possibly_suboptimal: false,
+        verified: false,
+    };
+    if verify {
+        result.verify(cube, &Goal::Solved);
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{seq::SliceRandom, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    /// Returns a 2x2 scrambled with `length` turns of `SOLVER_TURNS`, chosen with `seed`, so that
+    /// the default move set can solve it in at most `length` turns.
+    fn scrambled_2x2(length: usize, seed: u64) -> Cube {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut cube = Cube::new(2);
+        let scramble = (0..length).map(|_| *SOLVER_TURNS.choose(&mut rng).unwrap());
+        cube.apply_algorithm(scramble.collect());
+        cube
+    }
+
+    fn corner_distance(cube: &Cube) -> f32 {
+        coordinate::distance(cube).unwrap() as f32
+    }
+
+    #[test]
+    fn searches_agree_on_the_optimal_length() {
+        let config = SearchConfig::default();
+        for seed in 0..5 {
+            let cube = scrambled_2x2(3, seed);
+            let expected = bfs(cube.clone(), &config).solution_len;
+            assert!(expected.is_some_and(|len| len <= 3), "seed {seed}");
+            let results = [
+                idastar(cube.clone(), &corner_distance, &config),
+                parallel_idastar(cube.clone(), &corner_distance, &config),
+                iddfs(cube.clone(), &config),
+            ];
+            for result in results {
+                assert_eq!(result.solution_len, expected, "seed {seed}");
+            }
+        }
+    }
+
+    #[test]
+    fn max_solutions_are_distinct_and_optimal() {
+        // a scramble with at least three optimal solutions
+        let cube = scrambled_2x2(9, 5);
+        let config = SearchConfig {
+            max_solutions: 3,
+            ..SearchConfig::default()
+        };
+        let result = idastar(cube.clone(), &corner_distance, &config);
+        assert_eq!(result.solutions.len(), 3);
+        let distinct: HashSet<String> = result
+            .solutions
+            .iter()
+            .map(|solution| Turn::algo_string(solution))
+            .collect();
+        assert_eq!(distinct.len(), 3);
+        let optimal_len = idastar(cube, &corner_distance, &SearchConfig::default()).solution_len;
+        assert_eq!(result.solution_len, optimal_len);
+        assert!(result
+            .solutions
+            .iter()
+            .all(|solution| Some(solution.len()) == optimal_len));
     }
 }
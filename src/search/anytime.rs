@@ -0,0 +1,166 @@
+//! An anytime solver, which sends better and better solutions as it finds them.
+
+use std::sync::mpsc::Sender;
+
+use web_time::Instant;
+
+use super::{parallel_idastar, SearchConfig, SearchResult, SearchStatus};
+use crate::cube::Cube;
+
+/// Weights of the successive searches of `solve_anytime`, from the fastest to the optimal one.
+const ANYTIME_WEIGHTS: [f32; 4] = [2.0, 1.5, 1.2, 1.0];
+
+/// Solves `init_cube` with weighted IDA* searches of decreasing weights (see
+/// `SearchConfig::weight`), sending every solution that is shorter than the previous ones to
+/// `sender` as soon as it's found.
+///
+/// The heavily weighted searches find a solution quickly on hard states, and the following ones
+/// improve it until the time limit or the cancellation token of `config` stops them. The last
+/// search has a weight of 1 and only looks for solutions shorter than the best one, so if it
+/// ends, the best solution is optimal for an admissible heuristic. The weight and the checkpoint
+/// path of `config` are ignored, and its time limit and node budget apply to the whole solve.
+///
+/// Stops early if the receiver of `sender` is dropped. Returns the best solution found, with the
+/// nodes visited and the time taken by every search.
+pub fn solve_anytime(
+    init_cube: &Cube,
+    heuristic_function: &(dyn Fn(&Cube) -> f32 + Sync),
+    config: &SearchConfig,
+    sender: Sender<SearchResult>,
+) -> SearchResult {
+    let start_time = Instant::now();
+    let mut best: Option<SearchResult> = None;
+    let mut node_visited = 0;
+    let mut status = SearchStatus::GaveUp;
+    let mut bound = 0;
+    let mut iteration_node_visited = Vec::new();
+    let mut max_depth = 0;
+
+    for weight in ANYTIME_WEIGHTS {
+        let best_len = best.as_ref().and_then(|best| best.solution_len);
+        if best_len == Some(0) {
+            // nothing is shorter than not turning at all
+            break;
+        }
+        // the limits apply to the whole solve
+        let time_limit = config
+            .time_limit
+            .map(|time_limit| time_limit.saturating_sub(start_time.elapsed()));
+        let node_budget = config
+            .node_budget
+            .map(|node_budget| node_budget.saturating_sub(node_visited));
+        let give_up_limit = match best_len {
+            // an optimal search only has to look for shorter solutions
+            Some(best_len) if weight == 1.0 => best_len - 1,
+            _ => config.give_up_limit,
+        };
+        let result = parallel_idastar(
+            init_cube.clone(),
+            heuristic_function,
+            &SearchConfig {
+                weight,
+                time_limit,
+                node_budget,
+                give_up_limit,
+                max_solutions: 1,
+                checkpoint_path: None,
+                // giving up is how the last search proves that the best solution is optimal
+                fallback: false,
+                ..config.clone()
+            },
+        );
+        node_visited += result.node_visited;
+        status = result.status;
+        bound = result.bound;
+        iteration_node_visited.extend_from_slice(&result.iteration_node_visited);
+        max_depth = usize::max(max_depth, result.max_depth);
+
+        let improved = match (result.solution_len, best_len) {
+            (Some(len), Some(best_len)) => len < best_len,
+            (Some(_), None) => true,
+            _ => false,
+        };
+        if improved {
+            let result = SearchResult {
+                node_visited,
+                wall_time: start_time.elapsed(),
+                iterations: iteration_node_visited.len(),
+                iteration_node_visited: iteration_node_visited.clone(),
+                max_depth,
+                peak_stack_size: max_depth + 1,
+                ..result
+            };
+            if sender.send(result.clone()).is_err() {
+                // nobody is waiting for better solutions
+                return result;
+            }
+            best = Some(result);
+        }
+        if !matches!(status, SearchStatus::Solved | SearchStatus::GaveUp) {
+            break;
+        }
+    }
+
+    // if the optimal search wasn't interrupted, no solution is shorter than the best one, unless
+    // macros made it count moves instead of turns
+    let proven_optimal =
+        matches!(status, SearchStatus::Solved | SearchStatus::GaveUp) && config.macros.is_empty();
+    match best {
+        Some(best) => SearchResult {
+            node_visited,
+            wall_time: start_time.elapsed(),
+            status: SearchStatus::Solved,
+            possibly_suboptimal: !proven_optimal,
+            iterations: iteration_node_visited.len(),
+            iteration_node_visited,
+            max_depth,
+            peak_stack_size: max_depth + 1,
+            ..best
+        },
+        None => SearchResult {
+            solution: None,
+            solution_len: None,
+            node_visited,
+            wall_time: start_time.elapsed(),
+            status,
+            weight: 1.0,
+            possibly_suboptimal: false,
+            verified: false,
+            bound,
+            solutions: Vec::new(),
+            iterations: iteration_node_visited.len(),
+            iteration_node_visited,
+            max_depth,
+            peak_stack_size: max_depth + 1,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::{cube::Turn, search::coordinate};
+
+    #[test]
+    fn sends_shorter_and_shorter_solutions() {
+        let mut cube = Cube::new(2);
+        let scramble = "R U F' R2 U' F R' U2 F2 R U' F'";
+        cube.apply_algorithm(Turn::parse_algorithm(scramble.split(' ').collect()).unwrap());
+        let heuristic = |cube: &Cube| coordinate::distance(cube).unwrap() as f32;
+        let (sender, receiver) = mpsc::channel();
+        let result = solve_anytime(&cube, &heuristic, &SearchConfig::default(), sender);
+
+        let lengths: Vec<usize> = receiver
+            .iter()
+            .map(|solution| solution.solution_len.unwrap())
+            .collect();
+        assert!(!lengths.is_empty());
+        assert!(
+            lengths.windows(2).all(|pair| pair[1] < pair[0]),
+            "{lengths:?}"
+        );
+        assert_eq!(result.solution_len, lengths.last().copied());
+    }
+}
@@ -0,0 +1,225 @@
+//! Checkpoints of IDA*, so that an interrupted search can be resumed.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, Read, Write},
+    path::Path,
+};
+
+use super::{
+    persist::{self, read_u64, read_usize},
+    SearchConfig,
+};
+use crate::cube::{Cube, FaceDir, TurnDir};
+
+/// Magic bytes at the start of a search checkpoint file.
+const FILE_MAGIC: &[u8; 4] = b"RIDA";
+
+/// The progress of an IDA* search, as stored on disk, so that an interrupted search can resume
+/// from the limit it was at instead of starting over from the heuristic of the initial cube.
+pub struct SearchCheckpoint {
+    /// The limit of the iteration in progress.
+    pub bound: usize,
+    /// Nodes visited before the iteration in progress, and by its finished subtrees.
+    pub node_visited: usize,
+    /// Nodes visited by every finished iteration.
+    pub iteration_node_visited: Vec<usize>,
+    /// Depth of the deepest node visited so far.
+    pub max_depth: usize,
+    /// Subtrees of `parallel_idastar` (as indices of its split prefixes) that were searched at
+    /// `bound` without finding a solution, with the smallest evaluation that exceeded it.
+    pub finished_subtrees: Vec<(usize, usize)>,
+}
+impl SearchCheckpoint {
+    /// Returns a number identifying the search of `init_cube` with `config`, so that we don't
+    /// resume a checkpoint written for another cube or another search. The heuristic can't be
+    /// identified, so a search has to be resumed with the same heuristic.
+    pub fn fingerprint(init_cube: &Cube, config: &SearchConfig) -> u64 {
+        let mut values = vec![init_cube.size()];
+        values.extend(init_cube.facelet_codes().iter().map(|&c| c as usize));
+        values.extend(format!("{:?}", config.goal).bytes().map(usize::from));
+        values.push(config.weight.to_bits() as usize);
+        for action in config.actions() {
+            values.push(action.len());
+            for turn in action {
+                let turn_dir = match turn.get_turn_dir() {
+                    TurnDir::Clockwise => 1,
+                    TurnDir::Half => 2,
+                    TurnDir::CounterClockwise => 3,
+                };
+                values.extend([
+                    FaceDir::facelet_index(turn.get_face_dir()),
+                    turn_dir,
+                    turn.get_layer(),
+                ]);
+            }
+        }
+        persist::fingerprint(values)
+    }
+
+    /// Reads the checkpoint of the search of `config` if it has one, logging why if it can't be
+    /// resumed.
+    pub fn resume(config: &SearchConfig, fingerprint: u64) -> Option<SearchCheckpoint> {
+        let path = config.checkpoint_path.as_deref()?;
+        if !path.exists() {
+            return None;
+        }
+        match SearchCheckpoint::read(path, fingerprint) {
+            Ok(checkpoint) => {
+                log::info!(
+                    "Resuming from {} at limit {}",
+                    path.display(),
+                    checkpoint.bound
+                );
+                Some(checkpoint)
+            }
+            Err(e) => {
+                log::warn!("Can't resume from {}: {e}", path.display());
+                None
+            }
+        }
+    }
+
+    /// Writes the checkpoint to the checkpoint path of `config`, if it has one. A failed write
+    /// is logged, but doesn't stop the search.
+    pub fn save(&self, config: &SearchConfig, fingerprint: u64) {
+        let Some(path) = config.checkpoint_path.as_deref() else {
+            return;
+        };
+        if let Err(e) = self.write(path, fingerprint) {
+            log::warn!("Can't write the checkpoint to {}: {e}", path.display());
+        }
+    }
+
+    /// Removes the checkpoint of `config`, once its search ended and doesn't need to be resumed.
+    pub fn remove(config: &SearchConfig) {
+        if let Some(path) = config.checkpoint_path.as_deref() {
+            // there's nothing to remove if the search ended before its first checkpoint
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    /// Writes the checkpoint to `path`, without leaving a corrupted file if the write is
+    /// interrupted.
+    fn write(&self, path: &Path, fingerprint: u64) -> io::Result<()> {
+        persist::write_atomically(path, |writer| {
+            writer.write_all(FILE_MAGIC)?;
+            writer.write_all(&fingerprint.to_le_bytes())?;
+            for value in [self.bound, self.node_visited, self.max_depth] {
+                writer.write_all(&(value as u64).to_le_bytes())?;
+            }
+            writer.write_all(&(self.iteration_node_visited.len() as u64).to_le_bytes())?;
+            for nodes in self.iteration_node_visited.iter() {
+                writer.write_all(&(*nodes as u64).to_le_bytes())?;
+            }
+            writer.write_all(&(self.finished_subtrees.len() as u64).to_le_bytes())?;
+            for (subtree, min_f) in self.finished_subtrees.iter() {
+                writer.write_all(&(*subtree as u64).to_le_bytes())?;
+                writer.write_all(&(*min_f as u64).to_le_bytes())?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Reads the checkpoint at `path`, checking that it was written for `fingerprint`.
+    fn read(path: &Path, fingerprint: u64) -> io::Result<SearchCheckpoint> {
+        let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != FILE_MAGIC {
+            return Err(invalid("not a search checkpoint file"));
+        }
+        if read_u64(&mut reader)? != fingerprint {
+            return Err(invalid("checkpoint was written for another search"));
+        }
+        let bound = read_usize(&mut reader)?;
+        let node_visited = read_usize(&mut reader)?;
+        let max_depth = read_usize(&mut reader)?;
+
+        // the counts aren't trusted to preallocate, since a corrupted file could ask for any size
+        let num_iterations = read_usize(&mut reader)?;
+        let mut iteration_node_visited = Vec::new();
+        for _ in 0..num_iterations {
+            iteration_node_visited.push(read_usize(&mut reader)?);
+        }
+
+        let num_finished_subtrees = read_usize(&mut reader)?;
+        let mut finished_subtrees = Vec::new();
+        for _ in 0..num_finished_subtrees {
+            finished_subtrees.push((read_usize(&mut reader)?, read_usize(&mut reader)?));
+        }
+
+        Ok(SearchCheckpoint {
+            bound,
+            node_visited,
+            iteration_node_visited,
+            max_depth,
+            finished_subtrees,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::Turn;
+
+    fn checkpoint_config(name: &str) -> SearchConfig {
+        let file_name = format!("rubiks-checkpoint-test-{name}-{}", std::process::id());
+        SearchConfig {
+            checkpoint_path: Some(std::env::temp_dir().join(file_name)),
+            ..SearchConfig::default()
+        }
+    }
+
+    #[test]
+    fn resumes_what_it_saved() {
+        let config = checkpoint_config("round-trip");
+        let fingerprint = SearchCheckpoint::fingerprint(&Cube::new(2), &config);
+        let saved = SearchCheckpoint {
+            bound: 7,
+            node_visited: 12345,
+            iteration_node_visited: vec![1, 18, 243],
+            max_depth: 6,
+            finished_subtrees: vec![(0, 8), (5, 9)],
+        };
+        saved.save(&config, fingerprint);
+        let resumed = SearchCheckpoint::resume(&config, fingerprint);
+        SearchCheckpoint::remove(&config);
+
+        let resumed = resumed.expect("Error when resuming the checkpoint");
+        assert_eq!(resumed.bound, saved.bound);
+        assert_eq!(resumed.node_visited, saved.node_visited);
+        assert_eq!(resumed.iteration_node_visited, saved.iteration_node_visited);
+        assert_eq!(resumed.max_depth, saved.max_depth);
+        assert_eq!(resumed.finished_subtrees, saved.finished_subtrees);
+    }
+
+    #[test]
+    fn rejects_another_search() {
+        let config = checkpoint_config("mismatch");
+        let mut scrambled = Cube::new(2);
+        scrambled.apply_algorithm(Turn::parse_algorithm(vec!["R", "U"]).unwrap());
+        let fingerprint = SearchCheckpoint::fingerprint(&scrambled, &config);
+        let other_fingerprint = SearchCheckpoint::fingerprint(&Cube::new(2), &config);
+        assert_ne!(fingerprint, other_fingerprint);
+
+        let checkpoint = SearchCheckpoint {
+            bound: 3,
+            node_visited: 100,
+            iteration_node_visited: vec![10, 90],
+            max_depth: 3,
+            finished_subtrees: Vec::new(),
+        };
+        checkpoint.save(&config, fingerprint);
+        let path = config.checkpoint_path.as_deref().unwrap();
+        let error = SearchCheckpoint::read(path, other_fingerprint).err();
+        let resumed = SearchCheckpoint::resume(&config, other_fingerprint);
+        SearchCheckpoint::remove(&config);
+
+        assert_eq!(error.map(|e| e.kind()), Some(io::ErrorKind::InvalidData));
+        assert!(resumed.is_none());
+    }
+}
@@ -0,0 +1,361 @@
+//! Move and distance tables of the coordinates of `cube::cubie`, and IDA* over the corner
+//! coordinates of the 2x2.
+
+use std::sync::OnceLock;
+
+use web_time::Instant;
+
+use super::{Goal, SearchConfig, SearchResult, SearchStatus, StopLimits, STOP_CHECK_INTERVAL};
+use crate::cube::{
+    cubie::{CubieCube, NUM_CORNER_PERM, NUM_FLIP, NUM_SLICE, NUM_TWIST},
+    Cube, FaceDir, Turn, TurnDir,
+};
+
+/// Number of moves in the half-turn metric: each face turned a quarter, half or three quarters.
+pub const NUM_MOVES: usize = 18;
+
+/// Value of a distance table entry that hasn't been reached yet.
+const UNKNOWN: u8 = u8::MAX;
+
+/// Move tables of the coordinates: `table[coord][m]` is the coordinate after applying move `m`.
+///
+/// Move `m` turns the face `FaceDir::FACELET_ORDER[m / 3]` clockwise `m % 3 + 1` times.
+pub struct MoveTables {
+    /// Of the corner orientation, see `CubieCube::twist`.
+    pub twist: Vec<[u16; NUM_MOVES]>,
+    /// Of the edge orientation, see `CubieCube::flip`.
+    pub flip: Vec<[u16; NUM_MOVES]>,
+    /// Of the positions of the UD-slice edges, see `CubieCube::slice`.
+    pub slice: Vec<[u16; NUM_MOVES]>,
+    /// Of the corner permutation, see `CubieCube::corner_perm`.
+    pub corner_perm: Vec<[u16; NUM_MOVES]>,
+}
+impl MoveTables {
+    /// Returns the move tables, building them the first time they're needed.
+    pub fn get() -> &'static MoveTables {
+        static MOVE_TABLES: OnceLock<MoveTables> = OnceLock::new();
+        MOVE_TABLES.get_or_init(|| MoveTables {
+            twist: build_move_table(NUM_TWIST, CubieCube::twist, CubieCube::set_twist),
+            flip: build_move_table(NUM_FLIP, CubieCube::flip, CubieCube::set_flip),
+            slice: build_move_table(NUM_SLICE, CubieCube::slice, CubieCube::set_slice),
+            corner_perm: build_move_table(
+                NUM_CORNER_PERM,
+                CubieCube::corner_perm,
+                CubieCube::set_corner_perm,
+            ),
+        })
+    }
+}
+
+/// Returns the turn of the half-turn metric move `m`.
+pub fn move_turn(m: usize) -> Turn {
+    let turn_dir = match m % 3 {
+        0 => TurnDir::Clockwise,
+        1 => TurnDir::Half,
+        _ => TurnDir::CounterClockwise,
+    };
+    Turn::new(FaceDir::FACELET_ORDER[m / 3], turn_dir)
+}
+
+/// Returns the half-turn metric move of a face turn, the reverse of `move_turn`.
+pub fn turn_move(turn: &Turn) -> usize {
+    let power = match turn.get_turn_dir() {
+        TurnDir::Clockwise => 0,
+        TurnDir::Half => 1,
+        TurnDir::CounterClockwise => 2,
+    };
+    3 * FaceDir::facelet_index(turn.get_face_dir()) + power
+}
+
+/// Builds the move table of the coordinate read by `get` and written by `set`, which has `n`
+/// possible values.
+fn build_move_table(
+    n: usize,
+    get: fn(&CubieCube) -> usize,
+    set: fn(&mut CubieCube, usize),
+) -> Vec<[u16; NUM_MOVES]> {
+    let mut table = vec![[0; NUM_MOVES]; n];
+    for (coord, row) in table.iter_mut().enumerate() {
+        let mut cube = CubieCube::SOLVED;
+        set(&mut cube, coord);
+        for (face, face_turn) in CubieCube::face_turns().iter().enumerate() {
+            for power in 0..3 {
+                cube = cube.multiply(face_turn);
+                row[3 * face + power] = get(&cube) as u16;
+            }
+            // the fourth turn brings the face back for the next one
+            cube = cube.multiply(face_turn);
+        }
+    }
+    table
+}
+
+/// Exact distance tables of (combinations of) coordinates, in the half-turn metric.
+///
+/// Each table is the number of moves needed to solve its coordinates, ignoring the rest of the
+/// cube. This is the same idea as the walking distance of sliding puzzles: the pieces are only
+/// tracked by a few properties (orientation, which slice they're in), so the tables are small but
+/// still much more informed than counting misplaced stickers.
+pub struct DistanceTables {
+    /// Corner orientation combined with the positions of the UD-slice edges, indexed by
+    /// `twist * NUM_SLICE + slice`.
+    pub twist_slice: Vec<u8>,
+    /// Edge orientation combined with the positions of the UD-slice edges, indexed by
+    /// `flip * NUM_SLICE + slice`.
+    pub flip_slice: Vec<u8>,
+    /// Corner orientation alone, for the 2x2.
+    pub twist: Vec<u8>,
+    /// Corner permutation alone.
+    pub corner_perm: Vec<u8>,
+}
+impl DistanceTables {
+    /// Returns the distance tables, building them the first time they're needed.
+    pub fn get() -> &'static DistanceTables {
+        static DISTANCE_TABLES: OnceLock<DistanceTables> = OnceLock::new();
+        DISTANCE_TABLES.get_or_init(|| {
+            let move_tables = MoveTables::get();
+            DistanceTables {
+                twist_slice: build_distance_table(&move_tables.twist, &move_tables.slice),
+                flip_slice: build_distance_table(&move_tables.flip, &move_tables.slice),
+                twist: build_distance_table(&move_tables.twist, &[[0; NUM_MOVES]]),
+                corner_perm: build_distance_table(&move_tables.corner_perm, &[[0; NUM_MOVES]]),
+            }
+        })
+    }
+}
+
+/// Builds the distance table of the pair of coordinates with move tables `first` and `second`,
+/// indexed by `first_coord * second.len() + second_coord`, with a breadth-first search from the
+/// solved state (both coordinates `0`).
+///
+/// Pass a table with a single row of zeros as `second` to get the table of `first` alone. The
+/// move tables can have fewer columns than `NUM_MOVES` if the search only uses some moves.
+pub fn build_distance_table<const M: usize>(first: &[[u16; M]], second: &[[u16; M]]) -> Vec<u8> {
+    let mut table = vec![UNKNOWN; first.len() * second.len()];
+    table[0] = 0;
+    let mut frontier = vec![0];
+    let mut depth = 0;
+    while !frontier.is_empty() {
+        let mut next_frontier = Vec::new();
+        for index in frontier {
+            let (c1, c2) = (index / second.len(), index % second.len());
+            for m in 0..M {
+                let child = first[c1][m] as usize * second.len() + second[c2][m] as usize;
+                if table[child] == UNKNOWN {
+                    table[child] = depth + 1;
+                    next_frontier.push(child);
+                }
+            }
+        }
+        frontier = next_frontier;
+        depth += 1;
+    }
+    table
+}
+
+/// Returns a lower bound on the number of half-turn metric moves needed to solve `cube`, or
+/// `None` if it isn't a 2x2 or 3x3.
+pub fn distance(cube: &Cube) -> Option<usize> {
+    let cubie_cube = CubieCube::from_cube(cube)?;
+    let tables = DistanceTables::get();
+
+    let corner_distance = usize::max(
+        tables.twist[cubie_cube.twist()] as usize,
+        tables.corner_perm[cubie_cube.corner_perm()] as usize,
+    );
+    if cube.size() == 2 {
+        return Some(corner_distance);
+    }
+
+    let slice = cubie_cube.slice();
+    let twist_slice = tables.twist_slice[cubie_cube.twist() * NUM_SLICE + slice];
+    let flip_slice = tables.flip_slice[cubie_cube.flip() * NUM_SLICE + slice];
+    Some(
+        [corner_distance, twist_slice as usize, flip_slice as usize]
+            .into_iter()
+            .max()
+            .unwrap(),
+    )
+}
+
+/// IDA* over the corner coordinates of a 2x2, see `solve_2x2`.
+struct CornerSearch<'a> {
+    /// The turns to expand each node with, with their moves.
+    moves: Vec<(Turn, usize)>,
+    path: Vec<Turn>,
+    weight: f32,
+    /// Most turns of a path, see `SearchConfig::give_up_limit`.
+    give_up_limit: usize,
+    node_visited: usize,
+    max_depth: usize,
+    limits: StopLimits<'a>,
+    interrupted: Option<SearchStatus>,
+}
+impl CornerSearch<'_> {
+    /// Checks if the search should stop, like `DepthFirstSearch`.
+    fn is_interrupted(&mut self) -> bool {
+        if self.interrupted.is_some() {
+            return true;
+        }
+        let check_clock = self.node_visited.is_multiple_of(STOP_CHECK_INTERVAL);
+        self.interrupted = self.limits.interruption(check_clock, || self.node_visited);
+        self.interrupted.is_some()
+    }
+
+    /// Searches the subtree of the coordinates for a solution within `limit`, like
+    /// `DepthFirstSearch::search`. Returns `None` once a solution is found (left in `path`).
+    fn search(&mut self, twist: usize, corner_perm: usize, limit: usize) -> Option<usize> {
+        if self.is_interrupted() {
+            return Some(usize::MAX);
+        }
+        self.node_visited += 1;
+        self.max_depth = usize::max(self.max_depth, self.path.len());
+        let tables = DistanceTables::get();
+        let h = u8::max(tables.twist[twist], tables.corner_perm[corner_perm]);
+        let f = self.path.len() + (self.weight * h as f32).ceil() as usize;
+        if f > limit {
+            return Some(f);
+        }
+        if twist == 0 && corner_perm == 0 {
+            return None;
+        }
+        // a weighted limit can exceed the give up limit, but the paths can't
+        if self.path.len() >= self.give_up_limit {
+            return Some(usize::MAX);
+        }
+
+        let move_tables = MoveTables::get();
+        let mut min_f = usize::MAX;
+        for i in 0..self.moves.len() {
+            let (turn, m) = self.moves[i];
+            if turn.is_redundant_after(&self.path) {
+                continue;
+            }
+            self.path.push(turn);
+            let twist = move_tables.twist[twist][m] as usize;
+            let corner_perm = move_tables.corner_perm[corner_perm][m] as usize;
+            min_f = usize::min(min_f, self.search(twist, corner_perm, limit)?);
+            self.path.pop();
+        }
+        Some(min_f)
+    }
+}
+
+/// Solves a 2x2 with IDA* over its corner coordinates instead of its stickers.
+///
+/// A node is only the corner orientation and permutation coordinates, so applying a turn is two
+/// lookups in the `MoveTables`, and the heuristic is two lookups in the `DistanceTables`. This is
+/// much faster than `idastar`, which turns the faces of a `Cube` at every node. The move set of
+/// `config` can only turn the up, right, and front faces, so that the down left back corner stays
+/// in place. The give up limit, weight, time limit, cancellation token and node budget of
+/// `config` are used.
+///
+/// Returns `None` if `cube` isn't a 2x2 or isn't readable, if `config` doesn't look for the
+/// solved state, if its move set turns other faces, or if it has macros or asks for more than
+/// one solution, which only `idastar` supports.
+pub fn solve_2x2(cube: &Cube, config: &SearchConfig) -> Option<SearchResult> {
+    let start_time = Instant::now();
+    if cube.size() != 2
+        || config.goal != Goal::Solved
+        || !config.macros.is_empty()
+        || config.max_solutions > 1
+    {
+        return None;
+    }
+    let cubie_cube = CubieCube::from_cube(cube)?;
+    let fixed_faces = [FaceDir::Down, FaceDir::Left, FaceDir::Back];
+    let moves = config
+        .move_set
+        .turns()
+        .iter()
+        .map(|turn| {
+            let is_face_turn = turn.get_layer() == 1;
+            (is_face_turn && !fixed_faces.contains(&turn.get_face_dir()))
+                .then(|| (*turn, turn_move(turn)))
+        })
+        .collect::<Option<Vec<(Turn, usize)>>>()?;
+
+    let mut search = CornerSearch {
+        moves,
+        path: Vec::new(),
+        weight: config.weight,
+        give_up_limit: config.give_up_limit,
+        node_visited: 0,
+        max_depth: 0,
+        limits: StopLimits::new(config, start_time),
+        interrupted: None,
+    };
+    let (twist, corner_perm) = (cubie_cube.twist(), cubie_cube.corner_perm());
+    let mut limit = 0;
+    let mut bound;
+    let mut iteration_node_visited = Vec::new();
+    let found = loop {
+        bound = limit;
+        let node_visited_before = search.node_visited;
+        let outcome = search.search(twist, corner_perm, limit);
+        iteration_node_visited.push(search.node_visited - node_visited_before);
+        match outcome {
+            None => break true,
+            Some(min_f) => limit = min_f,
+        }
+        if limit > config.max_limit() || search.interrupted.is_some() {
+            break false;
+        }
+    };
+
+    let solution = found.then_some(search.path);
+    let mut result = SearchResult {
+        solution_len: solution.as_ref().map(Vec::len),
+        solutions: solution.iter().cloned().collect(),
+        solution,
+        node_visited: search.node_visited,
+        wall_time: start_time.elapsed(),
+        status: match (found, search.interrupted) {
+            (true, _) => SearchStatus::Solved,
+            (false, Some(interrupted)) => interrupted,
+            (false, None) => SearchStatus::GaveUp,
+        },
+        weight: config.weight,
+        possibly_suboptimal: config.weight > 1.0,
+        verified: false,
+        bound,
+        iterations: iteration_node_visited.len(),
+        iteration_node_visited,
+        max_depth: search.max_depth,
+        // only the coordinates of the current path are kept
+        peak_stack_size: search.max_depth + 1,
+    };
+    if config.verify {
+        result.verify(cube, &config.goal);
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::idastar;
+
+    #[test]
+    fn solutions_are_as_short_as_idastar() {
+        let config = SearchConfig::default();
+        let heuristic = |cube: &Cube| distance(cube).unwrap() as f32;
+        for seed in 0..5 {
+            let cube = Cube::builder().size(2).scrambled(8, seed).build().unwrap();
+            let result = solve_2x2(&cube, &config).expect("Error when reading the 2x2");
+            assert_eq!(result.status, SearchStatus::Solved);
+            let expected = idastar(cube.clone(), &heuristic, &config);
+            assert_eq!(result.solution_len, expected.solution_len, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn leaves_macros_to_idastar() {
+        let cube = Cube::builder().size(2).scrambled(5, 0).build().unwrap();
+        let config = SearchConfig {
+            macros: vec![Turn::parse_algorithm("R U R' U'".split(' ').collect()).unwrap()],
+            ..SearchConfig::default()
+        };
+        assert!(solve_2x2(&cube, &config).is_none());
+    }
+}
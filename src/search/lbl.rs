@@ -0,0 +1,452 @@
+//! The layer by layer (beginner's) method of the 3x3, solved one stage at a time.
+
+use std::{fmt::Display, sync::OnceLock};
+
+use super::MoveSet;
+use crate::cube::{
+    cubie::{CubieCube, NUM_EDGES},
+    Cube, FaceDir, Turn, TurnDir,
+};
+
+/// Positions of the cross edges: DR, DF, DL, DB.
+const CROSS_EDGES: [usize; 4] = [4, 5, 6, 7];
+/// Positions of the first layer corners: DFR, DLF, DBL, DRB.
+const FIRST_LAYER_CORNERS: [usize; 4] = [4, 5, 6, 7];
+/// Positions of the second layer edges: FR, FL, BL, BR.
+const SECOND_LAYER_EDGES: [usize; 4] = [8, 9, 10, 11];
+/// Positions of the last layer corners: URF, UFL, ULB, UBR.
+const LAST_LAYER_CORNERS: [usize; 4] = [0, 1, 2, 3];
+/// Positions of the last layer edges: UR, UF, UL, UB.
+const LAST_LAYER_EDGES: [usize; 4] = [0, 1, 2, 3];
+
+/// A stage never needs more than this many moves (turns or algorithms).
+const MAX_STAGE_DEPTH: usize = 8;
+
+/// Algorithms of the first layer corners, one for each slot: DFR, DLF, DBL, DRB. Repeating the
+/// algorithm of a slot twists and eventually inserts the corner above it.
+const CORNER_ALGORITHMS: [&str; 4] = ["R U R' U'", "F U F' U'", "L U L' U'", "B U B' U'"];
+/// Algorithms that insert an edge from the last layer into the second layer, inserting to the
+/// right and to the left for each face.
+const SECOND_LAYER_ALGORITHMS: [&str; 8] = [
+    "U R U' R' U' F' U F",
+    "U' L' U L U F U' F'",
+    "U B U' B' U' R' U R",
+    "U' F' U F U R U' R'",
+    "U L U' L' U' B' U B",
+    "U' R' U R U B U' B'",
+    "U F U' F' U' L' U L",
+    "U' B' U B U L U' L'",
+];
+/// Algorithms that flip the last layer edges.
+const EDGE_ORIENTATION_ALGORITHMS: [&str; 2] = ["F R U R' U' F'", "F U R U' R' F'"];
+/// Sune and anti-Sune, which twist the last layer corners.
+const CORNER_ORIENTATION_ALGORITHMS: [&str; 2] = ["R U R' U R U2 R'", "R U2 R' U' R U' R'"];
+/// The A permutations, which cycle three last layer corners.
+const CORNER_PERMUTATION_ALGORITHMS: [&str; 2] =
+    ["R' F R' B2 R F' R' B2 R2", "R2 B2 R F R' B2 R F' R"];
+/// The U permutations, which cycle three last layer edges.
+const EDGE_PERMUTATION_ALGORITHMS: [&str; 2] =
+    ["R U' R U R U R U' R' U' R2", "R2 U R U R' U' R' U' R' U R'"];
+
+/// One stage of a layer by layer solution.
+pub struct Stage {
+    /// Name of the stage, like `Cross` or `OLL`.
+    pub name: &'static str,
+    /// The turns that solve the stage.
+    pub solution: Vec<Turn>,
+}
+
+/// A solution found with the layer by layer (beginner's) method, split into its stages.
+pub struct LayerByLayerSolution {
+    /// The stages, in the order they're solved.
+    pub stages: Vec<Stage>,
+}
+impl LayerByLayerSolution {
+    /// Returns the turns of all the stages, in order, merging the turns of the same face where a
+    /// stage ends and the next one starts.
+    pub fn turns(&self) -> Vec<Turn> {
+        let turns: Vec<Turn> = self
+            .stages
+            .iter()
+            .flat_map(|stage| stage.solution.iter().copied())
+            .collect();
+        merge_turns(&turns)
+    }
+}
+impl Display for LayerByLayerSolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for stage in self.stages.iter() {
+            writeln!(
+                f,
+                "{:<14}{}",
+                format!("{}:", stage.name),
+                Turn::algo_string(&stage.solution)
+            )?;
+        }
+        write!(f, "Total: {} turns", self.turns().len())
+    }
+}
+
+/// A move of a stage search, which is either a single turn or a whole algorithm.
+struct Macro {
+    turns: Vec<Turn>,
+    cubie_cube: CubieCube,
+    /// Face that the move turns if it's a single turn. Two single turns of the same face in a row
+    /// are never needed.
+    face_dir: Option<FaceDir>,
+}
+impl Macro {
+    fn turn(turn: Turn) -> Macro {
+        Macro {
+            turns: vec![turn],
+            cubie_cube: CubieCube::SOLVED.apply_turn(&turn),
+            face_dir: Some(turn.get_face_dir()),
+        }
+    }
+
+    fn algorithm(algo: &str) -> Macro {
+        let turns = Turn::parse_algorithm(algo.split_whitespace().collect())
+            .expect("Error when parsing algorithm");
+        Macro {
+            cubie_cube: CubieCube::SOLVED.apply_algorithm(&turns),
+            turns,
+            face_dir: None,
+        }
+    }
+}
+
+/// Merges consecutive turns of the same layer of the same face, e.g. `U2 U'` into `U`, dropping
+/// the ones that cancel out. The algorithms of the stages are joined by adjusting the last layer, so this
+/// happens often.
+fn merge_turns(turns: &[Turn]) -> Vec<Turn> {
+    let quarter_turns = |turn: &Turn| match turn.get_turn_dir() {
+        TurnDir::Clockwise => 1,
+        TurnDir::Half => 2,
+        TurnDir::CounterClockwise => 3,
+    };
+    let mut merged: Vec<Turn> = Vec::with_capacity(turns.len());
+    for turn in turns.iter() {
+        let Some(last) = merged.last() else {
+            merged.push(*turn);
+            continue;
+        };
+        if last.get_face_dir() != turn.get_face_dir() || last.get_layer() != turn.get_layer() {
+            merged.push(*turn);
+            continue;
+        }
+        let (face_dir, layer) = (turn.get_face_dir(), turn.get_layer());
+        let turn_dir = match (quarter_turns(last) + quarter_turns(turn)) % 4 {
+            1 => Some(TurnDir::Clockwise),
+            2 => Some(TurnDir::Half),
+            3 => Some(TurnDir::CounterClockwise),
+            _ => None,
+        };
+        merged.pop();
+        if let Some(turn_dir) = turn_dir {
+            merged.push(Turn::new_slice(face_dir, turn_dir, layer));
+        }
+    }
+    merged
+}
+
+fn is_corner_solved(cube: &CubieCube, i: usize) -> bool {
+    cube.cp[i] as usize == i && cube.co[i] == 0
+}
+
+fn is_edge_solved(cube: &CubieCube, i: usize) -> bool {
+    cube.ep[i] as usize == i && cube.eo[i] == 0
+}
+
+/// Returns the number of half turn metric turns needed to bring the edge at each position and
+/// orientation home: `table[edge][2 * position + orientation]`.
+///
+/// Since the turns only move one edge at a time here, these are small BFSs over 24 states. The
+/// maximum over the edges of a set is an admissible heuristic for solving the set.
+fn edge_distance_tables() -> &'static [[u8; 2 * NUM_EDGES]; NUM_EDGES] {
+    static TABLES: OnceLock<[[u8; 2 * NUM_EDGES]; NUM_EDGES]> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let moves: Vec<CubieCube> = MoveSet::HalfTurn
+            .turns()
+            .iter()
+            .map(|turn| CubieCube::SOLVED.apply_turn(turn))
+            .collect();
+        let mut tables = [[u8::MAX; 2 * NUM_EDGES]; NUM_EDGES];
+        for (edge, table) in tables.iter_mut().enumerate() {
+            // the move set is closed under inverse, so the distance from the home of the edge is
+            // the same as the distance to it
+            table[2 * edge] = 0;
+            let mut frontier = vec![(edge, 0)];
+            let mut depth = 0;
+            while !frontier.is_empty() {
+                depth += 1;
+                let mut next_frontier = Vec::new();
+                for (position, orientation) in frontier {
+                    for m in moves.iter() {
+                        let i = m.ep.iter().position(|&p| p as usize == position).unwrap();
+                        let next = (i, (orientation + m.eo[i] as usize) % 2);
+                        if table[2 * next.0 + next.1] == u8::MAX {
+                            table[2 * next.0 + next.1] = depth;
+                            next_frontier.push(next);
+                        }
+                    }
+                }
+                frontier = next_frontier;
+            }
+        }
+        tables
+    })
+}
+
+/// Returns the number of turns needed to bring every edge of `edges` home, ignoring the others.
+fn edges_distance(cube: &CubieCube, edges: &[usize]) -> usize {
+    let tables = edge_distance_tables();
+    (0..NUM_EDGES)
+        .filter(|&i| edges.contains(&(cube.ep[i] as usize)))
+        .map(|i| tables[cube.ep[i] as usize][2 * i + cube.eo[i] as usize] as usize)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Searches for the shortest sequence of `moves` that brings `cube` to a state where `goal`
+/// holds, using `heuristic` to prune (it has to be admissible). Returns the indices of the
+/// moves, or `None` if there's no solution within `MAX_STAGE_DEPTH` moves.
+fn search_stage(
+    cube: &CubieCube,
+    moves: &[Macro],
+    goal: &dyn Fn(&CubieCube) -> bool,
+    heuristic: &dyn Fn(&CubieCube) -> usize,
+) -> Option<Vec<usize>> {
+    fn search(
+        cube: &CubieCube,
+        moves: &[Macro],
+        goal: &dyn Fn(&CubieCube) -> bool,
+        heuristic: &dyn Fn(&CubieCube) -> usize,
+        limit: usize,
+        path: &mut Vec<usize>,
+    ) -> bool {
+        if goal(cube) {
+            return true;
+        }
+        if path.len() + heuristic(cube) > limit || path.len() == limit {
+            return false;
+        }
+        let prev_face_dir = path.last().and_then(|&m| moves[m].face_dir);
+        for (i, m) in moves.iter().enumerate() {
+            if m.face_dir.is_some() && m.face_dir == prev_face_dir {
+                continue;
+            }
+            path.push(i);
+            if search(
+                &cube.multiply(&m.cubie_cube),
+                moves,
+                goal,
+                heuristic,
+                limit,
+                path,
+            ) {
+                return true;
+            }
+            path.pop();
+        }
+        false
+    }
+
+    let mut path = Vec::new();
+    (0..=MAX_STAGE_DEPTH)
+        .find(|&limit| search(cube, moves, goal, heuristic, limit, &mut path))
+        .map(|_| path)
+}
+
+/// Solves the stage called `name` from `cube`, appending it to `stages` and returning the cube
+/// after the stage.
+fn solve_stage(
+    cube: CubieCube,
+    stages: &mut Vec<Stage>,
+    name: &'static str,
+    moves: &[Macro],
+    goal: &dyn Fn(&CubieCube) -> bool,
+    heuristic: &dyn Fn(&CubieCube) -> usize,
+) -> Option<CubieCube> {
+    let path = search_stage(&cube, moves, goal, heuristic)?;
+    let solution: Vec<Turn> = path
+        .iter()
+        .flat_map(|&m| moves[m].turns.iter().copied())
+        .collect();
+    let cube = cube.apply_algorithm(&solution);
+    match stages.last_mut() {
+        Some(stage) if stage.name == name => {
+            stage.solution.extend(solution);
+            stage.solution = merge_turns(&stage.solution);
+        }
+        _ => stages.push(Stage {
+            name,
+            solution: merge_turns(&solution),
+        }),
+    }
+    Some(cube)
+}
+
+/// Solves a 3x3 with the layer by layer method: cross, first layer corners, second layer,
+/// orientation of the last layer (OLL) and permutation of the last layer (PLL), each done with a
+/// few well known algorithms.
+///
+/// Each piece (or step of the last layer) is solved by a tiny search over the turns or
+/// algorithms of its stage, so the solution is far from optimal but is found instantly. The
+/// cross is built on the down (white) face. Returns `None` if `cube` isn't a 3x3 or isn't
+/// solvable.
+pub fn solve_layer_by_layer(cube: &Cube) -> Option<LayerByLayerSolution> {
+    if cube.size() != 3 {
+        return None;
+    }
+    let mut cube = CubieCube::from_cube(cube)?;
+    let mut stages = Vec::new();
+
+    let no_heuristic = |_: &CubieCube| 0;
+    let u_turns = || {
+        Turn::parse_algorithm(vec!["U", "U2", "U'"])
+            .unwrap()
+            .into_iter()
+            .map(Macro::turn)
+    };
+    let with_u_turns = |algos: &[&str]| -> Vec<Macro> {
+        u_turns()
+            .chain(algos.iter().map(|algo| Macro::algorithm(algo)))
+            .collect()
+    };
+
+    // cross: every turn is allowed, one edge at a time
+    let turns: Vec<Macro> = MoveSet::HalfTurn
+        .turns()
+        .iter()
+        .copied()
+        .map(Macro::turn)
+        .collect();
+    for k in 1..=CROSS_EDGES.len() {
+        let edges = &CROSS_EDGES[..k];
+        let goal = |c: &CubieCube| edges.iter().all(|&e| is_edge_solved(c, e));
+        let heuristic = |c: &CubieCube| edges_distance(c, edges);
+        cube = solve_stage(cube, &mut stages, "Cross", &turns, &goal, &heuristic)?;
+    }
+
+    // first layer corners, one at a time
+    let moves = with_u_turns(&CORNER_ALGORITHMS);
+    for k in 1..=FIRST_LAYER_CORNERS.len() {
+        let corners = &FIRST_LAYER_CORNERS[..k];
+        let goal = |c: &CubieCube| {
+            CROSS_EDGES.iter().all(|&e| is_edge_solved(c, e))
+                && corners.iter().all(|&i| is_corner_solved(c, i))
+        };
+        cube = solve_stage(cube, &mut stages, "Corners", &moves, &goal, &no_heuristic)?;
+    }
+    let first_layer_solved = |c: &CubieCube| {
+        CROSS_EDGES.iter().all(|&e| is_edge_solved(c, e))
+            && FIRST_LAYER_CORNERS.iter().all(|&i| is_corner_solved(c, i))
+    };
+
+    // second layer edges, one at a time
+    let moves = with_u_turns(&SECOND_LAYER_ALGORITHMS);
+    for k in 1..=SECOND_LAYER_EDGES.len() {
+        let edges = &SECOND_LAYER_EDGES[..k];
+        let goal =
+            |c: &CubieCube| first_layer_solved(c) && edges.iter().all(|&e| is_edge_solved(c, e));
+        cube = solve_stage(
+            cube,
+            &mut stages,
+            "Second layer",
+            &moves,
+            &goal,
+            &no_heuristic,
+        )?;
+    }
+    let first_two_layers_solved = |c: &CubieCube| {
+        first_layer_solved(c) && SECOND_LAYER_EDGES.iter().all(|&e| is_edge_solved(c, e))
+    };
+
+    // OLL, first the edges then the corners
+    let moves = with_u_turns(&EDGE_ORIENTATION_ALGORITHMS);
+    let goal = |c: &CubieCube| {
+        first_two_layers_solved(c) && LAST_LAYER_EDGES.iter().all(|&e| c.eo[e] == 0)
+    };
+    cube = solve_stage(cube, &mut stages, "OLL", &moves, &goal, &no_heuristic)?;
+    let moves = with_u_turns(&CORNER_ORIENTATION_ALGORITHMS);
+    let goal = |c: &CubieCube| {
+        first_two_layers_solved(c)
+            && LAST_LAYER_EDGES.iter().all(|&e| c.eo[e] == 0)
+            && LAST_LAYER_CORNERS.iter().all(|&i| c.co[i] == 0)
+    };
+    cube = solve_stage(cube, &mut stages, "OLL", &moves, &goal, &no_heuristic)?;
+
+    // PLL, first the corners then the edges
+    let moves = with_u_turns(&CORNER_PERMUTATION_ALGORITHMS);
+    let goal = |c: &CubieCube| {
+        first_two_layers_solved(c)
+            && LAST_LAYER_EDGES.iter().all(|&e| c.eo[e] == 0)
+            && LAST_LAYER_CORNERS.iter().all(|&i| is_corner_solved(c, i))
+    };
+    cube = solve_stage(cube, &mut stages, "PLL", &moves, &goal, &no_heuristic)?;
+    let moves = with_u_turns(&EDGE_PERMUTATION_ALGORITHMS);
+    let goal = |c: &CubieCube| *c == CubieCube::SOLVED;
+    solve_stage(cube, &mut stages, "PLL", &moves, &goal, &no_heuristic)?;
+
+    Some(LayerByLayerSolution { stages })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solves_scrambles() {
+        let scrambles = [
+            "R U R' U'",
+            "F2 D L' B U2 R D' F L2 B' U R2",
+            "L U' B2 R D F' U2 L' B D2 R' F U L2 D' B'",
+            "D' R2 F U' L B2 D R' U2 F' L' D2 B R U' F2 L D'",
+        ];
+        for scramble in scrambles {
+            let mut cube = Cube::new(3);
+            cube.apply_algorithm(Turn::parse_algorithm(scramble.split(' ').collect()).unwrap());
+            let solution = solve_layer_by_layer(&cube).expect("no solution");
+            assert_eq!(solution.stages.len(), 5);
+            cube.apply_algorithm(solution.turns());
+            assert!(cube.is_solved());
+        }
+    }
+
+    #[test]
+    fn only_solves_the_3x3() {
+        assert!(solve_layer_by_layer(&Cube::new(2)).is_none());
+    }
+
+    #[test]
+    fn merges_turns_across_stages() {
+        let algo = |algo: &str| Turn::parse_algorithm(algo.split(' ').collect()).unwrap();
+        let solution = LayerByLayerSolution {
+            stages: vec![
+                Stage {
+                    name: "Cross",
+                    solution: algo("R U'"),
+                },
+                Stage {
+                    name: "Corners",
+                    solution: algo("U2 F"),
+                },
+            ],
+        };
+        assert_eq!(solution.turns(), algo("R U F"));
+    }
+
+    #[test]
+    fn only_merges_turns_of_the_same_layer() {
+        let turns = Turn::parse_algorithm(vec!["R", "2R", "2R", "R'"]).unwrap();
+        let merged = merge_turns(&turns);
+        assert_eq!(
+            merged,
+            [
+                turns[0],
+                Turn::new_slice(FaceDir::Right, TurnDir::Half, 2),
+                turns[3]
+            ]
+        );
+    }
+}
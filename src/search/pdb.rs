@@ -0,0 +1,423 @@
+//! Pattern databases: exact distances of a subset of the stickers, used as heuristics.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, stdout, BufReader, Read, Write},
+    path::Path,
+};
+
+use rayon::prelude::*;
+
+use super::persist::{self, read_u128, read_u64, read_u8};
+use crate::cube::{Cube, Turn};
+
+/// Code used for the facelets of pieces that a pattern doesn't track.
+const UNTRACKED: u8 = 7;
+/// Number of bits used to pack one facelet into a key.
+const BITS_PER_FACELET: usize = 3;
+/// Number of frontier states expanded between two progress updates.
+const PROGRESS_CHUNK: usize = 1 << 16;
+/// Width of the progress bar, in characters.
+const PROGRESS_BAR_WIDTH: usize = 30;
+/// Magic bytes at the start of a pattern database file.
+const FILE_MAGIC: &[u8; 4] = b"RPDB";
+
+/// An abstraction of the cube that only looks at some of its pieces.
+///
+/// The pattern looks at the facelets of some piece positions (for example all the corners), and
+/// only cares about where a subset of the pieces (the tracked pieces) are. Facelets of the other
+/// pieces are all considered the same. Since turns move corners to corners and edges to edges,
+/// applying a turn to a pattern state gives another pattern state, so we can search the (much
+/// smaller) pattern space instead of the whole cube.
+pub struct Pattern {
+    size: usize,
+    /// Facelet indices (as in `Cube::facelet_codes`) that the pattern looks at.
+    facelets: Vec<usize>,
+    /// Groups of positions in `facelets` that belong to the same piece position.
+    groups: Vec<Vec<usize>>,
+    /// Sorted color codes of the pieces that are tracked.
+    tracked_pieces: Vec<Vec<u8>>,
+}
+impl Pattern {
+    /// Creates a pattern looking at the piece positions in `groups` (facelet indices grouped by
+    /// piece), tracking the first `num_tracked` pieces of the solved cube.
+    ///
+    /// Panics if the pattern has too many facelets to be packed into a key.
+    fn new(size: usize, groups: Vec<Vec<usize>>, num_tracked: usize) -> Pattern {
+        let solved_codes = Cube::new(size).facelet_codes();
+        let tracked_pieces = groups
+            .iter()
+            .take(num_tracked)
+            .map(|group| Pattern::piece_colors(group.iter().map(|&i| solved_codes[i])))
+            .collect();
+
+        let mut facelets = Vec::new();
+        let mut local_groups = Vec::with_capacity(groups.len());
+        for group in groups {
+            local_groups.push((facelets.len()..facelets.len() + group.len()).collect());
+            facelets.extend(group);
+        }
+        if facelets.len() * BITS_PER_FACELET > u128::BITS as usize {
+            panic!("pattern has too many facelets to be packed into a key");
+        }
+
+        Pattern {
+            size,
+            facelets,
+            groups: local_groups,
+            tracked_pieces,
+        }
+    }
+
+    /// Pattern of the whole cube, ignoring centers. Only small enough for 2x2.
+    pub fn full(size: usize) -> Pattern {
+        let groups: Vec<Vec<usize>> = Cube::piece_groups(size)
+            .into_iter()
+            .filter(|group| group.len() > 1)
+            .collect();
+        let num_tracked = groups.len();
+        Pattern::new(size, groups, num_tracked)
+    }
+
+    /// Pattern of all the corners.
+    pub fn corners(size: usize) -> Pattern {
+        let groups: Vec<Vec<usize>> = Cube::piece_groups(size)
+            .into_iter()
+            .filter(|group| group.len() == 3)
+            .collect();
+        let num_tracked = groups.len();
+        Pattern::new(size, groups, num_tracked)
+    }
+
+    /// Pattern of the edges, only tracking `num_tracked` of them. Only small enough for 3x3.
+    ///
+    /// The classic edge pattern databases track 6 or 7 of the 12 edges.
+    pub fn edges(size: usize, num_tracked: usize) -> Pattern {
+        let groups: Vec<Vec<usize>> = Cube::piece_groups(size)
+            .into_iter()
+            .filter(|group| group.len() == 2)
+            .collect();
+        Pattern::new(size, groups, num_tracked)
+    }
+
+    /// Returns the sorted colors of a piece, used to identify it.
+    fn piece_colors(colors: impl Iterator<Item = u8>) -> Vec<u8> {
+        let mut colors: Vec<u8> = colors.collect();
+        colors.sort_unstable();
+        colors
+    }
+
+    /// Returns the pattern state of `cube`.
+    fn get_state(&self, cube: &Cube) -> Vec<u8> {
+        let codes = cube.facelet_codes();
+        let mut state: Vec<u8> = self.facelets.iter().map(|&i| codes[i]).collect();
+        for group in self.groups.iter() {
+            let colors = Pattern::piece_colors(group.iter().map(|&i| state[i]));
+            if !self.tracked_pieces.contains(&colors) {
+                for &i in group.iter() {
+                    state[i] = UNTRACKED;
+                }
+            }
+        }
+        state
+    }
+
+    /// Returns the permutation of the pattern state that `turn` applies, in the same format as
+    /// `Turn::facelet_permutation`.
+    fn get_permutation(&self, turn: &Turn) -> Vec<usize> {
+        let perm = turn.facelet_permutation(self.size);
+        self.facelets
+            .iter()
+            .map(|&i| {
+                let from = perm[i];
+                self.facelets
+                    .iter()
+                    .position(|&j| j == from)
+                    .expect("turn moves a facelet out of the pattern")
+            })
+            .collect()
+    }
+
+    fn pack(state: &[u8]) -> u128 {
+        state
+            .iter()
+            .fold(0, |key, &c| (key << BITS_PER_FACELET) | c as u128)
+    }
+
+    fn unpack(&self, mut key: u128) -> Vec<u8> {
+        let mut state = vec![0; self.facelets.len()];
+        for c in state.iter_mut().rev() {
+            *c = (key & ((1 << BITS_PER_FACELET) - 1)) as u8;
+            key >>= BITS_PER_FACELET;
+        }
+        state
+    }
+
+    /// Returns a number identifying the pattern together with the turns used to build a database,
+    /// so that we don't resume or load a file built for something else.
+    fn fingerprint(&self, perms: &[Vec<usize>]) -> u64 {
+        let solved = self.get_state(&Cube::new(self.size));
+        let mut values = vec![self.size, self.facelets.len()];
+        values.extend(self.facelets.iter());
+        values.extend(solved.iter().map(|&c| c as usize));
+        for perm in perms {
+            values.extend(perm.iter());
+        }
+        persist::fingerprint(values)
+    }
+}
+
+/// A table of the exact number of turns needed to solve each state of a `Pattern`.
+///
+/// Since solving the whole cube also solves the pattern, the table value is an admissible
+/// heuristic for the whole cube.
+pub struct PatternDatabase {
+    pattern: Pattern,
+    table: HashMap<u128, u8>,
+}
+impl PatternDatabase {
+    /// Builds the database of `pattern` with a breadth-first search from the solved state, using
+    /// `turns` as the possible actions.
+    ///
+    /// Each layer of the search is expanded in parallel. If `checkpoint_path` is given, the table
+    /// and the frontier are written there after every layer, and an existing checkpoint is resumed
+    /// instead of starting over. A finished database is also a valid checkpoint, so this loads it
+    /// directly.
+    pub fn build(
+        pattern: Pattern,
+        turns: &[Turn],
+        checkpoint_path: Option<&Path>,
+        print_progress: bool,
+    ) -> io::Result<PatternDatabase> {
+        let perms: Vec<Vec<usize>> = turns.iter().map(|t| pattern.get_permutation(t)).collect();
+        let fingerprint = pattern.fingerprint(&perms);
+
+        let (mut depth, mut table, mut frontier) = match checkpoint_path {
+            Some(path) if path.exists() => {
+                let checkpoint = Checkpoint::read(path, fingerprint)?;
+                log::info!(
+                    "Resuming from {} at depth {} ({} states)",
+                    path.display(),
+                    checkpoint.depth,
+                    checkpoint.table.len()
+                );
+                (checkpoint.depth, checkpoint.table, checkpoint.frontier)
+            }
+            _ => {
+                let root = Pattern::pack(&pattern.get_state(&Cube::new(pattern.size)));
+                (0, HashMap::from([(root, 0)]), vec![root])
+            }
+        };
+
+        while !frontier.is_empty() {
+            let mut next_frontier = Vec::new();
+            for (i, chunk) in frontier.chunks(PROGRESS_CHUNK).enumerate() {
+                if print_progress {
+                    print_progress_bar(depth, i * PROGRESS_CHUNK, frontier.len());
+                }
+                let children = chunk
+                    .par_iter()
+                    .flat_map_iter(|&key| {
+                        let state = pattern.unpack(key);
+                        perms.iter().map(move |perm| {
+                            let child: Vec<u8> = perm.iter().map(|&j| state[j]).collect();
+                            Pattern::pack(&child)
+                        })
+                    })
+                    .filter(|child| !table.contains_key(child));
+                next_frontier.par_extend(children);
+            }
+            next_frontier.par_sort_unstable();
+            next_frontier.dedup();
+
+            depth += 1;
+            for &key in next_frontier.iter() {
+                table.insert(key, depth);
+            }
+            frontier = next_frontier;
+
+            if print_progress {
+                print_progress_bar(depth - 1, 1, 1);
+                println!(
+                    "\t{} states at depth {depth}, {} in total",
+                    frontier.len(),
+                    table.len()
+                );
+            }
+            if let Some(path) = checkpoint_path {
+                Checkpoint::write(path, fingerprint, depth, &table, &frontier)?;
+            }
+        }
+
+        Ok(PatternDatabase { pattern, table })
+    }
+
+    /// Loads a finished database of `pattern` built with `turns` from `path`.
+    pub fn load(pattern: Pattern, turns: &[Turn], path: &Path) -> io::Result<PatternDatabase> {
+        let perms: Vec<Vec<usize>> = turns.iter().map(|t| pattern.get_permutation(t)).collect();
+        let checkpoint = Checkpoint::read(path, pattern.fingerprint(&perms))?;
+        if !checkpoint.frontier.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "pattern database is not finished building",
+            ));
+        }
+        Ok(PatternDatabase {
+            pattern,
+            table: checkpoint.table,
+        })
+    }
+
+    /// Returns the number of turns needed to solve the pattern of `cube`, or `None` if the pattern
+    /// state can't be reached with the turns the database was built with.
+    pub fn lookup(&self, cube: &Cube) -> Option<usize> {
+        let key = Pattern::pack(&self.pattern.get_state(cube));
+        self.table.get(&key).map(|&d| d as usize)
+    }
+
+    /// Returns the number of states in the database.
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    /// Returns whether the database has no state.
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+}
+
+fn print_progress_bar(depth: u8, done: usize, total: usize) {
+    let filled = done * PROGRESS_BAR_WIDTH / total.max(1);
+    print!(
+        "\rDepth {depth:<3}[{}{}] {done}/{total}",
+        "#".repeat(filled),
+        " ".repeat(PROGRESS_BAR_WIDTH - filled)
+    );
+    stdout().flush().expect("Error when printing text");
+}
+
+/// The state of a breadth-first search, as stored on disk.
+struct Checkpoint {
+    depth: u8,
+    table: HashMap<u128, u8>,
+    frontier: Vec<u128>,
+}
+impl Checkpoint {
+    /// Writes the checkpoint to `path`, without leaving a corrupted file if the write is
+    /// interrupted.
+    fn write(
+        path: &Path,
+        fingerprint: u64,
+        depth: u8,
+        table: &HashMap<u128, u8>,
+        frontier: &[u128],
+    ) -> io::Result<()> {
+        persist::write_atomically(path, |writer| {
+            writer.write_all(FILE_MAGIC)?;
+            writer.write_all(&fingerprint.to_le_bytes())?;
+            writer.write_all(&[depth])?;
+            writer.write_all(&(table.len() as u64).to_le_bytes())?;
+            for (key, d) in table.iter() {
+                writer.write_all(&key.to_le_bytes())?;
+                writer.write_all(&[*d])?;
+            }
+            writer.write_all(&(frontier.len() as u64).to_le_bytes())?;
+            for key in frontier.iter() {
+                writer.write_all(&key.to_le_bytes())?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Reads the checkpoint at `path`, checking that it was written for `fingerprint`.
+    fn read(path: &Path, fingerprint: u64) -> io::Result<Checkpoint> {
+        let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let file = File::open(path)?;
+        // a corrupted length can't preallocate more entries than the file holds
+        let max_entries = file.metadata()?.len() as usize / size_of::<u128>();
+        let mut reader = BufReader::new(file);
+
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != FILE_MAGIC {
+            return Err(invalid("not a pattern database file"));
+        }
+        if read_u64(&mut reader)? != fingerprint {
+            return Err(invalid("pattern database was built for another pattern"));
+        }
+        let depth = read_u8(&mut reader)?;
+
+        let table_len = read_u64(&mut reader)? as usize;
+        let mut table = HashMap::with_capacity(table_len.min(max_entries));
+        for _ in 0..table_len {
+            let key = read_u128(&mut reader)?;
+            table.insert(key, read_u8(&mut reader)?);
+        }
+
+        let frontier_len = read_u64(&mut reader)? as usize;
+        let mut frontier = Vec::with_capacity(frontier_len.min(max_entries));
+        for _ in 0..frontier_len {
+            frontier.push(read_u128(&mut reader)?);
+        }
+
+        Ok(Checkpoint {
+            depth,
+            table,
+            frontier,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::{FaceDir, TurnDir};
+
+    /// Returns the `i`-th of the 12 quarter turns of the faces.
+    fn quarter_turn(i: usize) -> Turn {
+        let turn_dir = match i % 2 {
+            0 => TurnDir::Clockwise,
+            _ => TurnDir::CounterClockwise,
+        };
+        Turn::new(FaceDir::FACELET_ORDER[i / 2], turn_dir)
+    }
+
+    /// Database of where 2 edges of the 3x3 are, small enough to build in a test.
+    fn two_edges(checkpoint_path: Option<&Path>) -> PatternDatabase {
+        let turns: Vec<Turn> = (0..12).map(quarter_turn).collect();
+        PatternDatabase::build(Pattern::edges(3, 2), &turns, checkpoint_path, false)
+            .expect("Error when building the database")
+    }
+
+    #[test]
+    fn reaches_every_state_of_two_edges() {
+        // each edge can be at any of the 12 positions, flipped or not
+        assert_eq!(two_edges(None).len(), 24 * 22);
+    }
+
+    #[test]
+    fn lookup_is_a_lower_bound() {
+        let database = two_edges(None);
+        // every turn is of another face than the one before
+        let algo: Vec<Turn> = (0..10).map(|i| quarter_turn(i * 5 % 12)).collect();
+        for length in 0..=algo.len() {
+            let mut cube = Cube::new(3);
+            cube.apply_algorithm(algo[..length].to_vec());
+            let distance = database.lookup(&cube).expect("unreachable state");
+            assert!(distance <= length);
+        }
+    }
+
+    #[test]
+    fn loads_what_it_built() {
+        let path = std::env::temp_dir().join(format!("rubiks-pdb-test-{}", std::process::id()));
+        let built = two_edges(Some(&path));
+        let turns: Vec<Turn> = (0..12).map(quarter_turn).collect();
+        let loaded = PatternDatabase::load(Pattern::edges(3, 2), &turns, &path)
+            .expect("Error when loading the database");
+        std::fs::remove_file(&path).expect("Error when removing the database");
+        assert_eq!(loaded.len(), built.len());
+        assert_eq!(loaded.table, built.table);
+    }
+}
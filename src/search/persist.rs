@@ -0,0 +1,57 @@
+//! Helpers shared by the files that searches write to disk: the checkpoints of IDA* and the
+//! pattern databases.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Read, Write},
+    path::Path,
+};
+
+/// Hashes `values` with FNV-1a, since it's stable across runs and platforms unlike
+/// `DefaultHasher`, so that the hash can identify what a file was written for.
+pub(crate) fn fingerprint(values: impl IntoIterator<Item = usize>) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for v in values {
+        hash ^= v as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Writes a file to `path` with `write_contents`.
+///
+/// The file is written next to `path` first and then renamed, so an interrupted write never
+/// leaves a corrupted file behind.
+pub(crate) fn write_atomically(
+    path: &Path,
+    write_contents: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+) -> io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    let mut writer = BufWriter::new(File::create(&tmp_path)?);
+    write_contents(&mut writer)?;
+    writer.flush()?;
+    drop(writer);
+    fs::rename(tmp_path, path)
+}
+
+pub(crate) fn read_u8(reader: &mut impl Read) -> io::Result<u8> {
+    let mut buf = [0; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+pub(crate) fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+pub(crate) fn read_usize(reader: &mut impl Read) -> io::Result<usize> {
+    Ok(read_u64(reader)? as usize)
+}
+
+pub(crate) fn read_u128(reader: &mut impl Read) -> io::Result<u128> {
+    let mut buf = [0; 16];
+    reader.read_exact(&mut buf)?;
+    Ok(u128::from_le_bytes(buf))
+}
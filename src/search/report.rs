@@ -0,0 +1,129 @@
+//! `SolveReport`, a solve written for other programs.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use web_time::Instant;
+
+use super::SearchResult;
+use crate::cube::Turn;
+
+/// A solve as the `rubiks solve --json` command and the statistics of the searches write it, so
+/// that other programs can read it. The fields only change with `SolveReport::VERSION`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolveReport {
+    /// The `SolveReport::VERSION` of the fields.
+    pub version: u32,
+    /// Size of the cube, like 3 for the 3x3.
+    pub size: usize,
+    /// The turns of the scramble, like `R U R' U'`.
+    pub scramble: String,
+    /// Name of the solver, like `--solver` takes it.
+    pub solver: String,
+    /// Name of the heuristic, like `--heuristic` takes it, or `none` for a search without one,
+    /// like the ones of `search::no_heuristic`.
+    pub heuristic: String,
+    /// `no-solution` if the solver failed, like the two-phase algorithm or the layer by layer
+    /// method can, otherwise the name of the `SearchStatus`.
+    pub status: String,
+    /// The turns of the solution, like the scramble, or `None` if there's none.
+    pub solution: Option<String>,
+    /// The solutions were applied to the scrambled cube and checked, see `SearchResult::verify`.
+    pub verified: bool,
+    /// The statistics of the search, or `None` if the solver failed.
+    pub metrics: Option<SolveMetrics>,
+    /// How long the solve took.
+    pub timings: Timings,
+}
+impl SolveReport {
+    /// Version of the fields of the report, raised when one of them is removed, renamed or
+    /// changes meaning.
+    pub const VERSION: u32 = 1;
+
+    /// Reports the solve of the cube of size `size` scrambled by `scramble`, for which `solver`
+    /// returned `result` in `wall_time`.
+    pub fn new(
+        size: usize,
+        scramble: &[Turn],
+        solver: &str,
+        heuristic: &str,
+        result: Option<&SearchResult>,
+        wall_time: Duration,
+    ) -> SolveReport {
+        SolveReport {
+            version: SolveReport::VERSION,
+            size,
+            scramble: Turn::algo_string(scramble),
+            solver: solver.to_string(),
+            heuristic: heuristic.to_string(),
+            status: result
+                .map_or("no-solution", |result| result.status.name())
+                .to_string(),
+            solution: result
+                .and_then(|result| result.solution.as_deref())
+                .map(Turn::algo_string),
+            verified: result.is_some_and(|result| result.verified),
+            metrics: result.map(SolveMetrics::from),
+            timings: Timings::from(wall_time),
+        }
+    }
+}
+
+/// The statistics of a search in a `SolveReport`, see `SearchResult`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolveMetrics {
+    /// See `SearchResult::solution_len`.
+    pub solution_length: Option<usize>,
+    /// See `SearchResult::node_visited`.
+    pub node_visited: usize,
+    /// See `SearchResult::iterations`.
+    pub iterations: usize,
+    /// See `SearchResult::bound`.
+    pub bound: usize,
+    /// See `SearchResult::max_depth`.
+    pub max_depth: usize,
+    /// See `SearchResult::nodes_per_second`.
+    pub nodes_per_second: f64,
+    /// See `SearchResult::possibly_suboptimal`.
+    pub possibly_suboptimal: bool,
+    /// See `SearchResult::peak_stack_size`.
+    pub peak_stack_size: usize,
+    /// Nodes visited by each iteration.
+    pub iteration_node_visited: Vec<usize>,
+}
+impl From<&SearchResult> for SolveMetrics {
+    fn from(result: &SearchResult) -> Self {
+        SolveMetrics {
+            solution_length: result.solution_len,
+            node_visited: result.node_visited,
+            iterations: result.iterations,
+            bound: result.bound,
+            max_depth: result.max_depth,
+            nodes_per_second: result.nodes_per_second(),
+            possibly_suboptimal: result.possibly_suboptimal,
+            peak_stack_size: result.peak_stack_size,
+            iteration_node_visited: result.iteration_node_visited.clone(),
+        }
+    }
+}
+
+/// How long a solve or a scramble took, in a `SolveReport` or in what `rubiks scramble --json`
+/// prints.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Timings {
+    /// Wall time of the solve or the scramble, in milliseconds.
+    pub wall_time_ms: f64,
+}
+impl Timings {
+    /// Returns the timings of what started at `start_time` and just ended.
+    pub fn since(start_time: Instant) -> Timings {
+        Timings::from(start_time.elapsed())
+    }
+}
+impl From<Duration> for Timings {
+    fn from(wall_time: Duration) -> Self {
+        Timings {
+            wall_time_ms: wall_time.as_secs_f64() * 1000.0,
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! Searches that run on their own thread while a frontend keeps its UI thread responsive.
+//!
+//! Only built where `std` can spawn threads, so not for `wasm32-unknown-unknown`, where a page
+//! calls the solvers of the `wasm` module instead, which return once they're done.
+
+use std::{
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+    thread::{self, JoinHandle},
+};
+
+use super::{parallel_idastar, SearchConfig, SearchResult};
+use crate::cube::Cube;
+
+/// Progress of a running search, which the search updates at the start of every iteration (see
+/// `SearchConfig::progress`).
+#[derive(Default)]
+pub struct SearchProgress {
+    limit: AtomicUsize,
+    iterations: AtomicUsize,
+    node_visited: AtomicUsize,
+}
+impl SearchProgress {
+    /// Returns the limit of the iteration in progress.
+    pub fn limit(&self) -> usize {
+        self.limit.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of finished iterations.
+    pub fn iterations(&self) -> usize {
+        self.iterations.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of nodes visited by the finished iterations.
+    pub fn node_visited(&self) -> usize {
+        self.node_visited.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn update(&self, limit: usize, iterations: usize, node_visited: usize) {
+        self.limit.store(limit, Ordering::Relaxed);
+        self.iterations.store(iterations, Ordering::Relaxed);
+        self.node_visited.store(node_visited, Ordering::Relaxed);
+    }
+}
+
+/// The result of a search once it's done, or the payload of its panic, and the waker of the task
+/// waiting for it.
+#[derive(Default)]
+struct SolveState {
+    result: Option<thread::Result<SearchResult>>,
+    waker: Option<Waker>,
+}
+
+/// Handle of a search running on its own thread, see `solve_async`.
+///
+/// The result can be waited for with `wait`, or awaited since the handle is a `Future`, which
+/// doesn't need any particular async runtime. Both give `Err` with the payload of the panic if
+/// the search panicked, like `JoinHandle::join`.
+pub struct SolveHandle {
+    progress: Arc<SearchProgress>,
+    cancel_token: Arc<AtomicBool>,
+    state: Arc<Mutex<SolveState>>,
+    thread: Option<JoinHandle<()>>,
+}
+impl SolveHandle {
+    /// Returns the progress of the search.
+    pub fn progress(&self) -> &SearchProgress {
+        &self.progress
+    }
+
+    /// Asks the search to stop. It ends with the `Cancelled` status at the next check, so the
+    /// result still has to be waited for.
+    pub fn cancel(&self) {
+        self.cancel_token.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns true once the result is ready.
+    pub fn is_finished(&self) -> bool {
+        self.thread.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
+    /// Blocks until the search ends and returns its result.
+    pub fn wait(mut self) -> thread::Result<SearchResult> {
+        if let Some(thread) = self.thread.take() {
+            thread.join().expect("Error when joining the search thread");
+        }
+        self.state
+            .lock()
+            .expect("Error when locking the search result")
+            .result
+            .take()
+            .expect("Error when taking the search result")
+    }
+}
+impl Future for SolveHandle {
+    type Output = thread::Result<SearchResult>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self
+            .state
+            .lock()
+            .expect("Error when locking the search result");
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Starts solving `init_cube` with `parallel_idastar` on a new thread and returns right away, so
+/// that a frontend can keep its UI thread responsive.
+///
+/// The handle reports the progress of the search, cancels it, and gives its result. The
+/// cancellation token and the progress of `config` are replaced by the ones of the handle, or
+/// reused if `config` has them.
+pub fn solve_async(
+    init_cube: Cube,
+    heuristic_function: impl Fn(&Cube) -> f32 + Send + Sync + 'static,
+    mut config: SearchConfig,
+) -> SolveHandle {
+    let progress = config.progress.get_or_insert_with(Arc::default).clone();
+    let cancel_token = config.cancel_token.get_or_insert_with(Arc::default).clone();
+    let state = Arc::new(Mutex::new(SolveState::default()));
+
+    let thread = {
+        let state = state.clone();
+        thread::spawn(move || {
+            // the task waiting for the result would wait forever if the panic ended the thread
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                parallel_idastar(init_cube, &heuristic_function, &config)
+            }));
+            let mut state = state.lock().expect("Error when locking the search result");
+            state.result = Some(result);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        })
+    };
+
+    SolveHandle {
+        progress,
+        cancel_token,
+        state,
+        thread: Some(thread),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, task::Wake, thread::Thread};
+
+    use super::*;
+    use crate::{
+        cube::Turn,
+        search::{no_heuristic, single_l0, SearchStatus},
+    };
+
+    /// Wakes the thread that polls the future, see `block_on`.
+    struct ThreadWaker(Thread);
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Polls `future` on this thread until it's ready, parking between the polls.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let waker = Arc::new(ThreadWaker(thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    /// Returns a 2x2 turned by `algo`.
+    fn cube(algo: &str) -> Cube {
+        let mut cube = Cube::new(2);
+        cube.apply_algorithm(Turn::parse_algorithm(algo.split(' ').collect()).unwrap());
+        cube
+    }
+
+    #[test]
+    fn waits_for_the_solution() {
+        let handle = solve_async(cube("R U F'"), single_l0, SearchConfig::default());
+        let result = handle.wait().expect("the search panicked");
+        assert_eq!(result.solution_len, Some(3));
+    }
+
+    #[test]
+    fn awaits_the_solution() {
+        let handle = solve_async(cube("R U F'"), single_l0, SearchConfig::default());
+        let result = block_on(handle).expect("the search panicked");
+        assert_eq!(result.solution_len, Some(3));
+    }
+
+    #[test]
+    fn reports_the_progress() {
+        let handle = solve_async(cube("R U F' R2 U'"), single_l0, SearchConfig::default());
+        while !handle.is_finished() {
+            thread::yield_now();
+        }
+        let (limit, iterations) = (handle.progress().limit(), handle.progress().iterations());
+        let result = handle.wait().expect("the search panicked");
+        assert_eq!(limit, result.bound);
+        assert_eq!(iterations + 1, result.iterations);
+    }
+
+    #[test]
+    fn cancels_the_search() {
+        // without a heuristic, a 3x3 this far from solved isn't solved before the cancellation
+        let mut cube = Cube::new(3);
+        let algo = "R U F' L D2 B R' U2 F L' D B2";
+        cube.apply_algorithm(Turn::parse_algorithm(algo.split(' ').collect()).unwrap());
+        let handle = solve_async(cube, no_heuristic, SearchConfig::default());
+        handle.cancel();
+        let result = handle.wait().expect("the search panicked");
+        assert_eq!(result.status, SearchStatus::Cancelled);
+        assert_eq!(result.solution, None);
+    }
+
+    #[test]
+    fn hands_over_a_panic() {
+        let handle = solve_async(
+            cube("R U F'"),
+            |_: &Cube| -> f32 { panic!("broken heuristic") },
+            SearchConfig::default(),
+        );
+        assert!(block_on(handle).is_err());
+    }
+}
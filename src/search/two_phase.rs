@@ -0,0 +1,383 @@
+//! The two-phase algorithm of the 3x3, which finds short solutions quickly.
+
+use std::sync::OnceLock;
+
+use web_time::Instant;
+
+use super::{
+    coordinate::{build_distance_table, move_turn, DistanceTables, MoveTables, NUM_MOVES},
+    Goal, SearchResult, SearchStatus,
+};
+use crate::cube::{
+    cubie::{CubieCube, NUM_SLICE, NUM_SLICE_PERM, NUM_UD_EDGE_PERM},
+    Cube, Turn,
+};
+
+/// Default maximum length of the solutions of `solve_two_phase`. Solutions this long are found
+/// in milliseconds, while asking for shorter ones can take much longer.
+pub const DEFAULT_MAX_LENGTH: usize = 24;
+
+/// Number of nodes that the search visits after its first solution, looking for a shorter one.
+pub const IMPROVEMENT_NODES: usize = 2_000_000;
+
+/// Number of moves of the second phase.
+const NUM_PHASE2_MOVES: usize = 10;
+/// The moves that keep the cube in the subgroup of the second phase (U, D, R2, F2, L2, B2), as
+/// indices of the half-turn metric moves (see `MoveTables`).
+const PHASE2_MOVES: [usize; NUM_PHASE2_MOVES] = [0, 1, 2, 4, 7, 9, 10, 11, 13, 16];
+
+/// Move and distance tables of the coordinates of the second phase.
+struct Phase2Tables {
+    corner_perm: Vec<[u16; NUM_PHASE2_MOVES]>,
+    ud_edge_perm: Vec<[u16; NUM_PHASE2_MOVES]>,
+    slice_perm: Vec<[u16; NUM_PHASE2_MOVES]>,
+    /// Indexed by `corner_perm * NUM_SLICE_PERM + slice_perm`.
+    corner_slice: Vec<u8>,
+    /// Indexed by `ud_edge_perm * NUM_SLICE_PERM + slice_perm`.
+    edge_slice: Vec<u8>,
+}
+impl Phase2Tables {
+    fn get() -> &'static Phase2Tables {
+        static PHASE2_TABLES: OnceLock<Phase2Tables> = OnceLock::new();
+        PHASE2_TABLES.get_or_init(|| {
+            let corner_perm: Vec<[u16; NUM_PHASE2_MOVES]> = MoveTables::get()
+                .corner_perm
+                .iter()
+                .map(|row| PHASE2_MOVES.map(|m| row[m]))
+                .collect();
+            let ud_edge_perm = build_phase2_move_table(
+                NUM_UD_EDGE_PERM,
+                CubieCube::ud_edge_perm,
+                CubieCube::set_ud_edge_perm,
+            );
+            let slice_perm = build_phase2_move_table(
+                NUM_SLICE_PERM,
+                CubieCube::slice_perm,
+                CubieCube::set_slice_perm,
+            );
+            Phase2Tables {
+                corner_slice: build_distance_table(&corner_perm, &slice_perm),
+                edge_slice: build_distance_table(&ud_edge_perm, &slice_perm),
+                corner_perm,
+                ud_edge_perm,
+                slice_perm,
+            }
+        })
+    }
+}
+
+/// Returns the cube of the half-turn metric move `m`.
+fn move_cube(m: usize) -> CubieCube {
+    let face_turn = &CubieCube::face_turns()[m / 3];
+    (0..=m % 3).fold(CubieCube::SOLVED, |cube, _| cube.multiply(face_turn))
+}
+
+/// Builds the move table of a coordinate of the second phase, like `build_move_table` but only
+/// with `PHASE2_MOVES`, since the other moves take the coordinate out of its meaning.
+fn build_phase2_move_table(
+    n: usize,
+    get: fn(&CubieCube) -> usize,
+    set: fn(&mut CubieCube, usize),
+) -> Vec<[u16; NUM_PHASE2_MOVES]> {
+    let moves = PHASE2_MOVES.map(move_cube);
+    let mut table = vec![[0; NUM_PHASE2_MOVES]; n];
+    for (coord, row) in table.iter_mut().enumerate() {
+        let mut cube = CubieCube::SOLVED;
+        set(&mut cube, coord);
+        for (entry, m) in row.iter_mut().zip(moves.iter()) {
+            *entry = get(&cube.multiply(m)) as u16;
+        }
+    }
+    table
+}
+
+/// Checks if move `m` is redundant after move `prev`: turning the same face twice in a row, or
+/// opposite faces out of the order of `FaceDir::FACELET_ORDER` (see `Turn::is_redundant_after`).
+fn is_redundant(prev: Option<&usize>, m: usize) -> bool {
+    let Some(prev) = prev else {
+        return false;
+    };
+    let (face, prev_face) = (m / 3, prev / 3);
+    face == prev_face || ((face + 3) % 6 == prev_face && face < prev_face)
+}
+
+/// State of a two-phase search.
+struct TwoPhaseSearch {
+    cube: CubieCube,
+    /// Length of the longest solution to look for, one less than the best one once there's one.
+    max_length: usize,
+    phase1_path: Vec<usize>,
+    phase2_path: Vec<usize>,
+    /// The shortest whole solution found yet.
+    best: Option<Vec<usize>>,
+    /// Number of visited nodes after which the search stops once it has a solution.
+    node_limit: usize,
+    node_visited: usize,
+    /// Length of the longest phase 1 and phase 2 path visited.
+    max_depth: usize,
+}
+impl TwoPhaseSearch {
+    /// Returns whether the search is over: it has a solution, and either it's empty or the search
+    /// visited its nodes looking for a shorter one.
+    fn is_done(&self) -> bool {
+        self.best
+            .as_ref()
+            .is_some_and(|best| best.is_empty() || self.node_visited >= self.node_limit)
+    }
+
+    /// Searches for phase 1 solutions of exactly `togo` more moves, and for each of them tries to
+    /// finish with phase 2, keeping the shortest whole solution. Returns true once the search is
+    /// done, see `is_done`.
+    fn phase1(&mut self, twist: usize, flip: usize, slice: usize, togo: usize) -> bool {
+        if self.is_done() {
+            return true;
+        }
+        self.node_visited += 1;
+        self.max_depth = usize::max(self.max_depth, self.phase1_path.len());
+        if togo == 0 {
+            // a phase 1 solution that ends with a phase 2 move was already tried one move earlier
+            let ends_with_phase2_move = self
+                .phase1_path
+                .last()
+                .is_some_and(|m| PHASE2_MOVES.contains(m));
+            return twist == 0
+                && flip == 0
+                && slice == 0
+                && !ends_with_phase2_move
+                && self.phase2();
+        }
+
+        let move_tables = MoveTables::get();
+        let distance_tables = DistanceTables::get();
+        for m in 0..NUM_MOVES {
+            if is_redundant(self.phase1_path.last(), m) {
+                continue;
+            }
+            let twist = move_tables.twist[twist][m] as usize;
+            let flip = move_tables.flip[flip][m] as usize;
+            let slice = move_tables.slice[slice][m] as usize;
+            let h = u8::max(
+                distance_tables.twist_slice[twist * NUM_SLICE + slice],
+                distance_tables.flip_slice[flip * NUM_SLICE + slice],
+            ) as usize;
+            if h >= togo {
+                continue;
+            }
+
+            self.phase1_path.push(m);
+            if self.phase1(twist, flip, slice, togo - 1) {
+                return true;
+            }
+            self.phase1_path.pop();
+        }
+        false
+    }
+
+    /// Tries to solve the cube reached by the current phase 1 solution with the moves left, and
+    /// keeps the whole solution if it's found. Returns whether the search is done.
+    fn phase2(&mut self) -> bool {
+        let tables = Phase2Tables::get();
+        let cube = self
+            .phase1_path
+            .iter()
+            .fold(self.cube, |cube, &m| cube.multiply(&move_cube(m)));
+        let (corner_perm, ud_edge_perm, slice_perm) =
+            (cube.corner_perm(), cube.ud_edge_perm(), cube.slice_perm());
+        let h = u8::max(
+            tables.corner_slice[corner_perm * NUM_SLICE_PERM + slice_perm],
+            tables.edge_slice[ud_edge_perm * NUM_SLICE_PERM + slice_perm],
+        ) as usize;
+        // a shorter solution may have been found since this phase 1 length was started
+        let Some(max_depth) = self.max_length.checked_sub(self.phase1_path.len()) else {
+            return false;
+        };
+        let found = (h..=max_depth)
+            .any(|depth| self.phase2_search(corner_perm, ud_edge_perm, slice_perm, depth));
+        if found {
+            let solution: Vec<usize> = self
+                .phase1_path
+                .iter()
+                .chain(self.phase2_path.iter())
+                .copied()
+                .collect();
+            log::debug!(
+                "The two-phase algorithm found a solution with {} turns in phase 1 and {} in phase 2",
+                self.phase1_path.len(),
+                self.phase2_path.len()
+            );
+            if self.best.is_none() {
+                self.node_limit = self.node_visited + IMPROVEMENT_NODES;
+            }
+            self.max_length = solution.len().saturating_sub(1);
+            self.best = Some(solution);
+            self.phase2_path.clear();
+        }
+        self.is_done()
+    }
+
+    fn phase2_search(
+        &mut self,
+        corner_perm: usize,
+        ud_edge_perm: usize,
+        slice_perm: usize,
+        togo: usize,
+    ) -> bool {
+        self.node_visited += 1;
+        let depth = self.phase1_path.len() + self.phase2_path.len();
+        self.max_depth = usize::max(self.max_depth, depth);
+        if togo == 0 {
+            return corner_perm == 0 && ud_edge_perm == 0 && slice_perm == 0;
+        }
+
+        let tables = Phase2Tables::get();
+        for (i, &m) in PHASE2_MOVES.iter().enumerate() {
+            let prev = self.phase2_path.last().or(self.phase1_path.last());
+            if is_redundant(prev, m) {
+                continue;
+            }
+            let corner_perm = tables.corner_perm[corner_perm][i] as usize;
+            let ud_edge_perm = tables.ud_edge_perm[ud_edge_perm][i] as usize;
+            let slice_perm = tables.slice_perm[slice_perm][i] as usize;
+            let h = u8::max(
+                tables.corner_slice[corner_perm * NUM_SLICE_PERM + slice_perm],
+                tables.edge_slice[ud_edge_perm * NUM_SLICE_PERM + slice_perm],
+            ) as usize;
+            if h >= togo {
+                continue;
+            }
+
+            self.phase2_path.push(m);
+            if self.phase2_search(corner_perm, ud_edge_perm, slice_perm, togo - 1) {
+                return true;
+            }
+            self.phase2_path.pop();
+        }
+        false
+    }
+}
+
+/// Solves a 3x3 with Kociemba's two-phase algorithm, in the half-turn metric.
+///
+/// Phase 1 brings the cube into the subgroup generated by U, D, R2, F2, L2 and B2, where the
+/// orientations are solved and the UD-slice edges are in the slice. Phase 2 then solves it with
+/// those moves only. Both phases are IDA* searches over coordinates, pruned with exact distance
+/// tables of pairs of coordinates. Phase 1 solutions are tried from the shortest, until one of
+/// them can be finished in at most `max_length` moves in total. The search then goes on with
+/// longer phase 1 solutions that could give a shorter whole solution, for `IMPROVEMENT_NODES`
+/// more nodes. The solution is optimal if the search ends before that.
+///
+/// The solution is checked with `SearchResult::verify` if `verify` is true, see
+/// `SearchConfig::verify`.
+///
+/// Returns `None` if `cube` isn't a 3x3, isn't solvable, or has no solution within
+/// `max_length` moves.
+pub fn solve_two_phase(cube: &Cube, max_length: usize, verify: bool) -> Option<SearchResult> {
+    let start_time = Instant::now();
+    if cube.size() != 3 {
+        return None;
+    }
+    let cubie_cube = CubieCube::from_cube(cube)?;
+    if !cubie_cube.is_solvable() {
+        return None;
+    }
+
+    let distance_tables = DistanceTables::get();
+    let (twist, flip, slice) = (cubie_cube.twist(), cubie_cube.flip(), cubie_cube.slice());
+    let h = u8::max(
+        distance_tables.twist_slice[twist * NUM_SLICE + slice],
+        distance_tables.flip_slice[flip * NUM_SLICE + slice],
+    ) as usize;
+
+    let mut search = TwoPhaseSearch {
+        cube: cubie_cube,
+        max_length,
+        phase1_path: Vec::new(),
+        phase2_path: Vec::new(),
+        best: None,
+        node_limit: usize::MAX,
+        node_visited: 0,
+        max_depth: 0,
+    };
+    // the nodes visited by the search of every phase 1 length
+    let mut iteration_node_visited = Vec::new();
+    let mut depth = h;
+    let mut stopped = false;
+    // `max_length` shrinks with every solution found
+    while depth <= search.max_length {
+        let node_visited_before = search.node_visited;
+        stopped = search.phase1(twist, flip, slice, depth);
+        iteration_node_visited.push(search.node_visited - node_visited_before);
+        if stopped {
+            break;
+        }
+        depth += 1;
+    }
+    let Some(best) = search.best else {
+        log::debug!("The two-phase algorithm found no solution within {max_length} turns");
+        return None;
+    };
+
+    let solution: Vec<Turn> = best.iter().map(|&m| move_turn(m)).collect();
+    let mut result = SearchResult {
+        solution_len: Some(solution.len()),
+        bound: solution.len(),
+        iterations: iteration_node_visited.len(),
+        iteration_node_visited,
+        max_depth: search.max_depth,
+        peak_stack_size: search.max_depth + 1,
+        solution: Some(solution.clone()),
+        solutions: vec![solution],
+        node_visited: search.node_visited,
+        wall_time: start_time.elapsed(),
+        status: SearchStatus::Solved,
+        weight: 1.0,
+        // every phase 1 length that could give a shorter solution was searched unless it stopped
+        possibly_suboptimal: stopped && !best.is_empty(),
+        verified: false,
+    };
+    if verify {
+        result.verify(cube, &Goal::Solved);
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn solves_seeded_scrambles_within_the_max_length() {
+        for seed in 0..3 {
+            let mut cube = Cube::new(3);
+            cube.scramble_with_rng(25, &mut StdRng::seed_from_u64(seed));
+            let result = solve_two_phase(&cube, DEFAULT_MAX_LENGTH, true).expect("no solution");
+            let solution = result.solution.expect("no solution");
+            assert!(solution.len() <= DEFAULT_MAX_LENGTH);
+            cube.apply_algorithm(solution);
+            assert!(cube.is_solved());
+        }
+    }
+
+    #[test]
+    fn finds_the_inverse_of_a_short_scramble() {
+        let mut cube = Cube::new(3);
+        cube.apply_algorithm(Turn::parse_algorithm(vec!["R", "U", "F'"]).unwrap());
+        let result = solve_two_phase(&cube, DEFAULT_MAX_LENGTH, true).expect("no solution");
+        assert_eq!(result.solution_len, Some(3));
+        assert!(!result.possibly_suboptimal);
+    }
+
+    #[test]
+    fn solved_cube_needs_no_turns() {
+        let result = solve_two_phase(&Cube::new(3), DEFAULT_MAX_LENGTH, true).expect("no solution");
+        assert_eq!(result.solution, Some(Vec::new()));
+    }
+
+    #[test]
+    fn only_solves_the_3x3() {
+        assert!(solve_two_phase(&Cube::new(2), DEFAULT_MAX_LENGTH, true).is_none());
+        assert!(solve_two_phase(&Cube::new(4), DEFAULT_MAX_LENGTH, true).is_none());
+    }
+}
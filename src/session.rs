@@ -0,0 +1,175 @@
+use std::{
+    io,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use csv::Writer;
+
+use rubiks::cube::Turn;
+
+/// Something that happened to the cube during a session of the app, see `SessionLog`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEvent {
+    /// The cube was replaced by a solved cube of this size.
+    Reset(usize),
+    /// The cube was replaced by one with these colors, see `Cube::facelet_string`.
+    Loaded(String),
+    Scramble(Vec<Turn>),
+    /// The next scramble is the daily scramble of this date, see `ScrambleOptions::daily`.
+    Daily(String),
+    /// Turns applied with their keys or typed as an algorithm.
+    Turns(Vec<Turn>),
+    /// A solution found by a search, which isn't applied by finding it.
+    Solution(Vec<Turn>),
+    /// Turns of a solution played on the cube.
+    Played(Vec<Turn>),
+    /// Turns applied to undo or redo others.
+    Undo(Vec<Turn>),
+    Redo(Vec<Turn>),
+    /// The cube was solved by hand after a scramble in this time, see `SessionLog::solve_time`.
+    Solved(Duration),
+}
+impl SessionEvent {
+    /// Returns the name of the event in the log file.
+    fn name(&self) -> &'static str {
+        match self {
+            SessionEvent::Reset(_) => "reset",
+            SessionEvent::Loaded(_) => "loaded",
+            SessionEvent::Scramble(_) => "scramble",
+            SessionEvent::Daily(_) => "daily",
+            SessionEvent::Turns(_) => "turns",
+            SessionEvent::Solution(_) => "solution",
+            SessionEvent::Played(_) => "played",
+            SessionEvent::Undo(_) => "undo",
+            SessionEvent::Redo(_) => "redo",
+            SessionEvent::Solved(_) => "solved",
+        }
+    }
+
+    /// Returns the turns that the event applied to the cube.
+    fn applied_turns(&self) -> &[Turn] {
+        match self {
+            SessionEvent::Reset(_)
+            | SessionEvent::Loaded(_)
+            | SessionEvent::Daily(_)
+            | SessionEvent::Solution(_)
+            | SessionEvent::Solved(_) => &[],
+            SessionEvent::Scramble(algo)
+            | SessionEvent::Turns(algo)
+            | SessionEvent::Played(algo)
+            | SessionEvent::Undo(algo)
+            | SessionEvent::Redo(algo) => algo,
+        }
+    }
+}
+
+/// Every event of a session of the app, with the time since the session started, so that the
+/// session can be replayed or reviewed from its log file.
+pub struct SessionLog {
+    start: Instant,
+    events: Vec<(Duration, SessionEvent)>,
+}
+impl SessionLog {
+    /// Starts a session with a solved cube of size `size`.
+    pub fn new(size: usize) -> SessionLog {
+        SessionLog {
+            start: Instant::now(),
+            events: vec![(Duration::ZERO, SessionEvent::Reset(size))],
+        }
+    }
+
+    pub fn log(&mut self, event: SessionEvent) {
+        self.events.push((self.start.elapsed(), event));
+    }
+
+    /// Returns the last `n` turns applied to the cube since it was last reset, loaded or
+    /// scrambled.
+    pub fn recent_turns(&self, n: usize) -> Vec<Turn> {
+        let since_start = self
+            .events
+            .iter()
+            .rev()
+            .take_while(|(_, event)| {
+                !matches!(
+                    event,
+                    SessionEvent::Reset(_) | SessionEvent::Loaded(_) | SessionEvent::Scramble(_)
+                )
+            })
+            .collect::<Vec<_>>();
+        let turns: Vec<Turn> = since_start
+            .iter()
+            .rev()
+            .flat_map(|(_, event)| event.applied_turns())
+            .copied()
+            .collect();
+        turns[turns.len().saturating_sub(n)..].to_vec()
+    }
+
+    /// Returns the last scramble, or `None` if the cube was reset or loaded since.
+    pub fn last_scramble(&self) -> Option<&[Turn]> {
+        let last_start = self.events.iter().rev().find(|(_, event)| {
+            matches!(
+                event,
+                SessionEvent::Reset(_) | SessionEvent::Loaded(_) | SessionEvent::Scramble(_)
+            )
+        });
+        match last_start {
+            Some((_, SessionEvent::Scramble(algo))) => Some(algo),
+            _ => None,
+        }
+    }
+
+    /// Returns how long the last scramble took to solve by hand, from the scramble to the last
+    /// event. Returns `None` if the cube was reset or loaded since, if a solution was played on it or
+    /// if the solve was already timed. Whether the cube is solved is up to the caller.
+    pub fn solve_time(&self) -> Option<Duration> {
+        let scramble = self.events.iter().rposition(|(_, event)| {
+            matches!(
+                event,
+                SessionEvent::Reset(_) | SessionEvent::Loaded(_) | SessionEvent::Scramble(_)
+            )
+        })?;
+        if !matches!(self.events[scramble].1, SessionEvent::Scramble(_)) {
+            return None;
+        }
+        let since_scramble = &self.events[scramble + 1..];
+        if since_scramble
+            .iter()
+            .any(|(_, event)| matches!(event, SessionEvent::Played(_) | SessionEvent::Solved(_)))
+        {
+            return None;
+        }
+        let (start, _) = self.events[scramble];
+        let (end, _) = since_scramble.last()?;
+        Some(end.saturating_sub(start))
+    }
+
+    /// Writes the session to a CSV file at `path`, with the seconds since the start, the name and
+    /// the turns of every event. The size of the cube of a reset, the colors of a loaded cube, the
+    /// date of a daily scramble and the time of a solve take the place of their turns.
+    pub fn write_csv(&self, path: &Path) -> io::Result<()> {
+        let mut csv_writer = Writer::from_path(path)?;
+        csv_writer.write_record(["seconds", "event", "turns"])?;
+        for (time, event) in &self.events {
+            let turns = match event {
+                SessionEvent::Reset(size) => format!("{size}x{size}"),
+                SessionEvent::Loaded(facelets) => facelets.clone(),
+                SessionEvent::Daily(date) => date.clone(),
+                SessionEvent::Solved(time) => format!("{:.3}", time.as_secs_f64()),
+                SessionEvent::Scramble(algo)
+                | SessionEvent::Turns(algo)
+                | SessionEvent::Solution(algo)
+                | SessionEvent::Played(algo)
+                | SessionEvent::Undo(algo)
+                | SessionEvent::Redo(algo) => Turn::algo_string(algo),
+            };
+            csv_writer.write_record([
+                format!("{:.3}", time.as_secs_f64()),
+                event.name().to_string(),
+                turns,
+            ])?;
+        }
+        csv_writer.flush()
+    }
+}
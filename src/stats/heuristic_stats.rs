@@ -7,7 +7,7 @@ use std::{
 
 use csv::Writer;
 
-use crate::cube::{Cube, Turn};
+use rubiks::cube::{Cube, Turn};
 
 const NUM_PER_SCRAMBLE: usize = 1000;
 const NUM_MOVE_PER_SCRAMBLE_RANGE: Range<usize> = 1..30;
@@ -50,7 +50,6 @@ pub fn check_heuristic(heuristic_function: &dyn Fn(&Cube) -> f32, heuristic_func
     // open the file in write or append mode depending on whether it exists
     let file = if file_exists {
         OpenOptions::new()
-            .write(true)
             .append(true)
             .open(CSV_FILE_PATH)
             .expect("Can't open file")
@@ -62,7 +61,7 @@ pub fn check_heuristic(heuristic_function: &dyn Fn(&Cube) -> f32, heuristic_func
     let mut csv_writer = Writer::from_writer(file);
     if !file_exists {
         csv_writer
-            .write_record(&[
+            .write_record([
                 "Scramble",
                 "Scramble Length",
                 "Heuristic Type",
@@ -1,28 +1,52 @@
 use std::{
     fs::{File, OpenOptions},
     io::{stdout, Write},
+    num::NonZeroUsize,
     ops::Range,
+    path::Path,
 };
 
-use csv::Writer;
+use csv::{ReaderBuilder, StringRecord, Writer};
 
-use crate::{
-    cube::{Cube, Turn},
-    search::{idastar, SearchResult},
+use rubiks::{
+    cube::Cube,
+    search::{idastar, report::SolveReport, SearchConfig, Solver},
 };
 
 const NUM_PER_SCRAMBLE: usize = 16;
 const NUM_MOVE_PER_SCRAMBLE_RANGE: Range<usize> = 1..10;
 const CSV_FILE_PATH: &str = "idastar_stats.csv";
+/// Columns of the CSV file, in order. The heuristic is named like `--heuristic` takes it, or
+/// `none`, and its cache size is empty if it isn't cached.
+const HEADER: [&str; 14] = [
+    "Scramble",
+    "Scramble Length",
+    "Solution",
+    "Solution Length",
+    "Heuristic Type",
+    "Heuristic Cache Size",
+    "Wall Time (ns)",
+    "Node Visited",
+    "Iterations",
+    "Final Bound",
+    "Max Depth",
+    "Nodes Per Second",
+    "Peak Stack Size",
+    "Iteration Nodes",
+];
 
 struct Data {
-    scramble: Vec<Turn>,
     scramble_len: usize,
-    search_result: SearchResult,
+    heuristic_cache_size: Option<NonZeroUsize>,
+    report: SolveReport,
 }
 
 #[allow(dead_code)]
-pub fn check_idastar(heuristic_function: &dyn Fn(&Cube) -> f32, heuristic_function_name: &str) {
+pub fn check_idastar(
+    heuristic_function: &dyn Fn(&Cube) -> f32,
+    heuristic_cache_size: Option<NonZeroUsize>,
+    heuristic_name: &str,
+) {
     let mut data = Vec::new();
     for m in NUM_MOVE_PER_SCRAMBLE_RANGE {
         for i in 0..NUM_PER_SCRAMBLE {
@@ -30,66 +54,114 @@ pub fn check_idastar(heuristic_function: &dyn Fn(&Cube) -> f32, heuristic_functi
             stdout().flush().expect("Error printing progress");
             let mut cube = Cube::new(2);
             let scramble = cube.scramble(m);
-            let search_result = idastar(cube, heuristic_function, false);
+            let config = SearchConfig {
+                heuristic_cache_size,
+                ..SearchConfig::default()
+            };
+            let search_result = idastar(cube, heuristic_function, &config);
             data.push(Data {
-                scramble,
                 scramble_len: m,
-                search_result,
+                heuristic_cache_size,
+                report: SolveReport::new(
+                    2,
+                    &scramble,
+                    Solver::IdaStar.name(),
+                    heuristic_name,
+                    Some(&search_result),
+                    search_result.wall_time,
+                ),
             });
         }
     }
     println!("\nWriting data to file");
 
-    // determine if the file exists
-    let file_exists = std::path::Path::new(CSV_FILE_PATH).exists();
-
-    // open the file in write or append mode depending on whether it exists
-    let file = if file_exists {
-        OpenOptions::new()
-            .write(true)
-            .append(true)
-            .open(CSV_FILE_PATH)
-            .expect("Can't open file")
-    } else {
-        File::create(CSV_FILE_PATH).expect("Can't create file")
-    };
-
-    // write the header row if the file is newly created
-    let mut csv_writer = Writer::from_writer(file);
-    if !file_exists {
-        csv_writer
-            .write_record(&[
-                "Scramble",
-                "Scramble Length",
-                "Solution",
-                "Solution Length",
-                "Heuristic Type",
-                "Wall Time (ns)",
-                "Node Visited",
-            ])
-            .expect("Error when writing header");
-    }
+    let mut csv_writer = open_csv();
 
     // write data to csv
     for d in data {
+        let metrics = d
+            .report
+            .metrics
+            .expect("Error: IDA* always returns metrics");
         csv_writer
             .write_record([
-                Turn::algo_string(&d.scramble),
+                d.report.scramble,
                 d.scramble_len.to_string(),
-                match &d.search_result.solution {
-                    None => "".to_string(),
-                    Some(algo) => Turn::algo_string(algo),
-                },
-                match &d.search_result.solution_len {
+                d.report.solution.unwrap_or_default(),
+                match metrics.solution_length {
                     None => "".to_string(),
                     Some(l) => l.to_string(),
                 },
-                heuristic_function_name.to_string(),
-                d.search_result.wall_time.as_nanos().to_string(),
-                d.search_result.node_visited.to_string(),
+                d.report.heuristic,
+                d.heuristic_cache_size
+                    .map_or(String::new(), |size| size.to_string()),
+                format!("{:.0}", d.report.timings.wall_time_ms * 1e6),
+                metrics.node_visited.to_string(),
+                metrics.iterations.to_string(),
+                metrics.bound.to_string(),
+                metrics.max_depth.to_string(),
+                format!("{:.0}", metrics.nodes_per_second),
+                metrics.peak_stack_size.to_string(),
+                metrics
+                    .iteration_node_visited
+                    .iter()
+                    .map(|n| n.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" "),
             ])
             .expect("Error when trying to write row");
     }
     csv_writer.flush().expect("Error when flushing writer");
     println!("Done!");
 }
+
+/// Opens the CSV file to append rows to, writing `HEADER` first if the file is new.
+///
+/// A file written with other columns, by an older version, is rewritten with `HEADER` first: its
+/// rows keep the fields of the columns that are still there, and leave the new ones empty.
+fn open_csv() -> Writer<File> {
+    let path = Path::new(CSV_FILE_PATH);
+    if !path.exists() {
+        let mut csv_writer = Writer::from_path(path).expect("Can't create file");
+        csv_writer
+            .write_record(HEADER)
+            .expect("Error when writing header");
+        return csv_writer;
+    }
+
+    let mut reader = ReaderBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .expect("Can't open file");
+    let old_header = reader.headers().expect("Error when reading header").clone();
+    if old_header.iter().eq(HEADER) {
+        let file = OpenOptions::new()
+            .append(true)
+            .open(path)
+            .expect("Can't open file");
+        return Writer::from_writer(file);
+    }
+    let rows: Vec<StringRecord> = reader
+        .records()
+        .collect::<Result<_, _>>()
+        .expect("Error when reading rows");
+
+    println!("Rewriting {CSV_FILE_PATH} with the new columns");
+    let mut csv_writer = Writer::from_path(path).expect("Can't create file");
+    csv_writer
+        .write_record(HEADER)
+        .expect("Error when writing header");
+    for row in rows {
+        let fields = HEADER.map(|column| {
+            old_header
+                .iter()
+                .position(|old_column| old_column == column)
+                .and_then(|i| row.get(i))
+                .unwrap_or_default()
+        });
+        csv_writer
+            .write_record(fields)
+            .expect("Error when trying to write row");
+    }
+    csv_writer
+}
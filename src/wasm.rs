@@ -0,0 +1,190 @@
+//! The cube and its solver for JavaScript, with the `wasm` feature. See `web/index.html` for how
+//! to build it and a page that uses it.
+
+use std::time::Duration;
+
+use wasm_bindgen::prelude::*;
+use web_sys::CanvasRenderingContext2d;
+
+use crate::{
+    cube::rendering::{CameraView, CubeRender},
+    cube::{Cube, Turn},
+    search::{self, coordinate, two_phase, MoveSet, SearchConfig},
+};
+
+/// How long IDA* may run when there's no faster solver for the size, since it blocks the page
+/// meanwhile.
+const IDASTAR_TIME_LIMIT: Duration = Duration::from_secs(5);
+/// Nodes that IDA* may visit when there's no faster solver for the size, in case the clock of the
+/// browser is coarse.
+const IDASTAR_NODE_BUDGET: usize = 20_000_000;
+
+/// A cube for JavaScript, with its view drawn on a canvas by `draw`.
+#[wasm_bindgen]
+pub struct WebCube {
+    cube: Cube,
+    /// Only used for its view and its projection, see `CubeRender::projected_stickers`.
+    cube_render: CubeRender,
+}
+#[wasm_bindgen]
+impl WebCube {
+    /// Creates a solved cube of size `size`, seen from the up, front and right corner.
+    #[wasm_bindgen(constructor)]
+    pub fn new(size: usize) -> WebCube {
+        let cube = Cube::new(size);
+        let mut cube_render = CubeRender::new(&cube, 1.0, 1.0, 1, 1);
+        cube_render.set_view(CameraView::Corner);
+        WebCube { cube, cube_render }
+    }
+
+    /// Returns the size of the cube, like 3 for the 3x3.
+    pub fn size(&self) -> usize {
+        self.cube.size()
+    }
+
+    /// Returns true if every face is a single color.
+    #[wasm_bindgen(js_name = isSolved)]
+    pub fn is_solved(&self) -> bool {
+        self.cube.is_solved()
+    }
+
+    /// Applies the turns of `algo`, like "R U R' U'". Returns false and leaves the cube as it is
+    /// if they can't be parsed or turn layers the cube doesn't have.
+    pub fn apply(&mut self, algo: &str) -> bool {
+        let Some(algo) = Turn::parse_algorithm(algo.split_whitespace().collect()) else {
+            return false;
+        };
+        if algo.iter().any(|turn| turn.get_layer() > self.cube.size()) {
+            return false;
+        }
+        self.cube.apply_algorithm(algo);
+        self.cube_render.update_colors(&self.cube);
+        true
+    }
+
+    /// Scrambles the cube with `k` random turns and returns them.
+    pub fn scramble(&mut self, k: usize) -> String {
+        let algo = self.cube.scramble(k);
+        self.cube_render.update_colors(&self.cube);
+        Turn::algo_string(&algo)
+    }
+
+    /// Resets the cube to a solved one of the same size.
+    pub fn reset(&mut self) {
+        self.cube = Cube::new(self.cube.size());
+        self.cube_render.update_colors(&self.cube);
+    }
+
+    /// Returns the turns that solve the cube, without applying them, or `undefined` if no
+    /// solution was found.
+    pub fn solve(&self) -> Option<String> {
+        solve_cube(&self.cube).ok()
+    }
+
+    /// Rotates the view by `yaw` and `pitch`, in radians.
+    pub fn rotate(&mut self, yaw: f32, pitch: f32) {
+        self.cube_render.rotate_yaw(yaw);
+        self.cube_render.rotate_pitch(pitch);
+    }
+
+    /// Draws the view of the cube on `context`, filling a square of `size` pixels in its top left
+    /// corner.
+    pub fn draw(&self, context: &CanvasRenderingContext2d, size: f64) {
+        // the image is as big as the sticker size, so find the sticker size that fills `size`
+        let (unit_size, _) = self.cube_render.projected_stickers(1.0);
+        let sticker_size = size as f32 / unit_size;
+        let (_, stickers) = self.cube_render.projected_stickers(sticker_size);
+
+        context.clear_rect(0.0, 0.0, size, size);
+        context.set_stroke_style_str("black");
+        context.set_line_width(sticker_size as f64 / 15.0);
+        context.set_line_join("round");
+        for (corners, (r, g, b)) in stickers {
+            context.begin_path();
+            let [(x, y), rest @ ..] = corners;
+            context.move_to(x as f64, y as f64);
+            for (x, y) in rest {
+                context.line_to(x as f64, y as f64);
+            }
+            context.close_path();
+            context.set_fill_style_str(&format!("#{r:02x}{g:02x}{b:02x}"));
+            context.fill();
+            context.stroke();
+        }
+    }
+}
+
+/// Returns the turns that solve `cube`, or the error that JavaScript gets if it can't be solved or
+/// no solution was found.
+///
+/// Browsers can't run the search on several threads, so it's `idastar` instead of the
+/// `parallel_idastar` of the terminal app when there's no faster solver for the size. It runs on
+/// the main thread of the page, so it gives up after `IDASTAR_TIME_LIMIT` or
+/// `IDASTAR_NODE_BUDGET` nodes, whichever comes first.
+fn solve_cube(cube: &Cube) -> Result<String, JsError> {
+    if cube.is_solvable() == Some(false) {
+        return Err(JsError::new("Unsolvable cube"));
+    }
+    let config = SearchConfig {
+        move_set: MoveSet::default_for(cube.size()),
+        time_limit: Some(IDASTAR_TIME_LIMIT),
+        node_budget: Some(IDASTAR_NODE_BUDGET),
+        ..SearchConfig::default()
+    };
+    let fast_result = match cube.size() {
+        2 => coordinate::solve_2x2(cube, &config),
+        3 => two_phase::solve_two_phase(cube, two_phase::DEFAULT_MAX_LENGTH, config.verify),
+        _ => None,
+    };
+    let result =
+        fast_result.unwrap_or_else(|| search::idastar(cube.clone(), &search::single_l0, &config));
+    result
+        .solution
+        .map(|algo| Turn::algo_string(&algo))
+        .ok_or_else(|| JsError::new("No solution found"))
+}
+
+/// Returns the cube of the letters of the colors of its facelets, see `Cube::from_facelets`, or
+/// the error that JavaScript gets.
+fn parse_facelets(facelets: &str) -> Result<Cube, JsError> {
+    Cube::from_facelets(facelets).ok_or_else(|| JsError::new("Invalid facelets"))
+}
+
+/// Returns the turns of `algo`, like "R U R' U'", or the error that JavaScript gets if they can't
+/// be parsed or turn layers that a cube of size `size` doesn't have.
+fn parse_algorithm(algo: &str, size: usize) -> Result<Vec<Turn>, JsError> {
+    Turn::parse_algorithm(algo.split_whitespace().collect())
+        .filter(|algo| algo.iter().all(|turn| turn.get_layer() <= size))
+        .ok_or_else(|| JsError::new("Invalid turns"))
+}
+
+// the solver without a view, for the pages that draw the cube themselves. the cubes are the
+// letters of the colors of their facelets, see `Cube::facelet_string`
+
+/// Returns the turns that solve the cube of `facelets`, or an empty string if it's already
+/// solved. Throws if the facelets are invalid or no solution was found.
+#[wasm_bindgen]
+pub fn solve(facelets: &str) -> Result<String, JsError> {
+    solve_cube(&parse_facelets(facelets)?)
+}
+
+/// Returns the facelets of the cube of `facelets` after the turns of `algo`. Throws if either
+/// is invalid.
+#[wasm_bindgen]
+pub fn apply(facelets: &str, algo: &str) -> Result<String, JsError> {
+    let mut cube = parse_facelets(facelets)?;
+    cube.apply_algorithm(parse_algorithm(algo, cube.size())?);
+    Ok(cube.facelet_string())
+}
+
+/// Returns `n` random turns of a cube of size `size`, which `apply` scrambles a cube with.
+#[wasm_bindgen]
+pub fn scramble(size: usize, n: usize) -> String {
+    Turn::algo_string(&Cube::new(size).scramble(n))
+}
+
+/// Returns the facelets of a solved cube of size `size`.
+#[wasm_bindgen]
+pub fn solved(size: usize) -> String {
+    Cube::new(size).facelet_string()
+}